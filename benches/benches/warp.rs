@@ -0,0 +1,57 @@
+//! Reprojection throughput benchmarks.
+//!
+//! Run with `cargo bench` from the `benches` directory.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use gdal::raster::{
+    create_and_reproject_to_buffer, CreateReprojectOptions, GdalWarpOptions, NumThreads,
+    WarpResampleAlg,
+};
+use gdal::spatial_ref::SpatialRef;
+use gdal::Dataset;
+
+/// Output sizes (in pixels per side) to warp the fixture to.
+const SIZES: [usize; 3] = [256, 512, 1024];
+/// Worker thread counts to warp with.
+const THREADS: [usize; 2] = [1, 4];
+/// Working memory limit for the warp kernel, in bytes.
+const MEMORY_LIMIT: usize = 64 << 20;
+
+fn reproject_throughput(c: &mut Criterion) {
+    let src = Dataset::open(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../fixtures/m_3607824_se_17_1_20160620_sub.tif"
+    ))
+    .unwrap();
+    let dst_srs = SpatialRef::from_epsg(4326).unwrap();
+
+    let mut group = c.benchmark_group("create_and_reproject_to_buffer");
+    for size in SIZES {
+        group.throughput(Throughput::Elements((size * size) as u64));
+        for threads in THREADS {
+            let warp_options = GdalWarpOptions::builder()
+                .resampling_alg(WarpResampleAlg::Bilinear)
+                .memory_limit(MEMORY_LIMIT)
+                .num_threads(NumThreads::Count(threads))
+                .unwrap()
+                .build();
+            let mut options = CreateReprojectOptions::new();
+            options.with_warp_options(warp_options);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{threads}_threads"), size),
+                &size,
+                |b, &size| {
+                    b.iter(|| {
+                        create_and_reproject_to_buffer::<u8>(&src, &dst_srs, (size, size), &options)
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, reproject_throughput);
+criterion_main!(benches);
@@ -26,10 +26,12 @@
 use gdal_sys::{CPLErr, CPLErrorNum, CPLGetErrorHandlerUserData};
 use libc::{c_char, c_void};
 
-use crate::errors::{CplErrType, Result};
+use crate::errors::{CplErrType, GdalError, Result};
 use crate::utils::_string;
 use once_cell::sync::Lazy;
+use std::cell::RefCell;
 use std::ffi::CString;
+use std::marker::PhantomData;
 use std::sync::Mutex;
 
 /// Set a GDAL library configuration option
@@ -113,6 +115,40 @@ pub fn clear_thread_local_config_option(key: &str) -> Result<()> {
     Ok(())
 }
 
+const NUM_THREADS_OPTION: &str = "GDAL_NUM_THREADS";
+
+/// Set the number of worker threads GDAL uses for multi-threaded I/O and processing, e.g.
+/// to fetch `/vsicurl/` ranges or to compress GeoTIFF blocks, through the `GDAL_NUM_THREADS`
+/// configuration option.
+///
+/// This is independent of the threads of the warp kernel, set with
+/// [`GdalWarpOptions::with_num_threads`](crate::raster::GdalWarpOptions::with_num_threads).
+pub fn set_num_io_threads(threads: usize) -> Result<()> {
+    if threads == 0 {
+        return Err(GdalError::BadArgument(
+            "at least one I/O thread is needed".into(),
+        ));
+    }
+    set_config_option(NUM_THREADS_OPTION, &threads.to_string())
+}
+
+/// Get the number of worker threads GDAL uses for multi-threaded I/O and processing, as set
+/// with [`set_num_io_threads`] or in the `GDAL_NUM_THREADS` configuration option.
+///
+/// `ALL_CPUS` is resolved to the number of CPUs. Returns `None` if the option is not set.
+pub fn num_io_threads() -> Result<Option<usize>> {
+    let value = get_config_option(NUM_THREADS_OPTION, "")?;
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if value.eq_ignore_ascii_case("ALL_CPUS") {
+        return Ok(Some(unsafe { gdal_sys::CPLGetNumCPUs() } as usize));
+    }
+    value.parse().map(Some).map_err(|_| {
+        GdalError::BadArgument(format!("invalid {NUM_THREADS_OPTION} option: {value}"))
+    })
+}
+
 type ErrorCallbackType = dyn FnMut(CplErrType, i32, &str) + 'static + Send;
 // We have to double-`Box` the type because we need two things:
 // 1. A stable pointer for moving the data in and out of the `Mutex`. This is done by the outer `Box`.
@@ -188,6 +224,96 @@ pub fn remove_error_handler() {
     callback_lock.take();
 }
 
+/// A GDAL error recorded by an [`ErrorCapture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedError {
+    pub class: CplErrType,
+    pub number: i32,
+    pub message: String,
+}
+
+/// Scoped capture of the GDAL errors raised on the current thread.
+///
+/// While it lives, errors are recorded into a local buffer instead of being passed to the
+/// error handler, and once it is dropped, the previous handler is restored, as is GDAL's
+/// "last error" state. Errors raised within the scope thus stay within it, which keeps
+/// e.g. the requests served by different threads of a server from seeing each other's errors.
+///
+/// ```rust, no_run
+/// use gdal::config::ErrorCapture;
+/// use gdal::Dataset;
+///
+/// let capture = ErrorCapture::new();
+/// assert!(Dataset::open("no_such_file.tif").is_err());
+/// assert!(!capture.errors().is_empty());
+/// ```
+pub struct ErrorCapture {
+    errors: Box<RefCell<Vec<CapturedError>>>,
+    previous: (CPLErr::Type, CPLErrorNum, CString),
+    // Make !Sync and !Send, as the error handler stack is per thread.
+    _private: PhantomData<*mut c_void>,
+}
+
+impl ErrorCapture {
+    /// Start capturing the errors raised on the current thread.
+    pub fn new() -> Self {
+        unsafe extern "C" fn capture_handler(
+            error_type: CPLErr::Type,
+            error_num: CPLErrorNum,
+            error_msg_ptr: *const c_char,
+        ) {
+            let errors = &*(CPLGetErrorHandlerUserData() as *const RefCell<Vec<CapturedError>>);
+            errors.borrow_mut().push(CapturedError {
+                class: error_type.into(),
+                number: error_num,
+                message: _string(error_msg_ptr),
+            });
+        }
+
+        let previous = unsafe {
+            (
+                gdal_sys::CPLGetLastErrorType(),
+                gdal_sys::CPLGetLastErrorNo(),
+                CString::new(_string(gdal_sys::CPLGetLastErrorMsg())).unwrap_or_default(),
+            )
+        };
+        let errors = Box::new(RefCell::new(Vec::new()));
+        unsafe {
+            gdal_sys::CPLErrorReset();
+            gdal_sys::CPLPushErrorHandlerEx(
+                Some(capture_handler),
+                errors.as_ref() as *const _ as *mut c_void,
+            );
+        }
+        ErrorCapture {
+            errors,
+            previous,
+            _private: PhantomData,
+        }
+    }
+
+    /// The errors captured so far, oldest first.
+    pub fn errors(&self) -> Vec<CapturedError> {
+        self.errors.borrow().clone()
+    }
+}
+
+impl Default for ErrorCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ErrorCapture {
+    fn drop(&mut self) {
+        let (class, number, message) = &self.previous;
+        unsafe {
+            gdal_sys::CPLPopErrorHandler();
+            gdal_sys::CPLErrorSetState(*class, *number, message.as_ptr());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +334,28 @@ mod tests {
         test_set_option_with_embedded_nul_thread_local();
 
         test_clear_option_thread_local();
+
+        test_num_io_threads();
+    }
+
+    fn test_num_io_threads() {
+        // Restored afterwards, e.g. when set in the environment.
+        let previous = get_config_option("GDAL_NUM_THREADS", "").unwrap();
+        clear_config_option("GDAL_NUM_THREADS").unwrap();
+        assert_eq!(num_io_threads().unwrap(), None);
+        set_num_io_threads(4).unwrap();
+        assert_eq!(get_config_option("GDAL_NUM_THREADS", "").unwrap(), "4");
+        assert_eq!(num_io_threads().unwrap(), Some(4));
+        assert!(set_num_io_threads(0).is_err());
+
+        set_config_option("GDAL_NUM_THREADS", "ALL_CPUS").unwrap();
+        assert!(num_io_threads().unwrap().unwrap() >= 1);
+        clear_config_option("GDAL_NUM_THREADS").unwrap();
+        assert_eq!(num_io_threads().unwrap(), None);
+
+        if !previous.is_empty() {
+            set_config_option("GDAL_NUM_THREADS", &previous).unwrap();
+        }
     }
 
     fn test_set_get_option() {
@@ -295,4 +443,36 @@ mod tests {
             "DEFAULT"
         );
     }
+
+    #[test]
+    fn test_error_capture() {
+        use crate::utils::_last_cpl_err;
+        use crate::Dataset;
+
+        unsafe { gdal_sys::CPLErrorReset() };
+        {
+            let capture = ErrorCapture::new();
+            assert!(Dataset::open("no_such_file.tif").is_err());
+            let errors = capture.errors();
+            assert!(!errors.is_empty());
+            assert_eq!(errors[0].class, CplErrType::Failure);
+            assert!(errors[0].message.contains("no_such_file.tif"));
+        }
+        // Nothing leaks out of the capture scope.
+        assert_eq!(unsafe { gdal_sys::CPLGetLastErrorType() }, CPLErr::CE_None);
+        let err = _last_cpl_err(CPLErr::CE_Failure);
+        assert!(!err.to_string().contains("no_such_file.tif"), "{err}");
+
+        // An error raised before the scope is still there afterwards.
+        let msg = CString::new("earlier error").unwrap();
+        unsafe { gdal_sys::CPLErrorSetState(CPLErr::CE_Failure, 1, msg.as_ptr()) };
+        {
+            let capture = ErrorCapture::new();
+            assert!(Dataset::open("no_such_file.tif").is_err());
+            assert!(!capture.errors().is_empty());
+        }
+        let err = _last_cpl_err(CPLErr::CE_Failure);
+        assert!(err.to_string().contains("earlier error"), "{err}");
+        unsafe { gdal_sys::CPLErrorReset() };
+    }
 }
@@ -4,7 +4,7 @@ use gdal_sys::{self, CPLErr, GDALDatasetH, GDALMajorObjectH};
 
 use crate::cpl::CslStringList;
 use crate::errors::*;
-use crate::options::DatasetOptions;
+use crate::options::{DatasetOptions, GdalOpenFlags, GdalOpenMode};
 use crate::raster::RasterCreationOption;
 use crate::utils::{_last_cpl_err, _last_null_pointer_err, _path_to_c_string, _string};
 use crate::{
@@ -69,6 +69,50 @@ impl Dataset {
         Self::_open_ex(path.as_ref(), options)
     }
 
+    /// Open a raster dataset at the given `path`, in read-only or update `mode`.
+    ///
+    /// Fails for vector-only datasets.
+    pub fn open_with_mode<P: AsRef<Path>>(path: P, mode: GdalOpenMode) -> Result<Dataset> {
+        Self::_open_ex(
+            path.as_ref(),
+            DatasetOptions {
+                open_flags: GdalOpenFlags::GDAL_OF_RASTER | mode.into(),
+                ..DatasetOptions::default()
+            },
+        )
+    }
+
+    /// Get the access mode this dataset was opened (or created) with.
+    pub fn open_mode(&self) -> GdalOpenMode {
+        match unsafe { gdal_sys::GDALGetAccess(self.c_dataset) } {
+            access if access == gdal_sys::GDALAccess::GA_Update as i32 => GdalOpenMode::Update,
+            _ => GdalOpenMode::ReadOnly,
+        }
+    }
+
+    /// Close this dataset and open the same file again, with the same driver, in update mode,
+    /// as a raster dataset (see [`open_with_mode`](Self::open_with_mode)).
+    ///
+    /// Fails for datasets not backed by a file, such as in-memory ones.
+    pub fn reopen_as_update(self) -> Result<Dataset> {
+        let path = self.description()?;
+        if path.is_empty() {
+            return Err(GdalError::BadArgument(
+                "cannot reopen a dataset without a file name".into(),
+            ));
+        }
+        let driver = self.driver().short_name();
+        self.close()?;
+        Self::_open_ex(
+            Path::new(&path),
+            DatasetOptions {
+                open_flags: GdalOpenFlags::GDAL_OF_RASTER | GdalOpenMode::Update.into(),
+                allowed_drivers: Some(&[driver.as_str()]),
+                ..DatasetOptions::default()
+            },
+        )
+    }
+
     fn _open_ex(path: &Path, options: DatasetOptions) -> Result<Dataset> {
         crate::driver::_register_drivers();
 
@@ -144,6 +188,9 @@ impl Dataset {
             ptr::null()
         };
 
+        // Clear any error left over by an earlier call, so that it isn't taken as
+        // the reason for a failure to open.
+        unsafe { gdal_sys::CPLErrorReset() };
         let c_dataset = unsafe {
             gdal_sys::GDALOpenEx(
                 c_filename.as_ptr(),
@@ -154,6 +201,10 @@ impl Dataset {
             )
         };
         if c_dataset.is_null() {
+            let err_class = unsafe { gdal_sys::CPLGetLastErrorType() };
+            if err_class != CPLErr::CE_None {
+                return Err(_last_cpl_err(err_class));
+            }
             return Err(_last_null_pointer_err("GDALOpenEx"));
         }
         Ok(Dataset {
@@ -446,6 +497,47 @@ mod tests {
         .unwrap_err();
     }
 
+    #[test]
+    fn test_reopen_as_update() {
+        use crate::raster::Buffer;
+        use crate::test_utils::{SuppressGDALErrorLog, TempFixture};
+
+        let tmp = TempFixture::fixture("tinymarble.tif");
+        let ds = Dataset::open_with_mode(&tmp, GdalOpenMode::ReadOnly).unwrap();
+        assert_eq!(ds.open_mode(), GdalOpenMode::ReadOnly);
+        let zeros = Buffer::new((2, 2), vec![0u8; 4]);
+        {
+            let _nolog = SuppressGDALErrorLog::new();
+            let mut band = ds.rasterband(1).unwrap();
+            assert!(band.write((0, 0), (2, 2), &zeros).is_err());
+        }
+
+        let ds = ds.reopen_as_update().unwrap();
+        assert_eq!(ds.open_mode(), GdalOpenMode::Update);
+        let mut band = ds.rasterband(1).unwrap();
+        band.write((0, 0), (2, 2), &zeros).unwrap();
+        band.set_no_data_value(Some(0.0)).unwrap();
+        drop(ds);
+
+        let ds = Dataset::open(&tmp).unwrap();
+        let band = ds.rasterband(1).unwrap();
+        assert_eq!(band.no_data_value(), Some(0.0));
+        let data = band.read_as::<u8>((0, 0), (2, 2), (2, 2), None).unwrap();
+        assert_eq!(data.data, [0; 4]);
+
+        let mem = crate::DriverManager::get_driver_by_name("MEM")
+            .unwrap()
+            .create("", 1, 1, 1)
+            .unwrap();
+        assert!(mem.reopen_as_update().is_err());
+
+        // Only rasters are opened.
+        let _nolog = SuppressGDALErrorLog::new();
+        let roads = TempFixture::fixture("roads.geojson");
+        assert!(Dataset::open_with_mode(&roads, GdalOpenMode::Update).is_err());
+        assert!(Dataset::open_with_mode(&roads, GdalOpenMode::ReadOnly).is_err());
+    }
+
     #[test]
     fn test_raster_count_on_vector() {
         let ds = Dataset::open(fixture("roads.geojson")).unwrap();
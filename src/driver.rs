@@ -192,7 +192,7 @@ impl Driver {
         )
     }
 
-    fn _create_with_band_type_with_options(
+    pub(crate) fn _create_with_band_type_with_options(
         &self,
         filename: &Path,
         size_x: isize,
@@ -205,7 +205,18 @@ impl Driver {
         for option in options {
             options_c.set_name_value(option.key, option.value)?;
         }
+        self._create(filename, size_x, size_y, bands, data_type, &options_c)
+    }
 
+    fn _create(
+        &self,
+        filename: &Path,
+        size_x: isize,
+        size_y: isize,
+        bands: isize,
+        data_type: GdalDataType,
+        options_c: &CslStringList,
+    ) -> Result<Dataset> {
         let c_filename = _path_to_c_string(filename)?;
         let c_dataset = unsafe {
             gdal_sys::GDALCreate(
@@ -240,6 +251,44 @@ impl Driver {
         )
     }
 
+    /// Create a vector-only dataset, passing it the driver-specific dataset creation `options`,
+    /// as `"KEY=VALUE"` strings.
+    ///
+    /// This is [`create_vector_only`](Self::create_vector_only) with creation options, e.g. to
+    /// create a [GeoPackage](https://gdal.org/drivers/vector/gpkg.html) with a given `VERSION`.
+    /// Layer creation options, such as `SPATIAL_INDEX=YES`, are passed the same way to
+    /// [`Dataset::create_layer`] through [`LayerOptions::options`](crate::vector::LayerOptions::options).
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # fn main() -> gdal::errors::Result<()> {
+    /// use gdal::DriverManager;
+    /// let d = DriverManager::get_driver_by_name("GPKG")?;
+    /// let ds = d.create_vector_only_with_options("/tmp/foo.gpkg", &["VERSION=1.2"])?;
+    /// assert_eq!(ds.raster_count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_vector_only_with_options<P: AsRef<Path>>(
+        &self,
+        filename: P,
+        options: &[&str],
+    ) -> Result<Dataset> {
+        let mut options_c = CslStringList::new();
+        for option in options {
+            options_c.add_string(option)?;
+        }
+        self._create(
+            filename.as_ref(),
+            0,
+            0,
+            0,
+            GdalDataType::Unknown,
+            &options_c,
+        )
+    }
+
     /// Delete named dataset.
     ///
     /// It is unwise to have open dataset handles on this dataset when it is deleted.
@@ -73,8 +73,15 @@ pub enum GdalError {
     UnlinkMemFile { file_name: String },
     #[error("BadArgument")]
     BadArgument(String),
+    #[error("The output would be empty")]
+    EmptyOutput,
     #[error("Date conversion error: {0}")]
     DateError(String),
+    #[error("I/O error: {msg}")]
+    IoError {
+        kind: std::io::ErrorKind,
+        msg: String,
+    },
 
     #[cfg(all(major_ge_3, minor_ge_1))]
     #[error("Unhandled type '{data_type}' on GDAL MD method {method_name}")]
@@ -84,6 +91,36 @@ pub enum GdalError {
     },
 }
 
+impl From<std::io::Error> for GdalError {
+    fn from(err: std::io::Error) -> Self {
+        GdalError::IoError {
+            kind: err.kind(),
+            msg: err.to_string(),
+        }
+    }
+}
+
+impl GdalError {
+    /// The GDAL error class, if this error was reported by GDAL's CPL error handling.
+    pub fn cpl_error_class(&self) -> Option<CplErrorClass> {
+        match self {
+            GdalError::CplError { class, .. } => Some((*class).into()),
+            _ => None,
+        }
+    }
+
+    /// The GDAL error number (one of the `CPLE_*` codes, e.g. `4` for `CPLE_OpenFailed`),
+    /// if this error was reported by GDAL's CPL error handling.
+    ///
+    /// See [`CPLErrorNum`](https://gdal.org/api/cpl.html#_CPPv411CPLErrorNum).
+    pub fn cpl_error_number(&self) -> Option<i32> {
+        match self {
+            GdalError::CplError { number, .. } => Some(*number),
+            _ => None,
+        }
+    }
+}
+
 /// A wrapper for [`CPLErr::Type`] that reflects it as an enum
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(C)]
@@ -95,6 +132,9 @@ pub enum CplErrType {
     Fatal = 4,
 }
 
+/// The class of a GDAL error, as returned by [`GdalError::cpl_error_class`].
+pub type CplErrorClass = CplErrType;
+
 impl From<CPLErr::Type> for CplErrType {
     fn from(error_type: CPLErr::Type) -> Self {
         if error_type > 4 {
@@ -118,4 +158,29 @@ mod tests {
 
         is_send::<GdalError>();
     }
+
+    #[test]
+    fn test_cpl_error_accessors() {
+        use crate::{Dataset, DatasetOptions, GdalOpenFlags};
+
+        let _nolog = crate::test_utils::SuppressGDALErrorLog::new();
+        // GDAL only reports why a file can't be opened when asked to.
+        let options = DatasetOptions {
+            open_flags: GdalOpenFlags::GDAL_OF_VERBOSE_ERROR,
+            ..DatasetOptions::default()
+        };
+        let err = Dataset::open_ex("/does/not/exist.tif", options).unwrap_err();
+        assert_eq!(err.cpl_error_class(), Some(CplErrorClass::Failure));
+        // CPLE_OpenFailed
+        assert_eq!(err.cpl_error_number(), Some(4));
+
+        // Otherwise, the error above isn't taken as the reason for a later failure.
+        let err = Dataset::open("/does/not/exist.tif").unwrap_err();
+        assert!(matches!(err, GdalError::NullPointer { .. }));
+        assert_eq!(err.cpl_error_class(), None);
+
+        let err = GdalError::BadArgument("foo".into());
+        assert_eq!(err.cpl_error_class(), None);
+        assert_eq!(err.cpl_error_number(), None);
+    }
 }
@@ -2,12 +2,14 @@
 
 use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
+use std::ptr::NonNull;
 
 use gdal_sys::CPLErr;
+use libc::{c_int, c_void};
 
-use crate::errors::Result;
+use crate::errors::{GdalError, Result};
 use crate::spatial_ref::SpatialRef;
-use crate::utils::{_last_cpl_err, _string};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err, _string};
 use crate::Dataset;
 
 /// An owned Ground Control Point.
@@ -106,7 +108,121 @@ impl From<&GcpRef<'_>> for Gcp {
     }
 }
 
+/// Model fitted to GCPs by [`Dataset::fit_transform`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GcpMethod {
+    /// Polynomial of the given order (1 to 3), fitted by least squares. Order `0` selects
+    /// the highest order supported by the number of GCPs.
+    Polynomial(u8),
+    /// Thin plate spline, going exactly through every GCP.
+    ThinPlateSpline,
+}
+
+/// Transformer between the pixel/line coordinates of a raster and georeferenced coordinates,
+/// e.g. fitted to GCPs by [`Dataset::fit_transform`].
+#[derive(Debug)]
+pub struct PixelTransformer {
+    c_transformer: NonNull<c_void>,
+}
+
+impl PixelTransformer {
+    /// Transform `(pixel, line)` raster coordinates to georeferenced `(x, y)` ones.
+    pub fn transform(&self, pixel: f64, line: f64) -> Result<(f64, f64)> {
+        self.apply(false, pixel, line)
+    }
+
+    /// Transform georeferenced `(x, y)` coordinates to `(pixel, line)` raster ones.
+    pub fn inverse_transform(&self, x: f64, y: f64) -> Result<(f64, f64)> {
+        self.apply(true, x, y)
+    }
+
+    /// Distance, in georeferenced units, between the position of `gcp` and where its
+    /// pixel/line coordinates are transformed to.
+    pub fn residual(&self, gcp: &GcpRef) -> Result<f64> {
+        let (x, y) = self.transform(gcp.pixel(), gcp.line())?;
+        Ok((x - gcp.x()).hypot(y - gcp.y()))
+    }
+
+    fn apply(&self, dst_to_src: bool, x: f64, y: f64) -> Result<(f64, f64)> {
+        let (mut x_out, mut y_out, mut z_out) = (x, y, 0.0);
+        let mut success: c_int = 0;
+        let rv = unsafe {
+            gdal_sys::GDALUseTransformer(
+                self.c_transformer.as_ptr(),
+                c_int::from(dst_to_src),
+                1,
+                &mut x_out,
+                &mut y_out,
+                &mut z_out,
+                &mut success,
+            )
+        };
+        if rv == 0 || success == 0 {
+            return Err(GdalError::BadArgument(format!(
+                "cannot transform point ({x}, {y})"
+            )));
+        }
+        Ok((x_out, y_out))
+    }
+}
+
+impl Drop for PixelTransformer {
+    fn drop(&mut self) {
+        unsafe { gdal_sys::GDALDestroyTransformer(self.c_transformer.as_ptr()) };
+    }
+}
+
 impl Dataset {
+    /// Fit a transformer from pixel/line to georeferenced coordinates to the GCPs of the
+    /// dataset, using `method`, except the ones at the `exclude` indices (in [`Dataset::gcps`]).
+    ///
+    /// Leaving out GCPs in turn and measuring their [residual](PixelTransformer::residual)
+    /// under the resulting transformer (leave-one-out validation) helps spotting the
+    /// erroneous ones before warping.
+    ///
+    /// See: [`GDALCreateGCPTransformer`](https://gdal.org/api/gdal_alg.html#_CPPv424GDALCreateGCPTransformeriPK8GDAL_GCPii)
+    /// and [`GDALCreateTPSTransformer`](https://gdal.org/api/gdal_alg.html#_CPPv424GDALCreateTPSTransformeriPK8GDAL_GCPi)
+    pub fn fit_transform(&self, method: GcpMethod, exclude: &[usize]) -> Result<PixelTransformer> {
+        let gcps = self.gcps();
+        if let Some(index) = exclude.iter().find(|&&i| i >= gcps.len()) {
+            return Err(GdalError::BadArgument(format!(
+                "cannot exclude GCP {index} of {}",
+                gcps.len()
+            )));
+        }
+        let fitted = gcps
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !exclude.contains(i))
+            .map(|(_, gcp)| gcp.inner)
+            .collect::<Vec<_>>();
+        let count = fitted.len() as c_int;
+
+        let c_transformer = match method {
+            GcpMethod::Polynomial(order) => {
+                if order > 3 {
+                    return Err(GdalError::BadArgument(format!(
+                        "polynomial order must be at most 3, got {order}"
+                    )));
+                }
+                unsafe {
+                    gdal_sys::GDALCreateGCPTransformer(
+                        count,
+                        fitted.as_ptr(),
+                        c_int::from(order),
+                        0,
+                    )
+                }
+            }
+            GcpMethod::ThinPlateSpline => unsafe {
+                gdal_sys::GDALCreateTPSTransformer(count, fitted.as_ptr(), 0)
+            },
+        };
+        let c_transformer = NonNull::new(c_transformer)
+            .ok_or_else(|| _last_null_pointer_err("GDALCreateGCPTransformer"))?;
+        Ok(PixelTransformer { c_transformer })
+    }
+
     /// Get output spatial reference system for GCPs.
     ///
     /// # Notes
@@ -139,6 +255,13 @@ impl Dataset {
         Some(_string(cc_ptr))
     }
 
+    /// Get the number of GCPs of this dataset.
+    ///
+    /// See: [`GDALGetGCPCount`](https://gdal.org/api/raster_c_api.html#_CPPv415GDALGetGCPCount12GDALDatasetH)
+    pub fn gcp_count(&self) -> usize {
+        unsafe { gdal_sys::GDALGetGCPCount(self.c_dataset()) as usize }
+    }
+
     /// Fetch GCPs.
     ///
     /// See: [`GDALDataset::GetGCPs`](https://gdal.org/api/gdaldataset_cpp.html#_CPPv4N11GDALDataset7GetGCPsEv)
@@ -153,6 +276,11 @@ impl Dataset {
     /// This method assigns the passed set of GCPs to this dataset, as well as
     /// setting their coordinate system.
     ///
+    /// When assigning GCPs, an existing geotransform is reset to the default one, which GDAL
+    /// treats as missing, as it would otherwise take precedence over the GCPs when warping.
+    /// A spatial reference set with [`Dataset::set_spatial_ref`] still takes precedence over
+    /// the one of the GCPs, though.
+    ///
     /// See: [`GDALDataset::SetGCPs(int, const GDAL_GCP *, const OGRSpatialReference *)`](https://gdal.org/api/gdaldataset_cpp.html#_CPPv4N11GDALDataset7SetGCPsEiPK8GDAL_GCPPK19OGRSpatialReference)
     ///
     /// # Panics
@@ -202,6 +330,10 @@ impl Dataset {
             })
             .collect::<Vec<_>>();
 
+        if !gdal_gcps.is_empty() {
+            self.clear_geo_transform()?;
+        }
+
         let rv = unsafe {
             gdal_sys::GDALSetGCPs2(
                 self.c_dataset(),
@@ -217,11 +349,29 @@ impl Dataset {
 
         Ok(())
     }
+
+    /// Reset the geotransform of this dataset, if any, to the default one.
+    fn clear_geo_transform(&self) -> Result<()> {
+        const DEFAULT_GEO_TRANSFORM: [f64; 6] = [0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let mut transformation = DEFAULT_GEO_TRANSFORM;
+        let rv =
+            unsafe { gdal_sys::GDALGetGeoTransform(self.c_dataset(), transformation.as_mut_ptr()) };
+        if rv != CPLErr::CE_None || transformation == DEFAULT_GEO_TRANSFORM {
+            return Ok(());
+        }
+        transformation = DEFAULT_GEO_TRANSFORM;
+        let rv =
+            unsafe { gdal_sys::GDALSetGeoTransform(self.c_dataset(), transformation.as_mut_ptr()) };
+        if rv != CPLErr::CE_None {
+            return Err(_last_cpl_err(rv));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Gcp;
+    use super::{Gcp, GcpMethod};
     use crate::spatial_ref::SpatialRef;
     use crate::test_utils::{fixture, TempFixture};
     use crate::Dataset;
@@ -247,6 +397,7 @@ mod tests {
         let dataset = Dataset::open(fixture("gcp.tif")).unwrap();
         let gcps = dataset.gcps();
         assert_eq!(gcps.len(), 210);
+        assert_eq!(dataset.gcp_count(), 210);
         assert_eq!(gcps[0].id(), "1");
         assert_eq!(gcps[0].info(), "");
         assert_eq!(gcps[0].pixel(), 0.0);
@@ -299,4 +450,112 @@ mod tests {
         assert_eq!(spatial_ref.auth_name().unwrap(), "EPSG");
         assert_eq!(spatial_ref.auth_code().unwrap(), 3857);
     }
+
+    #[test]
+    #[cfg_attr(not(all(major_ge_3, minor_ge_5)), ignore)]
+    fn test_fit_transform() {
+        let dataset = crate::DriverManager::get_driver_by_name("MEM")
+            .unwrap()
+            .create("", 10, 10, 1)
+            .unwrap();
+        // A 4x4 grid of GCPs on an affine transformation, one of them off by 50 units.
+        let mut gcps = (0..16)
+            .map(|i| {
+                let (pixel, line) = ((i % 4 * 3) as f64, (i / 4 * 3) as f64);
+                Gcp {
+                    id: i.to_string(),
+                    info: String::new(),
+                    pixel,
+                    line,
+                    x: 100.0 + 2.0 * pixel,
+                    y: 50.0 - 2.0 * line,
+                    z: 0.0,
+                }
+            })
+            .collect::<Vec<_>>();
+        gcps[5].x += 50.0;
+        dataset
+            .set_gcps(gcps, &SpatialRef::from_epsg(3857).unwrap())
+            .unwrap();
+
+        let rms = |exclude: &[usize]| {
+            let transformer = dataset
+                .fit_transform(GcpMethod::Polynomial(1), exclude)
+                .unwrap();
+            let squares = dataset
+                .gcps()
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !exclude.contains(i))
+                .map(|(_, gcp)| transformer.residual(gcp).unwrap().powi(2))
+                .collect::<Vec<_>>();
+            (squares.iter().sum::<f64>() / squares.len() as f64).sqrt()
+        };
+        let with_bad = rms(&[]);
+        let without_bad = rms(&[5]);
+        assert!(with_bad > 1.0, "{with_bad}");
+        assert!(without_bad < 1e-6, "{without_bad}");
+
+        // The left out GCP stands out.
+        let transformer = dataset
+            .fit_transform(GcpMethod::Polynomial(1), &[5])
+            .unwrap();
+        let residual = transformer.residual(&dataset.gcps()[5]).unwrap();
+        assert!((residual - 50.0).abs() < 1e-6, "{residual}");
+        let (pixel, line) = transformer.inverse_transform(106.0, 44.0).unwrap();
+        assert!((pixel - 3.0).abs() < 1e-6 && (line - 3.0).abs() < 1e-6);
+
+        assert!(dataset
+            .fit_transform(GcpMethod::Polynomial(1), &[16])
+            .is_err());
+        assert!(dataset
+            .fit_transform(GcpMethod::Polynomial(4), &[])
+            .is_err());
+        assert!(dataset
+            .fit_transform(GcpMethod::ThinPlateSpline, &[5])
+            .is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(not(all(major_ge_3, minor_ge_5)), ignore)]
+    fn test_set_gcps_over_geo_transform() {
+        use crate::raster::{create_and_reproject_dataset, CreateReprojectOptions};
+
+        let mut dataset = crate::DriverManager::get_driver_by_name("MEM")
+            .unwrap()
+            .create("", 10, 10, 1)
+            .unwrap();
+        // A stale geotransform, far from the GCPs.
+        dataset
+            .set_geo_transform(&[10.0, 0.1, 0.0, 50.0, 0.0, -0.1])
+            .unwrap();
+        let gcps = [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (10.0, 10.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(pixel, line))| Gcp {
+                id: i.to_string(),
+                info: String::new(),
+                pixel,
+                line,
+                x: -100.0 + pixel / 10.0,
+                y: 41.0 - line / 10.0,
+                z: 0.0,
+            })
+            .collect::<Vec<_>>();
+        let srs = SpatialRef::from_epsg(4326).unwrap();
+        dataset.set_gcps(gcps, &srs).unwrap();
+        assert_eq!(dataset.gcp_count(), 4);
+
+        let dest = TempFixture::empty("gcps_over_geo_transform.tif");
+        let out = create_and_reproject_dataset(
+            &dataset,
+            dest.path(),
+            &srs,
+            &CreateReprojectOptions::new(),
+        )
+        .unwrap();
+        let gt = out.geo_transform().unwrap();
+        assert!((gt[0] + 100.0).abs() < 1e-6, "{gt:?}");
+        assert!((gt[3] - 41.0).abs() < 1e-6, "{gt:?}");
+    }
 }
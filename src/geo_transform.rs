@@ -1,7 +1,9 @@
 use crate::errors;
 use crate::errors::GdalError;
+use crate::utils::_path_to_c_string;
 use libc::c_double;
 use std::mem::MaybeUninit;
+use std::path::Path;
 
 /// A six-element array storing the coefficients of an [affine transform]
 /// used in mapping coordinates between pixel/line `(P, L)` (raster) space,
@@ -99,6 +101,31 @@ pub trait GeoTransformEx {
     ///
     /// [GDALInvGeoTransform]: https://gdal.org/api/raster_c_api.html#_CPPv419GDALInvGeoTransformPdPd
     fn invert(&self) -> errors::Result<GeoTransform>;
+
+    /// Read a [`GeoTransform`] from an ESRI [world file] (e.g. `.wld`, `.tfw`, `.pgw`).
+    ///
+    /// Useful for supplying georeferencing to rasters in formats lacking it.
+    ///
+    /// Wraps [GDALLoadWorldFile].
+    ///
+    /// [world file]: https://en.wikipedia.org/wiki/World_file
+    /// [GDALLoadWorldFile]: https://gdal.org/api/raster_c_api.html#_CPPv417GDALLoadWorldFilePKcPd
+    fn from_world_file<P: AsRef<Path>>(path: P) -> errors::Result<GeoTransform>
+    where
+        Self: Sized,
+    {
+        let path = path.as_ref();
+        let c_path = _path_to_c_string(path)?;
+        let mut gt = GeoTransform::default();
+        let rv = unsafe { gdal_sys::GDALLoadWorldFile(c_path.as_ptr(), gt.as_mut_ptr()) };
+        if rv == 0 {
+            return Err(GdalError::BadArgument(format!(
+                "Unable to read world file '{}'",
+                path.display()
+            )));
+        }
+        Ok(gt)
+    }
 }
 
 impl GeoTransformEx for GeoTransform {
@@ -123,10 +123,10 @@ pub mod vsi;
 
 pub use dataset::Dataset;
 pub use geo_transform::{GeoTransform, GeoTransformEx};
-pub use options::{DatasetOptions, GdalOpenFlags};
+pub use options::{DatasetOptions, GdalOpenFlags, GdalOpenMode};
 
 pub use driver::{Driver, DriverManager};
-pub use gcp::{Gcp, GcpRef};
+pub use gcp::{Gcp, GcpMethod, GcpRef, PixelTransformer};
 #[cfg(any(major_ge_4, all(major_is_3, minor_ge_6)))]
 pub use gdal_sys::ArrowArrayStream;
 pub use metadata::{Metadata, MetadataEntry};
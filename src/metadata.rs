@@ -2,6 +2,7 @@ use crate::errors::*;
 use crate::gdal_major_object::MajorObject;
 use crate::utils::{_last_cpl_err, _last_null_pointer_err, _string, _string_array};
 use gdal_sys::{self, CPLErr};
+use std::collections::HashMap;
 use std::ffi::CString;
 
 /// General-Purpose Metadata API
@@ -232,6 +233,48 @@ pub trait Metadata: MajorObject {
     {
         MetadataIter::new(self)
     }
+
+    /// Get all metadata, keyed by domain and then by item name.
+    ///
+    /// The default domain is keyed by `""`. The content of `xml:` domains
+    /// (e.g. `xml:XMP`, `xml:VRT`) is a single XML document rather than key/value pairs:
+    /// it is reported as one entry with an empty key, holding the whole document.
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// use gdal::{Dataset, Metadata};
+    /// # fn main() -> gdal::errors::Result<()> {
+    /// let dataset = Dataset::open("fixtures/tinymarble.tif")?;
+    /// let metadata = dataset.all_metadata();
+    /// assert_eq!(metadata["IMAGE_STRUCTURE"]["INTERLEAVE"], "PIXEL");
+    /// assert!(metadata["xml:XMP"][""].starts_with("<"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn all_metadata(&self) -> HashMap<String, HashMap<String, String>> {
+        let mut all = HashMap::new();
+        for domain in self.metadata_domains() {
+            if all.contains_key(&domain) {
+                continue;
+            }
+            let entries = self.metadata_domain(&domain).unwrap_or_default();
+            let items = if domain.starts_with("xml:") {
+                entries
+                    .into_iter()
+                    .map(|document| (String::new(), document))
+                    .collect()
+            } else {
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.split_once('='))
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect()
+            };
+            all.insert(domain, items);
+        }
+        all
+    }
 }
 
 /// Standalone metadata entry, as returned by iterator from [`Metadata::metadata`].
@@ -391,6 +434,34 @@ mod tests {
         assert_eq!(band.description().unwrap(), description);
     }
 
+    #[test]
+    fn test_all_metadata() {
+        let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+        let mut dataset = driver.create("", 1, 1, 1).unwrap();
+        dataset
+            .set_metadata_item("AREA_OR_POINT", "Point", "")
+            .unwrap();
+        dataset
+            .set_metadata_item("LINE_OFF", "2138", "RPC")
+            .unwrap();
+        dataset
+            .set_metadata_item("SAMP_OFF", "3312", "RPC")
+            .unwrap();
+
+        let metadata = dataset.all_metadata();
+        assert_eq!(metadata[""]["AREA_OR_POINT"], "Point");
+        assert_eq!(metadata["RPC"].len(), 2);
+        assert_eq!(metadata["RPC"]["LINE_OFF"], "2138");
+        assert_eq!(metadata["RPC"]["SAMP_OFF"], "3312");
+
+        let dataset = Dataset::open(fixture("tinymarble.tif")).unwrap();
+        let metadata = dataset.all_metadata();
+        assert_eq!(metadata["IMAGE_STRUCTURE"]["INTERLEAVE"], "PIXEL");
+        let xmp = &metadata["xml:XMP"];
+        assert_eq!(xmp.len(), 1);
+        assert!(xmp[""].contains("<x:xmpmeta"), "{xmp:?}");
+    }
+
     #[test]
     fn test_md_iter() {
         // Driver metadata...
@@ -11,6 +11,30 @@ pub struct DatasetOptions<'a> {
     pub sibling_files: Option<&'a [&'a str]>,
 }
 
+/// Access mode of a [`crate::Dataset`], see [`crate::Dataset::open_with_mode`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GdalOpenMode {
+    /// Read-only access (default).
+    ReadOnly,
+    /// Read and write access.
+    Update,
+}
+
+impl Default for GdalOpenMode {
+    fn default() -> GdalOpenMode {
+        GdalOpenMode::ReadOnly
+    }
+}
+
+impl From<GdalOpenMode> for GdalOpenFlags {
+    fn from(mode: GdalOpenMode) -> GdalOpenFlags {
+        match mode {
+            GdalOpenMode::ReadOnly => GdalOpenFlags::GDAL_OF_READONLY,
+            GdalOpenMode::Update => GdalOpenFlags::GDAL_OF_UPDATE,
+        }
+    }
+}
+
 // These are skipped by bindgen and manually updated.
 #[cfg(major_ge_2)]
 bitflags! {
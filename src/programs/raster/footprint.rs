@@ -0,0 +1,118 @@
+use crate::{
+    errors::*,
+    utils::_last_null_pointer_err,
+    vector::{Geometry, LayerAccess},
+    Dataset, DriverManager,
+};
+use libc::{c_char, c_int};
+use std::{
+    ffi::CString,
+    ptr::{null, null_mut},
+};
+
+impl Dataset {
+    /// Compute the footprint of the valid data of this dataset, as a polygon or multipolygon,
+    /// like the `gdal_footprint` program.
+    ///
+    /// `options` are the arguments of the program, e.g. `["-b", "1", "-max_points", "100"]`;
+    /// the output format and layer name are ignored. The valid pixels are those of the mask band
+    /// of each selected band, so that the no-data pixels are left out. By default, the footprint
+    /// is in the georeferenced coordinates of the dataset, and has its spatial reference.
+    ///
+    /// Only available with GDAL 3.8 or later.
+    ///
+    /// See: [`GDALFootprint`](https://gdal.org/api/gdal_utils.html#_CPPv413GDALFootprintPKc12GDALDatasetH12GDALDatasetHPK20GDALFootprintOptionsPi)
+    /// and the [program docs](https://gdal.org/programs/gdal_footprint.html).
+    pub fn footprint(&self, options: &[&str]) -> Result<Geometry> {
+        let cstr_args = options
+            .iter()
+            .map(|&arg| CString::new(arg))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut c_args = cstr_args
+            .iter()
+            .map(|x| x.as_ptr() as *mut c_char)
+            .chain(std::iter::once(null_mut()))
+            .collect::<Vec<_>>();
+        let c_options =
+            unsafe { gdal_sys::GDALFootprintOptionsNew(c_args.as_mut_ptr(), null_mut()) };
+        if c_options.is_null() {
+            return Err(_last_null_pointer_err("GDALFootprintOptionsNew"));
+        }
+
+        let out = DriverManager::get_driver_by_name("Memory")?.create_vector_only("")?;
+        let mut usage_error: c_int = 0;
+        let rv = unsafe {
+            let rv = gdal_sys::GDALFootprint(
+                null(),
+                out.c_dataset(),
+                self.c_dataset(),
+                c_options,
+                &mut usage_error,
+            );
+            gdal_sys::GDALFootprintOptionsFree(c_options);
+            rv
+        };
+        // On success, the output dataset is returned, and still owned by `out`.
+        if rv.is_null() {
+            return Err(_last_null_pointer_err("GDALFootprint"));
+        }
+
+        let mut layer = out.layer(0)?;
+        let srs = layer.spatial_ref();
+        let feature = layer
+            .features()
+            .next()
+            .ok_or_else(|| GdalError::BadArgument("the dataset has no valid pixel".into()))?;
+        let mut geometry = feature
+            .geometry()
+            .cloned()
+            .ok_or_else(|| GdalError::BadArgument("the footprint has no geometry".into()))?;
+        if let (None, Some(srs)) = (geometry.spatial_ref(), srs) {
+            geometry.set_spatial_ref(srs);
+        }
+        Ok(geometry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raster::Buffer;
+    use crate::spatial_ref::SpatialRef;
+    use crate::DriverManager;
+
+    #[test]
+    fn test_footprint() {
+        let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+        let mut dataset = driver
+            .create_with_band_type::<u8, _>("", 10, 10, 1)
+            .unwrap();
+        dataset
+            .set_geo_transform(&[100.0, 1.0, 0.0, 200.0, 0.0, -1.0])
+            .unwrap();
+        dataset
+            .set_spatial_ref(&SpatialRef::from_epsg(32631).unwrap())
+            .unwrap();
+        // Valid pixels inside a no-data collar of 2 pixels.
+        let values = (0..100)
+            .map(|i| {
+                let (x, y) = (i % 10, i / 10);
+                u8::from((2..8).contains(&x) && (2..8).contains(&y))
+            })
+            .collect::<Vec<_>>();
+        let mut band = dataset.rasterband(1).unwrap();
+        band.set_no_data_value(Some(0.0)).unwrap();
+        band.write((0, 0), (10, 10), &Buffer::new((10, 10), values))
+            .unwrap();
+
+        let footprint = dataset.footprint(&[]).unwrap();
+        assert_eq!(footprint.area(), 36.0);
+        let envelope = footprint.envelope();
+        assert_eq!(
+            (envelope.MinX, envelope.MinY, envelope.MaxX, envelope.MaxY),
+            (102.0, 192.0, 108.0, 198.0)
+        );
+        assert_eq!(footprint.spatial_ref().unwrap().auth_code().unwrap(), 32631);
+
+        assert!(dataset.footprint(&["-b", "2"]).is_err());
+    }
+}
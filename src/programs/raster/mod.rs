@@ -1,3 +1,5 @@
+#[cfg(all(major_ge_3, minor_ge_8))]
+mod footprint;
 #[cfg(all(major_ge_3, minor_ge_1))]
 mod mdimtranslate;
 mod vrt;
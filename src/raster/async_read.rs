@@ -0,0 +1,219 @@
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::ptr::null_mut;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use gdal_sys::{CPLErr, GDALAsyncStatusType, GDALDatasetH};
+use libc::c_int;
+
+use crate::errors::{GdalError, Result};
+use crate::metadata::Metadata;
+use crate::raster::{Buffer, GdalType};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err};
+use crate::{Dataset, DatasetOptions, GdalOpenFlags, GdalOpenMode};
+
+/// A read of a window of a [`RasterBand`](crate::raster::RasterBand), in progress, as returned
+/// by [`RasterBand::read_async`](crate::raster::RasterBand::read_async).
+///
+/// It is backed by GDAL's [asynchronous reader], and resolves to the read [`Buffer`] once the
+/// reader reports the whole window as complete.
+///
+/// On the first poll, the file of the dataset is opened again on a worker thread, which runs the
+/// reader to completion and then wakes the task, so the executor isn't blocked by the read,
+/// even with drivers without native support for asynchronous reads (most of them), which read
+/// the whole window at once. Dropping the future before it resolves doesn't stop the worker
+/// thread, but its result is discarded.
+///
+/// Datasets without a file name, such as in-memory ones, can't be opened again, and are read
+/// during the first poll instead.
+///
+/// [asynchronous reader]: https://gdal.org/api/gdalasyncreader_cpp.html
+pub struct AsyncRead<'a, T: GdalType + Copy> {
+    dataset: &'a Dataset,
+    band_index: c_int,
+    window: (isize, isize),
+    window_size: (usize, usize),
+    size: (usize, usize),
+    shared: Option<Arc<Mutex<Shared<T>>>>,
+    done: bool,
+}
+
+/// State shared with the worker thread.
+struct Shared<T> {
+    result: Option<Result<Vec<T>>>,
+    waker: Option<Waker>,
+}
+
+/// The window of a band to read, and how.
+#[derive(Clone, Copy)]
+struct Request {
+    band_index: c_int,
+    window: (isize, isize),
+    window_size: (usize, usize),
+    size: (usize, usize),
+}
+
+impl<'a, T: GdalType + Copy> AsyncRead<'a, T> {
+    pub(crate) fn new(
+        dataset: &'a Dataset,
+        band_index: c_int,
+        window: (isize, isize),
+        window_size: (usize, usize),
+        size: (usize, usize),
+    ) -> Self {
+        AsyncRead {
+            dataset,
+            band_index,
+            window,
+            window_size,
+            size,
+            shared: None,
+            done: false,
+        }
+    }
+
+    fn request(&self) -> Request {
+        Request {
+            band_index: self.band_index,
+            window: self.window,
+            window_size: self.window_size,
+            size: self.size,
+        }
+    }
+}
+
+impl<'a, T: GdalType + Copy + Send + 'static> AsyncRead<'a, T> {
+    /// Start reading on a worker thread, or read right away when the dataset can't be opened
+    /// again.
+    fn begin(&mut self, waker: &Waker) -> Arc<Mutex<Shared<T>>> {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: Some(waker.clone()),
+        }));
+        let request = self.request();
+        let path = self.dataset.description().unwrap_or_default();
+        if path.is_empty() {
+            let result = unsafe { read_window(self.dataset.c_dataset(), request) };
+            shared.lock().unwrap().result = Some(result);
+            return shared;
+        }
+        if self.dataset.open_mode() == GdalOpenMode::Update {
+            // So that the worker sees what has been written so far.
+            unsafe { gdal_sys::GDALFlushCache(self.dataset.c_dataset()) };
+        }
+        let driver = self.dataset.driver().short_name();
+        let worker_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            let result = Dataset::open_ex(
+                Path::new(&path),
+                DatasetOptions {
+                    open_flags: GdalOpenFlags::GDAL_OF_RASTER,
+                    allowed_drivers: Some(&[driver.as_str()]),
+                    ..DatasetOptions::default()
+                },
+            )
+            .and_then(|dataset| unsafe { read_window(dataset.c_dataset(), request) });
+            let mut shared = worker_shared.lock().unwrap();
+            shared.result = Some(result);
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+        shared
+    }
+}
+
+/// Run GDAL's asynchronous reader on `request` to completion.
+///
+/// # Safety
+/// `c_dataset` must be a valid dataset, not used by other threads meanwhile.
+unsafe fn read_window<T: GdalType + Copy>(
+    c_dataset: GDALDatasetH,
+    request: Request,
+) -> Result<Vec<T>> {
+    let pixels = request.size.0 * request.size.1;
+    let mut data: Vec<T> = Vec::with_capacity(pixels);
+    let mut band_map = [request.band_index];
+    let reader = gdal_sys::GDALBeginAsyncReader(
+        c_dataset,
+        request.window.0 as c_int,
+        request.window.1 as c_int,
+        request.window_size.0 as c_int,
+        request.window_size.1 as c_int,
+        data.as_mut_ptr().cast(),
+        request.size.0 as c_int,
+        request.size.1 as c_int,
+        T::gdal_ordinal(),
+        1,
+        band_map.as_mut_ptr(),
+        0,
+        0,
+        0,
+        null_mut(),
+    );
+    if reader.is_null() {
+        return Err(_last_null_pointer_err("GDALBeginAsyncReader"));
+    }
+    let status = loop {
+        let (mut x_off, mut y_off, mut x_size, mut y_size) = (0, 0, 0, 0);
+        // A negative timeout waits for as long as needed.
+        let status = gdal_sys::GDALARGetNextUpdatedRegion(
+            reader,
+            -1.0,
+            &mut x_off,
+            &mut y_off,
+            &mut x_size,
+            &mut y_size,
+        );
+        if status == GDALAsyncStatusType::GARIO_COMPLETE
+            || status == GDALAsyncStatusType::GARIO_ERROR
+        {
+            break status;
+        }
+    };
+    let result = if status == GDALAsyncStatusType::GARIO_COMPLETE {
+        // The reader has written every value of the buffer.
+        data.set_len(pixels);
+        Ok(data)
+    } else {
+        Err(_last_cpl_err(CPLErr::CE_Failure))
+    };
+    gdal_sys::GDALEndAsyncReader(c_dataset, reader);
+    result
+}
+
+impl<'a, T: GdalType + Copy + Send + 'static> Future for AsyncRead<'a, T> {
+    type Output = Result<Buffer<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(Err(GdalError::BadArgument(
+                "asynchronous read polled after completion".into(),
+            )));
+        }
+        let shared = match &this.shared {
+            Some(shared) => Arc::clone(shared),
+            None => {
+                let shared = this.begin(cx.waker());
+                this.shared = Some(Arc::clone(&shared));
+                shared
+            }
+        };
+        let mut shared = shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => {
+                this.done = true;
+                Poll::Ready(result.map(|data| Buffer::new(this.size, data)))
+            }
+            None => {
+                // Woken by the worker thread once done.
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
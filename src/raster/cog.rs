@@ -0,0 +1,139 @@
+use crate::errors::Result;
+use crate::raster::RasterBand;
+use crate::vsi::stat;
+use crate::{Dataset, Metadata};
+
+/// Images up to this size, in both dimensions, don't need to be tiled nor to have overviews.
+const MAX_UNTILED_SIZE: usize = 512;
+
+/// The outcome of [`Dataset::validate_cog`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CogValidation {
+    /// Reasons why the dataset isn't a valid Cloud Optimized GeoTIFF.
+    pub errors: Vec<String>,
+    /// Deviations from the recommended structure, which don't prevent the dataset from being
+    /// read efficiently, e.g. missing overviews.
+    pub warnings: Vec<String>,
+}
+
+impl CogValidation {
+    /// Whether the dataset is a valid Cloud Optimized GeoTIFF, i.e. there are no errors.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Dataset {
+    /// Check whether this dataset is a valid [Cloud Optimized GeoTIFF][COG], as GDAL's
+    /// [`validate_cloud_optimized_geotiff.py`] script does: a tiled GeoTIFF, with internal
+    /// tiled overviews, whose image file directories come first, before the data of the
+    /// overviews, from the smallest to the full resolution image.
+    ///
+    /// Returns an error only if the structure of the dataset can't be read.
+    ///
+    /// [COG]: https://gdal.org/drivers/raster/cog.html
+    /// [`validate_cloud_optimized_geotiff.py`]: https://github.com/OSGeo/gdal/blob/master/swig/python/gdal-utils/osgeo_utils/samples/validate_cloud_optimized_geotiff.py
+    pub fn validate_cog(&self) -> Result<CogValidation> {
+        let mut validation = CogValidation::default();
+        let errors = &mut validation.errors;
+        let warnings = &mut validation.warnings;
+        if self.driver().short_name() != "GTiff" {
+            errors.push("The file is not a GeoTIFF".to_owned());
+            return Ok(validation);
+        }
+        if self.raster_count() == 0 {
+            errors.push("The file has no raster band".to_owned());
+            return Ok(validation);
+        }
+
+        let main_band = self.rasterband(1)?;
+        let overview_count = main_band.overview_count()?.max(0) as usize;
+        if overview_count > 0 && stat(format!("{}.ovr", self.description()?))?.is_some() {
+            errors
+                .push("Overviews found in external .ovr file. They should be internal".to_owned());
+        }
+
+        let (cols, rows) = main_band.size();
+        if cols > MAX_UNTILED_SIZE || rows > MAX_UNTILED_SIZE {
+            if !is_tiled(&main_band) {
+                errors.push(format!(
+                    "The file is greater than {MAX_UNTILED_SIZE}xH or Wx{MAX_UNTILED_SIZE}, but is not tiled"
+                ));
+            }
+            if overview_count == 0 {
+                warnings.push(format!(
+                    "The file is greater than {MAX_UNTILED_SIZE}xH or Wx{MAX_UNTILED_SIZE}, it is recommended to include internal overviews"
+                ));
+            }
+        }
+        if self.metadata_item("LAYOUT", "IMAGE_STRUCTURE").as_deref() != Some("COG") {
+            warnings.push("The file doesn't declare the COG layout in its ghost header".to_owned());
+        }
+
+        let mut ifd_offsets = vec![tiff_offset(&main_band, "IFD_OFFSET")];
+        let mut data_offsets = vec![tiff_offset(&main_band, "BLOCK_OFFSET_0_0")];
+        for i in 0..overview_count {
+            let overview = main_band.overview(i as isize)?;
+            let (cols, rows) = overview.size();
+            if (cols > MAX_UNTILED_SIZE || rows > MAX_UNTILED_SIZE) && !is_tiled(&overview) {
+                errors.push(format!("Overview of index {i} is not tiled"));
+            }
+            let ifd_offset = tiff_offset(&overview, "IFD_OFFSET");
+            let previous = *ifd_offsets.last().unwrap();
+            if ifd_offset < previous {
+                let (name, offset) = match i {
+                    0 => ("the main image".to_owned(), ifd_offsets[0]),
+                    _ => (format!("overview of index {}", i - 1), previous),
+                };
+                errors.push(format!(
+                    "The offset of the IFD for overview of index {i} is {ifd_offset}, whereas it should be greater than the one of {name}, which is at byte {offset}"
+                ));
+            }
+            ifd_offsets.push(ifd_offset);
+            data_offsets.push(tiff_offset(&overview, "BLOCK_OFFSET_0_0"));
+        }
+
+        if ifd_offsets[0] != 8 && ifd_offsets[0] != 16 {
+            errors.push(format!(
+                "The offset of the main IFD should be 8 for ClassicTIFF or 16 for BigTIFF. It is {} instead",
+                ifd_offsets[0]
+            ));
+        }
+        let last_data = *data_offsets.last().unwrap();
+        if last_data != 0 && last_data < *ifd_offsets.last().unwrap() {
+            errors.push(match overview_count {
+                0 => "The offset of the first block of the image should be after its IFD",
+                _ => "The offset of the first block of the smallest overview should be after its IFD",
+            }
+            .to_owned());
+        }
+        for i in (1..data_offsets.len().saturating_sub(1)).rev() {
+            if data_offsets[i] != 0 && data_offsets[i] < data_offsets[i + 1] {
+                errors.push(format!(
+                    "The offset of the first block of overview of index {} should be after the one of the overview of index {i}",
+                    i - 1
+                ));
+            }
+        }
+        if data_offsets.len() >= 2 && data_offsets[0] != 0 && data_offsets[0] < data_offsets[1] {
+            errors.push(format!(
+                "The offset of the first block of the main resolution image should be after the one of the overview of index {}",
+                overview_count - 1
+            ));
+        }
+        Ok(validation)
+    }
+}
+
+/// Whether `band` is split in tiles, rather than in strips.
+fn is_tiled(band: &RasterBand) -> bool {
+    let (block_cols, _) = band.block_size();
+    block_cols < band.size().0
+}
+
+/// The byte offset given by the `key` item of the `TIFF` metadata domain of `band`, or 0.
+fn tiff_offset(band: &RasterBand, key: &str) -> u64 {
+    band.metadata_item(key, "TIFF")
+        .and_then(|offset| offset.parse().ok())
+        .unwrap_or(0)
+}
@@ -74,26 +74,45 @@
 //!     ...
 //! ```
 
+mod async_read;
+mod cog;
 #[cfg(all(major_ge_3, minor_ge_1))]
 mod mdarray;
+mod polygonize;
 pub mod processing;
 mod rasterband;
 mod rasterize;
+mod tile_index;
 mod types;
 mod warp;
 
+pub use async_read::AsyncRead;
+pub use cog::CogValidation;
 #[cfg(all(major_ge_3, minor_ge_1))]
 pub use mdarray::{
     Attribute, Dimension, ExtendedDataType, ExtendedDataTypeClass, Group, MDArray, MdStatisticsAll,
 };
+pub use polygonize::{polygonize, PolygonizeOptions};
 pub use rasterband::{
-    Buffer, ByteBuffer, CmykEntry, ColorEntry, ColorInterpretation, ColorTable, GrayEntry,
-    HlsEntry, PaletteInterpretation, RasterBand, ResampleAlg, RgbaEntry, StatisticsAll,
+    BandDescriptor, Buffer, ByteBuffer, CmykEntry, ColorEntry, ColorInterpretation, ColorTable,
+    GrayEntry, HlsEntry, PaletteInterpretation, RasterBand, ResampleAlg, RgbaEntry, StatisticsAll,
     StatisticsMinMax,
 };
 pub use rasterize::{rasterize, BurnSource, MergeAlgorithm, OptimizeMode, RasterizeOptions};
+pub use tile_index::{create_tile_index, open_tile_index};
 pub use types::{AdjustedValue, GdalDataType, GdalType};
-pub use warp::reproject;
+pub use warp::{
+    create_and_reproject, create_and_reproject_dataset, create_and_reproject_if_stale,
+    create_and_reproject_pyramid, create_and_reproject_to_buffer,
+    create_and_reproject_to_tile_matrix, create_and_reproject_to_vsimem,
+    create_and_reproject_with_footprint, create_and_reproject_with_mask,
+    create_and_reproject_with_overviews, merge_into_mosaic, reproject, reproject_into,
+    reproject_stack_like, reproject_streaming, reproject_to_cog, reproject_to_writer,
+    suggested_warp_output, BigTiffMode, Compression, CreateReprojectOptions,
+    CreateReprojectOptionsBuilder, GdalWarpOptions, GdalWarpOptionsBuilder, InitDest, Interleave,
+    NumThreads, Photometric, Predictor, ReprojectIntoOptions, ReprojectIntoOptionsBuilder,
+    ReprojectOutcome, SuggestedWarpOutput, WarpReport, WarpResampleAlg, STREAMING_BLOCK_SIZE,
+};
 
 /// Key/value pair for passing driver-specific creation options to
 /// [`Driver::create_with_band_type_wth_options`](crate::Driver::create_with_band_type_with_options`).
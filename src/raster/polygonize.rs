@@ -0,0 +1,68 @@
+use std::convert::TryFrom;
+use std::ptr;
+
+use gdal_sys::{self, CPLErr};
+use libc::c_int;
+
+use crate::cpl::CslStringList;
+use crate::errors::*;
+use crate::raster::RasterBand;
+use crate::utils::_last_cpl_err;
+use crate::vector::LayerAccess;
+
+/// Options that specify how to polygonize a raster band.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PolygonizeOptions {
+    /// Set to `true` to consider diagonally adjacent pixels as connected (8-connectedness),
+    /// instead of only the horizontally and vertically adjacent ones. Defaults to `false`.
+    pub eight_connected: bool,
+}
+
+impl TryFrom<PolygonizeOptions> for CslStringList {
+    type Error = GdalError;
+
+    fn try_from(value: PolygonizeOptions) -> Result<CslStringList> {
+        let mut options = CslStringList::new();
+        if value.eight_connected {
+            options.set_name_value("8CONNECTED", "8")?;
+        }
+        Ok(options)
+    }
+}
+
+/// Create a polygon feature in `layer` for each connected region of pixels sharing the same
+/// value in `band`.
+///
+/// Only the pixels that are valid according to `mask` (if any) are polygonized; pass
+/// `band.open_mask_band()` to skip the no-data pixels. If `value_field` is set, the pixel
+/// value of each region is written to the field at that index of `layer`, which must exist.
+///
+/// The polygons are in the georeferenced coordinates of the dataset of `band`. `layer` can
+/// belong to any vector dataset, e.g. a GeoPackage created with
+/// [`Driver::create_vector_only_with_options`](crate::Driver::create_vector_only_with_options).
+///
+/// See: [`GDALPolygonize`](https://gdal.org/api/gdal_alg.html#_CPPv414GDALPolygonize15GDALRasterBandH15GDALRasterBandH9OGRLayerHiPPc16GDALProgressFuncPv)
+pub fn polygonize<L: LayerAccess>(
+    band: &RasterBand,
+    mask: Option<&RasterBand>,
+    layer: &mut L,
+    value_field: Option<usize>,
+    options: PolygonizeOptions,
+) -> Result<()> {
+    let c_options = CslStringList::try_from(options)?;
+    let rv = unsafe {
+        gdal_sys::GDALPolygonize(
+            band.c_rasterband(),
+            mask.map_or(ptr::null_mut(), |mask| mask.c_rasterband()),
+            layer.c_layer(),
+            value_field.map_or(-1, |field| field as c_int),
+            c_options.as_ptr(),
+            None,
+            ptr::null_mut(),
+        )
+    };
+    if rv != CPLErr::CE_None {
+        return Err(_last_cpl_err(rv));
+    }
+    Ok(())
+}
@@ -1,13 +1,15 @@
 use crate::dataset::Dataset;
 use crate::gdal_major_object::MajorObject;
+use crate::geo_transform::GeoTransformEx;
 use crate::metadata::Metadata;
-use crate::raster::{GdalDataType, GdalType};
+use crate::raster::{AsyncRead, GdalDataType, GdalType};
 use crate::utils::{_last_cpl_err, _last_null_pointer_err, _string};
 use gdal_sys::{
     self, CPLErr, GDALColorEntry, GDALColorInterp, GDALColorTableH, GDALComputeRasterMinMax,
     GDALCreateColorRamp, GDALCreateColorTable, GDALDestroyColorTable, GDALGetPaletteInterpretation,
-    GDALGetRasterStatistics, GDALMajorObjectH, GDALPaletteInterp, GDALRIOResampleAlg, GDALRWFlag,
-    GDALRasterBandH, GDALRasterIOExtraArg, GDALSetColorEntry, GDALSetRasterColorTable,
+    GDALGetRandomRasterSample, GDALGetRasterStatistics, GDALMajorObjectH, GDALPaletteInterp,
+    GDALRIOResampleAlg, GDALRWFlag, GDALRasterBandH, GDALRasterIOExtraArg, GDALSetColorEntry,
+    GDALSetRasterColorTable,
 };
 use libc::c_int;
 use std::ffi::CString;
@@ -16,7 +18,7 @@ use std::marker::PhantomData;
 use std::str::FromStr;
 
 #[cfg(feature = "ndarray")]
-use ndarray::Array2;
+use ndarray::{Array2, ArrayView2, ArrayViewMut2};
 
 use crate::errors::*;
 use crate::raster::ResampleAlg::{
@@ -79,6 +81,40 @@ impl Dataset {
         Ok(())
     }
 
+    /// Make sure the dataset has overviews with decimation factors 2, 4, …, 2<sup>`min_levels`</sup>,
+    /// building those missing with `resampling` (e.g. `"AVERAGE"`).
+    ///
+    /// The overviews already present, as seen on the first band, are left alone, so this does
+    /// nothing if all the levels exist. See [`build_overviews`](Self::build_overviews).
+    ///
+    /// Fails with [`GdalError::BadArgument`] if `min_levels` is more than 30, as the
+    /// decimation factors would not fit in an `i32`.
+    pub fn ensure_overviews(&mut self, min_levels: usize, resampling: &str) -> Result<()> {
+        if min_levels > 30 {
+            return Err(GdalError::BadArgument(format!(
+                "min_levels must be at most 30, got {min_levels}"
+            )));
+        }
+        if self.raster_count() == 0 {
+            return Ok(());
+        }
+        let band = self.rasterband(1)?;
+        let x_size = band.x_size();
+        let mut existing = Vec::new();
+        for i in 0..band.overview_count()? {
+            let overview_x_size = band.overview(i as isize)?.x_size();
+            existing.push((x_size as f64 / overview_x_size as f64).round() as i32);
+        }
+        let missing: Vec<i32> = (1..=min_levels as u32)
+            .map(|level| 1 << level)
+            .filter(|factor| !existing.contains(factor))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        self.build_overviews(resampling, &missing, &[])
+    }
+
     /// Fetch the number of raster bands on this dataset.
     pub fn raster_count(&self) -> usize {
         (unsafe { gdal_sys::GDALGetRasterCount(self.c_dataset()) }) as usize
@@ -90,6 +126,191 @@ impl Dataset {
         let size_y = unsafe { gdal_sys::GDALGetRasterYSize(self.c_dataset()) } as usize;
         (size_x, size_y)
     }
+
+    /// Describe every band of this dataset: its data type, no-data value, color interpretation
+    /// and description, e.g. to create a compatible destination for
+    /// [`reproject_into`](crate::raster::reproject_into).
+    pub fn band_descriptors(&self) -> Result<Vec<BandDescriptor>> {
+        (1..=self.raster_count())
+            .map(|index| {
+                let band = self.rasterband(index)?;
+                Ok(BandDescriptor {
+                    index,
+                    datatype: band.band_type(),
+                    nodata: band.no_data_value(),
+                    color_interp: band.color_interpretation(),
+                    description: band.description()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Read the value of the pixel of band `band_index` (_1-based_) containing the
+    /// georeferenced point (`x`, `y`), expressed in the dataset's spatial reference.
+    ///
+    /// Returns `None` if the point falls outside the raster, or on a no-data pixel.
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # fn main() -> gdal::errors::Result<()> {
+    /// use gdal::Dataset;
+    /// let dataset = Dataset::open("fixtures/m_3607824_se_17_1_20160620_sub.tif")?;
+    /// let value = dataset.value_at(768269.5, 4057291.5, 1)?;
+    /// assert!(value.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn value_at(&self, x: f64, y: f64, band_index: usize) -> Result<Option<f64>> {
+        let band = self.rasterband(band_index)?;
+        let (pixel, line) = self.geo_transform()?.invert()?.apply(x, y);
+        let (pixel, line) = (pixel.floor(), line.floor());
+        let (cols, rows) = self.raster_size();
+        if pixel < 0.0 || line < 0.0 || pixel >= cols as f64 || line >= rows as f64 {
+            return Ok(None);
+        }
+        let value = band.read_as::<f64>((pixel as isize, line as isize), (1, 1), (1, 1), None)?;
+        Ok(valid_value(value.data[0], band.no_data_value()))
+    }
+
+    /// Read the values of the pixels of band `band_index` (_1-based_) containing each of the
+    /// georeferenced points `coords`, expressed in the dataset's spatial reference.
+    ///
+    /// Values are returned in the order of `coords`, with `None` for points falling outside
+    /// the raster or on no-data pixels. Points are grouped by block, so each block holding
+    /// at least one of them is read only once.
+    pub fn sample_points(
+        &self,
+        coords: &[(f64, f64)],
+        band_index: usize,
+    ) -> Result<Vec<Option<f64>>> {
+        let band = self.rasterband(band_index)?;
+        let inverse = self.geo_transform()?.invert()?;
+        let (cols, rows) = self.raster_size();
+        let (block_cols, block_rows) = band.block_size();
+        let no_data = band.no_data_value();
+
+        // (block row, block column), pixel, line and index in `coords` of points within the raster
+        let mut points: Vec<((usize, usize), usize, usize, usize)> = coords
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(x, y))| {
+                let (pixel, line) = inverse.apply(x, y);
+                let (pixel, line) = (pixel.floor(), line.floor());
+                if pixel < 0.0 || line < 0.0 || pixel >= cols as f64 || line >= rows as f64 {
+                    return None;
+                }
+                let (pixel, line) = (pixel as usize, line as usize);
+                Some(((line / block_rows, pixel / block_cols), pixel, line, i))
+            })
+            .collect();
+        points.sort_unstable_by_key(|point| point.0);
+
+        let mut values = vec![None; coords.len()];
+        let mut current_block: Option<((usize, usize), Buffer<f64>)> = None;
+        for (block, pixel, line, i) in points {
+            let (x_off, y_off) = (block.1 * block_cols, block.0 * block_rows);
+            if current_block.as_ref().map(|(b, _)| *b) != Some(block) {
+                let size = (block_cols.min(cols - x_off), block_rows.min(rows - y_off));
+                let data =
+                    band.read_as::<f64>((x_off as isize, y_off as isize), size, size, None)?;
+                current_block = Some((block, data));
+            }
+            if let Some((_, data)) = &current_block {
+                let value = data.data[(line - y_off) * data.size.0 + (pixel - x_off)];
+                values[i] = valid_value(value, no_data);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Check whether `other` holds the same pixels as this dataset.
+    ///
+    /// Both datasets must have the same size and band count, and every pair of corresponding
+    /// pixels must differ by at most `tolerance` (two NaN values are considered equal).
+    /// Georeferencing and metadata are not compared. Bands are read one block at a time.
+    pub fn raster_equals(&self, other: &Dataset, tolerance: f64) -> Result<bool> {
+        if self.raster_size() != other.raster_size() || self.raster_count() != other.raster_count()
+        {
+            return Ok(false);
+        }
+        let (cols, rows) = self.raster_size();
+        for band_index in 1..=self.raster_count() {
+            let band = self.rasterband(band_index)?;
+            let other_band = other.rasterband(band_index)?;
+            let (block_cols, block_rows) = band.block_size();
+            for y_off in (0..rows).step_by(block_rows) {
+                for x_off in (0..cols).step_by(block_cols) {
+                    let window = (x_off as isize, y_off as isize);
+                    let size = (block_cols.min(cols - x_off), block_rows.min(rows - y_off));
+                    let data = band.read_as::<f64>(window, size, size, None)?;
+                    let other_data = other_band.read_as::<f64>(window, size, size, None)?;
+                    let equal = data
+                        .data
+                        .iter()
+                        .zip(&other_data.data)
+                        .all(|(a, b)| (a - b).abs() <= tolerance || (a.is_nan() && b.is_nan()));
+                    if !equal {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Read a window of `window_size` pixels at `window` from each of `bands` (_1-based_),
+    /// stretching the values of each band from its own range in `ranges` to `0..=255`, e.g. to
+    /// display an RGB composite.
+    ///
+    /// Returns one buffer per band, in the order of `bands`, or [`GdalError::BadArgument`] if
+    /// a band index is out of range. See [`RasterBand::read_stretched_u8`] for how values are
+    /// stretched.
+    pub fn read_rgb_stretched(
+        &self,
+        bands: &[usize],
+        window: (isize, isize),
+        window_size: (usize, usize),
+        ranges: &[(f64, f64)],
+    ) -> Result<Vec<Buffer<u8>>> {
+        if bands.len() != ranges.len() {
+            return Err(GdalError::BadArgument(format!(
+                "expected one stretch range per band, got {} bands and {} ranges",
+                bands.len(),
+                ranges.len()
+            )));
+        }
+        let mut stretched = Vec::with_capacity(bands.len());
+        for (&band_index, &(min, max)) in bands.iter().zip(ranges) {
+            if band_index < 1 || band_index > self.raster_count() {
+                return Err(GdalError::BadArgument(format!(
+                    "band index {band_index} is out of range, the dataset has {} bands (1-based)",
+                    self.raster_count()
+                )));
+            }
+            let band = self.rasterband(band_index)?;
+            stretched.push(band.read_stretched_u8(window, window_size, min, max)?);
+        }
+        Ok(stretched)
+    }
+}
+
+/// `Some(value)`, unless `value` is the no-data value.
+fn valid_value(value: f64, no_data: Option<f64>) -> Option<f64> {
+    match no_data {
+        Some(no_data) if no_data == value || (no_data.is_nan() && value.is_nan()) => None,
+        _ => Some(value),
+    }
+}
+
+/// `valid` over `total`, failing if there are no pixels to take a fraction of.
+pub(super) fn valid_fraction(valid: usize, total: usize) -> Result<f64> {
+    if total == 0 {
+        return Err(GdalError::BadArgument(
+            "cannot compute the valid data fraction of a band without pixels".to_string(),
+        ));
+    }
+    Ok(valid as f64 / total as f64)
 }
 
 /// Resampling algorithms used throughout various GDAL raster I/O operations.
@@ -487,6 +708,26 @@ impl<'a> RasterBand<'a> {
         Ok(Buffer { size, data })
     }
 
+    /// Read a [`Buffer<T>`] from this band asynchronously, where `T` implements [`GdalType`].
+    ///
+    /// Same as [`read_as`](Self::read_as), with nearest neighbour resampling, but returns a
+    /// [`Future`](std::future::Future) driving GDAL's asynchronous reader, for drivers able to
+    /// read in the background. See [`AsyncRead`] for details.
+    ///
+    /// # Arguments
+    /// * `window` - the window position from top left
+    /// * `window_size` - the window size (GDAL will interpolate data if `window_size` != `size`)
+    /// * `size` - the desired size of the 'Buffer'
+    pub fn read_async<T: Copy + GdalType>(
+        &self,
+        window: (isize, isize),
+        window_size: (usize, usize),
+        size: (usize, usize),
+    ) -> AsyncRead<'a, T> {
+        let band_index = unsafe { gdal_sys::GDALGetBandNumber(self.c_rasterband) };
+        AsyncRead::new(self.dataset, band_index, window, window_size, size)
+    }
+
     #[cfg(feature = "ndarray")]
     #[cfg_attr(docsrs, doc(cfg(feature = "array")))]
     /// Read a [`Array2<T>`] from this band, where `T` implements [`GdalType`].
@@ -521,6 +762,40 @@ impl<'a> RasterBand<'a> {
         self.read_as::<T>((0, 0), (size.0, size.1), (size.0, size.1), None)
     }
 
+    /// Read a window of `window_size` pixels at `window`, linearly stretching the values from
+    /// `min`..=`max` to `0..=255`, e.g. for display.
+    ///
+    /// Values outside of that range are clamped to `0` or `255`, and NaN values map to `0`.
+    /// `min` and `max` would typically come from [`compute_raster_min_max`](Self::compute_raster_min_max)
+    /// or [`get_statistics`](Self::get_statistics).
+    pub fn read_stretched_u8(
+        &self,
+        window: (isize, isize),
+        window_size: (usize, usize),
+        min: f64,
+        max: f64,
+    ) -> Result<Buffer<u8>> {
+        if min.is_nan() || max.is_nan() || max <= min {
+            return Err(GdalError::BadArgument(format!(
+                "stretch range must be non-empty, got {min}..={max}"
+            )));
+        }
+        let data = self.read_as::<f64>(window, window_size, window_size, None)?;
+        let scale = 255.0 / (max - min);
+        let stretched = data
+            .data
+            .iter()
+            .map(|&v| {
+                if v.is_nan() {
+                    0
+                } else {
+                    ((v - min) * scale).round().clamp(0.0, 255.0) as u8
+                }
+            })
+            .collect();
+        Ok(Buffer::new(data.size, stretched))
+    }
+
     #[cfg(feature = "ndarray")]
     #[cfg_attr(docsrs, doc(cfg(feature = "array")))]
     /// Read a [`Array2<T>`] from a [`Dataset`] block, where `T` implements [`GdalType`].
@@ -784,12 +1059,14 @@ impl<'a> RasterBand<'a> {
     /// Fetch image statistics.
     ///
     /// Returns the minimum, maximum, mean and standard deviation of all pixel values in this band.
+    /// Pixels equal to the no-data value of the band, if any, are excluded.
     /// If approximate statistics are sufficient, the `is_approx_ok` flag can be set to true in which case overviews, or a subset of image tiles may be used in computing the statistics.
     ///
     /// If `force` is `false` results will only be returned if it can be done quickly (i.e. without scanning the data).
     /// If force` is `false` and results cannot be returned efficiently, the method will return `None`.
     ///
     /// Note that file formats using PAM (Persistent Auxiliary Metadata) services will generally cache statistics in the .pam file allowing fast fetch after the first request.
+    /// Statistics cached before the no-data value was set (or changed) still include the former no-data pixels.
     ///
     /// This methods is a wrapper for [`GDALGetRasterStatistics`](https://gdal.org/api/gdalrasterband_cpp.html#_CPPv4N14GDALRasterBand13GetStatisticsEiiPdPdPdPd).
     ///
@@ -847,6 +1124,58 @@ impl<'a> RasterBand<'a> {
             max: min_max[1],
         })
     }
+
+    /// Fetch a sample of up to `count` pixel values, spread over the whole band.
+    ///
+    /// Pixels matching the no-data value are skipped, so fewer values than requested may be
+    /// returned; never more than the number of pixels in the band. This is much cheaper than
+    /// computing full statistics on very large rasters, and is usually sufficient to build
+    /// a histogram or a contrast stretch.
+    ///
+    /// This methods is a wrapper for [`GDALGetRandomRasterSample`](https://gdal.org/api/raster_c_api.html#_CPPv425GDALGetRandomRasterSample15GDALRasterBandHiPf).
+    ///
+    pub fn random_sample(&self, count: usize) -> Result<Vec<f64>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let count = count.min(c_int::MAX as usize);
+        let mut samples = vec![0f32; count];
+        let num_samples = unsafe {
+            GDALGetRandomRasterSample(self.c_rasterband, count as c_int, samples.as_mut_ptr())
+        };
+        if num_samples < 0 {
+            return Err(_last_cpl_err(CPLErr::CE_Failure));
+        }
+        samples.truncate(num_samples as usize);
+        Ok(samples.into_iter().map(f64::from).collect())
+    }
+
+    /// Compute the fraction of the pixels of this band holding valid data, according to its
+    /// mask band (see [`open_mask_band`](Self::open_mask_band)), e.g. `0.5` if half of them
+    /// are no-data.
+    ///
+    /// If `approx` is `true` and the band has overviews, the mask of the smallest overview is
+    /// read instead of the full resolution one, which is much faster on large rasters.
+    ///
+    /// Fails with [`GdalError::BadArgument`] if the mask read has no pixels.
+    pub fn valid_data_fraction(&self, approx: bool) -> Result<f64> {
+        let overview;
+        let band = match self.overview_count()? {
+            count if approx && count > 0 => {
+                overview = self.overview(count as isize - 1)?;
+                &overview
+            }
+            _ => self,
+        };
+        let mask = band.open_mask_band()?;
+        let (cols, rows) = mask.size();
+        let mut valid = 0;
+        for line in 0..rows {
+            let data = mask.read_as::<u8>((0, line as isize), (cols, 1), (cols, 1), None)?;
+            valid += data.data.iter().filter(|&&v| v > 0).count();
+        }
+        valid_fraction(valid, cols * rows)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -863,6 +1192,21 @@ pub struct StatisticsAll {
     pub std_dev: f64,
 }
 
+/// Description of a band of a [`Dataset`], as returned by [`Dataset::band_descriptors`].
+#[derive(Debug, PartialEq)]
+pub struct BandDescriptor {
+    /// Index of the band (_1-based_).
+    pub index: usize,
+    /// Data type of the band, see [`RasterBand::band_type`].
+    pub datatype: GdalDataType,
+    /// No-data value of the band, if any, see [`RasterBand::no_data_value`].
+    pub nodata: Option<f64>,
+    /// Color interpretation of the band, see [`RasterBand::color_interpretation`].
+    pub color_interp: ColorInterpretation,
+    /// Description of the band, empty if unset, see [`Metadata::description`].
+    pub description: String,
+}
+
 impl<'a> MajorObject for RasterBand<'a> {
     fn gdal_object_ptr(&self) -> GDALMajorObjectH {
         self.c_rasterband
@@ -880,6 +1224,34 @@ impl<T: GdalType> Buffer<T> {
     pub fn new(size: (usize, usize), data: Vec<T>) -> Buffer<T> {
         Buffer { size, data }
     }
+
+    #[cfg(feature = "ndarray")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "array")))]
+    /// Borrow the data as an [`ArrayView2<T>`], without copying it.
+    ///
+    /// Fails if the length of the data doesn't match the size of the buffer.
+    ///
+    /// # Note
+    /// The Matrix shape is (rows, cols) and raster shape is (cols in x-axis, rows in y-axis).
+    pub fn as_array_view2(&self) -> Result<ArrayView2<'_, T>> {
+        Ok(ArrayView2::from_shape(
+            (self.size.1, self.size.0),
+            &self.data,
+        )?)
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "array")))]
+    /// Mutably borrow the data as an [`ArrayViewMut2<T>`], without copying it, e.g. to process
+    /// it in place before writing it back.
+    ///
+    /// See [`as_array_view2`](Self::as_array_view2).
+    pub fn as_array_view_mut2(&mut self) -> Result<ArrayViewMut2<'_, T>> {
+        Ok(ArrayViewMut2::from_shape(
+            (self.size.1, self.size.0),
+            &mut self.data,
+        )?)
+    }
 }
 
 pub type ByteBuffer = Buffer<u8>;
@@ -1,9 +1,10 @@
 use crate::dataset::Dataset;
+use crate::errors::GdalError;
 use crate::metadata::Metadata;
-use crate::raster::rasterband::ResampleAlg;
+use crate::raster::rasterband::{valid_fraction, ResampleAlg};
 use crate::raster::{
-    ByteBuffer, ColorEntry, ColorInterpretation, ColorTable, GdalDataType, RasterCreationOption,
-    StatisticsAll, StatisticsMinMax,
+    Buffer, ByteBuffer, ColorEntry, ColorInterpretation, ColorTable, GdalDataType,
+    RasterCreationOption, StatisticsAll, StatisticsMinMax,
 };
 use crate::test_utils::{fixture, TempFixture};
 use crate::vsi::unlink_mem_file;
@@ -357,6 +358,35 @@ fn test_read_raster_as_array() {
     assert_eq!(rb.band_type(), GdalDataType::UInt8);
 }
 
+#[test]
+#[cfg(feature = "ndarray")]
+fn test_buffer_as_array_view() {
+    let dataset = Dataset::open(fixture("tinymarble.tif")).unwrap();
+    let rb = dataset.rasterband(1).unwrap();
+    let mut buffer = rb.read_as::<u8>((19, 5), (3, 4), (3, 4), None).unwrap();
+    let data = arr2(&[
+        [226, 225, 157],
+        [215, 222, 225],
+        [213, 231, 229],
+        [171, 189, 192],
+    ]);
+    let view = buffer.as_array_view2().unwrap();
+    assert_eq!(view, data);
+    assert_eq!(view.as_ptr(), buffer.data.as_ptr());
+
+    let mut view = buffer.as_array_view_mut2().unwrap();
+    view[[1, 2]] = 0;
+    view.row_mut(3).fill(1);
+    assert_eq!(
+        buffer.data,
+        [226, 225, 157, 215, 222, 0, 213, 231, 229, 1, 1, 1]
+    );
+
+    buffer.data.pop();
+    assert!(buffer.as_array_view2().is_err());
+    assert!(buffer.as_array_view_mut2().is_err());
+}
+
 #[test]
 #[cfg(feature = "ndarray")]
 fn test_read_block_as_array() {
@@ -513,6 +543,52 @@ fn test_read_overviews() {
     assert_eq!(overview_4.size(), (25, 13));
 }
 
+#[test]
+fn test_ensure_overviews() {
+    let source = Dataset::open(fixture("tinymarble.tif")).unwrap();
+    let tmp = TempFixture::empty("tinymarble_ovr.tif");
+    let driver = DriverManager::get_driver_by_name("GTiff").unwrap();
+    let mut dataset = source.create_copy(&driver, tmp.path(), &[]).unwrap();
+    assert_eq!(dataset.rasterband(1).unwrap().overview_count().unwrap(), 0);
+
+    dataset.ensure_overviews(2, "AVERAGE").unwrap();
+    let band = dataset.rasterband(1).unwrap();
+    assert_eq!(band.overview_count().unwrap(), 2);
+    assert_eq!(band.overview(1).unwrap().size(), (25, 13));
+
+    // Mark the existing overview, to find out whether it gets rebuilt.
+    let mut overview = band.overview(0).unwrap();
+    overview
+        .write((0, 0), (1, 1), &Buffer::new((1, 1), vec![42u8]))
+        .unwrap();
+    dataset.ensure_overviews(2, "AVERAGE").unwrap();
+    let band = dataset.rasterband(1).unwrap();
+    assert_eq!(band.overview_count().unwrap(), 2);
+    let marker = band
+        .overview(0)
+        .unwrap()
+        .read_as::<u8>((0, 0), (1, 1), (1, 1), None)
+        .unwrap();
+    assert_eq!(marker.data, [42]);
+
+    // Only the missing level is added.
+    dataset.ensure_overviews(3, "AVERAGE").unwrap();
+    let band = dataset.rasterband(1).unwrap();
+    assert_eq!(band.overview_count().unwrap(), 3);
+    let marker = band
+        .overview(0)
+        .unwrap()
+        .read_as::<u8>((0, 0), (1, 1), (1, 1), None)
+        .unwrap();
+    assert_eq!(marker.data, [42]);
+
+    assert!(matches!(
+        dataset.ensure_overviews(31, "AVERAGE"),
+        Err(GdalError::BadArgument(_))
+    ));
+    assert_eq!(dataset.rasterband(1).unwrap().overview_count().unwrap(), 3);
+}
+
 #[test]
 fn test_fail_read_overviews() {
     let dataset = Dataset::open(fixture("offset_scaled_tinymarble.tif")).unwrap();
@@ -613,6 +689,75 @@ fn test_rasterize() {
     );
 }
 
+#[test]
+fn test_polygonize_to_geopackage() {
+    use crate::spatial_ref::SpatialRef;
+    use crate::vector::sql::Dialect;
+    use crate::vector::{LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType};
+
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let mut dataset = driver.create("", 4, 4, 1).unwrap();
+    dataset
+        .set_geo_transform(&[0.0, 1.0, 0.0, 4.0, 0.0, -1.0])
+        .unwrap();
+    let srs = SpatialRef::from_epsg(32631).unwrap();
+    dataset.set_spatial_ref(&srs).unwrap();
+    // Left half 1, right half 2, with a 3 in the lower right corner.
+    let mut values = [1u8, 1, 2, 2].repeat(4);
+    values[15] = 3;
+    dataset
+        .rasterband(1)
+        .unwrap()
+        .write((0, 0), (4, 4), &Buffer::new((4, 4), values))
+        .unwrap();
+
+    let path = TempFixture::empty("polygonized.gpkg");
+    let gpkg = DriverManager::get_driver_by_name("GPKG").unwrap();
+    let mut out = gpkg
+        .create_vector_only_with_options(path.path(), &["VERSION=1.2"])
+        .unwrap();
+    let mut layer = out
+        .create_layer(LayerOptions {
+            name: "regions",
+            srs: Some(&srs),
+            ty: OGRwkbGeometryType::wkbPolygon,
+            options: Some(&["SPATIAL_INDEX=YES"]),
+        })
+        .unwrap();
+    layer
+        .create_defn_fields(&[("value", OGRFieldType::OFTInteger)])
+        .unwrap();
+    super::polygonize(
+        &dataset.rasterband(1).unwrap(),
+        None,
+        &mut layer,
+        Some(0),
+        super::PolygonizeOptions::default(),
+    )
+    .unwrap();
+    drop(out);
+
+    let out = Dataset::open(path.path()).unwrap();
+    let mut layer = out.layer_by_name("regions").unwrap();
+    assert_eq!(layer.feature_count(), 3);
+    let mut found: Vec<_> = layer
+        .features()
+        .map(|f| f.field_as_integer(0).unwrap().unwrap())
+        .collect();
+    found.sort_unstable();
+    assert_eq!(found, [1, 2, 3]);
+    let mut has_index = out
+        .execute_sql(
+            "SELECT HasSpatialIndex('regions', 'geom')",
+            None,
+            Dialect::DEFAULT,
+        )
+        .unwrap()
+        .unwrap();
+    let has_index = has_index.features().next().unwrap().field_as_integer(0);
+    assert_eq!(has_index.unwrap(), Some(1));
+}
+
 #[test]
 fn test_rasterband_unit() {
     let dataset = Dataset::open(fixture("tinymarble.tif")).unwrap();
@@ -751,6 +896,322 @@ fn test_raster_stats() {
     );
 }
 
+#[test]
+fn test_raster_stats_nodata() {
+    // A 10x10 band framed by no-data, as left on the edges of a reprojected raster.
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let dataset = driver
+        .create_with_band_type::<f32, _>("", 10, 10, 1)
+        .unwrap();
+    let mut rb = dataset.rasterband(1).unwrap();
+    let data: Vec<f32> = (0..100)
+        .map(|i| {
+            let (col, row) = (i % 10, i / 10);
+            if (2..8).contains(&col) && (2..8).contains(&row) {
+                (col + row) as f32
+            } else {
+                -9999.0
+            }
+        })
+        .collect();
+    rb.write((0, 0), (10, 10), &Buffer::new((10, 10), data.clone()))
+        .unwrap();
+    rb.set_no_data_value(Some(-9999.0)).unwrap();
+
+    let valid: Vec<f64> = data
+        .iter()
+        .filter(|&&v| v != -9999.0)
+        .map(|&v| v as f64)
+        .collect();
+    assert_eq!(valid.len(), 36);
+    let mean = valid.iter().sum::<f64>() / valid.len() as f64;
+    let std_dev =
+        (valid.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / valid.len() as f64).sqrt();
+
+    let stats = rb.get_statistics(true, false).unwrap().unwrap();
+    assert_eq!((stats.min, stats.max), (4.0, 14.0));
+    assert!((stats.mean - mean).abs() < 1e-9, "{stats:?}");
+    assert!((stats.std_dev - std_dev).abs() < 1e-9, "{stats:?}");
+
+    assert_eq!(
+        rb.compute_raster_min_max(false).unwrap(),
+        StatisticsMinMax {
+            min: 4.0,
+            max: 14.0,
+        }
+    );
+}
+
+#[test]
+fn test_read_stretched_u8() {
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let dataset = driver.create_with_band_type::<u16, _>("", 5, 1, 1).unwrap();
+    let mut rb = dataset.rasterband(1).unwrap();
+    let data = vec![1000u16, 1500, 2000, 500, 60000];
+    rb.write((0, 0), (5, 1), &Buffer::new((5, 1), data))
+        .unwrap();
+
+    let stretched = rb
+        .read_stretched_u8((0, 0), (5, 1), 1000.0, 2000.0)
+        .unwrap();
+    assert_eq!(stretched.size, (5, 1));
+    // Range endpoints, midpoint, then clamped values below and above.
+    assert_eq!(stretched.data, [0, 128, 255, 0, 255]);
+
+    let min_max = rb.compute_raster_min_max(false).unwrap();
+    let stretched = rb
+        .read_stretched_u8((0, 0), (5, 1), min_max.min, min_max.max)
+        .unwrap();
+    assert_eq!((stretched.data[3], stretched.data[4]), (0, 255));
+
+    assert!(rb.read_stretched_u8((0, 0), (5, 1), 10.0, 10.0).is_err());
+}
+
+#[test]
+fn test_band_descriptors() {
+    let dataset = Dataset::open(fixture("tinymarble.tif")).unwrap();
+    let descriptors = dataset.band_descriptors().unwrap();
+    assert_eq!(descriptors.len(), 3);
+    for (i, (descriptor, color_interp)) in descriptors
+        .iter()
+        .zip([
+            ColorInterpretation::RedBand,
+            ColorInterpretation::GreenBand,
+            ColorInterpretation::BlueBand,
+        ])
+        .enumerate()
+    {
+        assert_eq!(descriptor.index, i + 1);
+        assert_eq!(descriptor.datatype, GdalDataType::UInt8);
+        assert_eq!(descriptor.nodata, None);
+        assert_eq!(descriptor.color_interp, color_interp);
+    }
+
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let dataset = driver.create_with_band_type::<f32, _>("", 1, 1, 3).unwrap();
+    dataset
+        .rasterband(2)
+        .unwrap()
+        .set_no_data_value(Some(-9999.0))
+        .unwrap();
+    dataset
+        .rasterband(3)
+        .unwrap()
+        .set_description("elevation")
+        .unwrap();
+    let descriptors = dataset.band_descriptors().unwrap();
+    assert_eq!(descriptors.len(), 3);
+    assert!(descriptors
+        .iter()
+        .all(|descriptor| descriptor.datatype == GdalDataType::Float32));
+    let nodata: Vec<_> = descriptors.iter().map(|d| d.nodata).collect();
+    assert_eq!(nodata, [None, Some(-9999.0), None]);
+    assert_eq!(descriptors[2].description, "elevation");
+}
+
+#[test]
+fn test_read_rgb_stretched() {
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let dataset = driver.create_with_band_type::<f32, _>("", 2, 1, 3).unwrap();
+    let ranges = [(0.0, 1.0), (100.0, 200.0), (-50.0, 50.0)];
+    let values = [[0.0, 1.0], [200.0, 100.0], [50.0, 50.0]];
+    for (i, band_values) in values.iter().enumerate() {
+        let mut rb = dataset.rasterband(i + 1).unwrap();
+        let data = band_values.to_vec();
+        rb.write((0, 0), (2, 1), &Buffer::new((2, 1), data))
+            .unwrap();
+    }
+
+    let rgb = dataset
+        .read_rgb_stretched(&[1, 2, 3], (0, 0), (2, 1), &ranges)
+        .unwrap();
+    assert_eq!(rgb.len(), 3);
+    assert!(rgb.iter().all(|band| band.size == (2, 1)));
+    assert_eq!(rgb[0].data, [0, 255]);
+    assert_eq!(rgb[1].data, [255, 0]);
+    assert_eq!(rgb[2].data, [255, 255]);
+
+    let bgr = dataset
+        .read_rgb_stretched(
+            &[3, 2, 1],
+            (0, 0),
+            (2, 1),
+            &[ranges[2], ranges[1], ranges[0]],
+        )
+        .unwrap();
+    assert_eq!(bgr[0].data, [255, 255]);
+    assert_eq!(bgr[1].data, [255, 0]);
+    assert_eq!(bgr[2].data, [0, 255]);
+
+    assert!(dataset
+        .read_rgb_stretched(&[1, 2, 3], (0, 0), (2, 1), &ranges[..2])
+        .is_err());
+    assert!(matches!(
+        dataset.read_rgb_stretched(&[0, 1, 2], (0, 0), (2, 1), &ranges),
+        Err(GdalError::BadArgument(_))
+    ));
+    assert!(matches!(
+        dataset.read_rgb_stretched(&[1, 2, 4], (0, 0), (2, 1), &ranges),
+        Err(GdalError::BadArgument(_))
+    ));
+}
+
+#[test]
+fn test_value_at() {
+    use crate::GeoTransformEx;
+
+    let dataset = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif")).unwrap();
+    let gt = dataset.geo_transform().unwrap();
+    let band = dataset.rasterband(2).unwrap();
+    for (pixel, line) in [(0, 0), (10, 20), (299, 299)] {
+        let expected = band
+            .read_as::<f64>((pixel, line), (1, 1), (1, 1), None)
+            .unwrap()
+            .data[0];
+        let (x, y) = gt.apply(pixel as f64 + 0.5, line as f64 + 0.5);
+        assert_eq!(dataset.value_at(x, y, 2).unwrap(), Some(expected));
+    }
+
+    // Out of bounds
+    let (x, y) = gt.apply(-0.5, 0.5);
+    assert_eq!(dataset.value_at(x, y, 2).unwrap(), None);
+    let (x, y) = gt.apply(0.5, 300.5);
+    assert_eq!(dataset.value_at(x, y, 2).unwrap(), None);
+    assert!(dataset.value_at(x, y, 5).is_err());
+
+    // No-data
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let mut mem = driver.create_with_band_type::<u8, _>("", 2, 1, 1).unwrap();
+    mem.set_geo_transform(&[0.0, 1.0, 0.0, 1.0, 0.0, -1.0])
+        .unwrap();
+    let mut band = mem.rasterband(1).unwrap();
+    band.write((0, 0), (2, 1), &Buffer::new((2, 1), vec![7u8, 255]))
+        .unwrap();
+    band.set_no_data_value(Some(255.0)).unwrap();
+    assert_eq!(mem.value_at(0.5, 0.5, 1).unwrap(), Some(7.0));
+    assert_eq!(mem.value_at(1.5, 0.5, 1).unwrap(), None);
+}
+
+#[test]
+fn test_sample_points() {
+    use crate::GeoTransformEx;
+
+    let dataset = Dataset::open(fixture("labels.tif")).unwrap();
+    let gt = dataset.geo_transform().unwrap();
+    let (cols, rows) = dataset.raster_size();
+    let band = dataset.rasterband(1).unwrap();
+    let no_data = band.no_data_value();
+    let all = band.read_band_as::<u8>().unwrap();
+
+    // A grid of points, visited column-major to cross blocks back and forth,
+    // followed by one point outside of the raster.
+    let mut pixels = Vec::new();
+    for pixel in (0..cols).step_by(7) {
+        for line in (0..rows).step_by(5) {
+            pixels.push((pixel, line));
+        }
+    }
+    let mut coords: Vec<_> = pixels
+        .iter()
+        .map(|&(p, l)| gt.apply(p as f64 + 0.5, l as f64 + 0.5))
+        .collect();
+    coords.push(gt.apply(-1.0, -1.0));
+
+    let values = dataset.sample_points(&coords, 1).unwrap();
+    assert_eq!(values.len(), coords.len());
+    assert_eq!(values.last(), Some(&None));
+    for (&(p, l), value) in pixels.iter().zip(&values) {
+        let expected = all.data[l * cols + p] as f64;
+        if Some(expected) == no_data {
+            assert_eq!(*value, None);
+        } else {
+            assert_eq!(*value, Some(expected));
+        }
+    }
+
+    assert!(dataset.sample_points(&[], 1).unwrap().is_empty());
+}
+
+#[test]
+fn test_raster_equals() {
+    let dataset = Dataset::open(fixture("tinymarble.tif")).unwrap();
+    assert!(dataset.raster_equals(&dataset, 0.0).unwrap());
+
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let copy = dataset.create_copy(&driver, "", &[]).unwrap();
+    assert!(dataset.raster_equals(&copy, 0.0).unwrap());
+
+    // Bump a single pixel of the last band.
+    let mut band = copy.rasterband(3).unwrap();
+    let (cols, rows) = band.size();
+    let (x, y) = (cols as isize - 1, rows as isize - 1);
+    let pixel = band.read_as::<u8>((x, y), (1, 1), (1, 1), None).unwrap();
+    let bumped = Buffer::new((1, 1), vec![pixel.data[0].wrapping_add(2)]);
+    band.write((x, y), (1, 1), &bumped).unwrap();
+    assert!(!dataset.raster_equals(&copy, 0.0).unwrap());
+    assert!(!dataset.raster_equals(&copy, 1.0).unwrap());
+    assert!(dataset.raster_equals(&copy, 254.0).unwrap());
+
+    let fewer_bands = driver
+        .create_with_band_type::<u8, _>("", cols as isize, rows as isize, 1)
+        .unwrap();
+    assert!(!dataset.raster_equals(&fewer_bands, 0.0).unwrap());
+}
+
+#[test]
+fn test_random_sample() {
+    let fixture = TempFixture::fixture("tinymarble.tif");
+
+    let dataset = Dataset::open(&fixture).unwrap();
+    let rb = dataset.rasterband(1).unwrap();
+
+    let sample = rb.random_sample(1000).unwrap();
+    assert!(!sample.is_empty());
+    assert!(sample.len() <= 1000);
+    assert!(sample.iter().all(|v| (0.0..=255.0).contains(v)));
+
+    // Bounded by the number of pixels
+    let (cols, rows) = dataset.raster_size();
+    let sample = rb.random_sample(10 * cols * rows).unwrap();
+    assert!(!sample.is_empty());
+    assert!(sample.len() <= cols * rows);
+
+    assert!(rb.random_sample(0).unwrap().is_empty());
+}
+
+#[test]
+fn test_valid_data_fraction() {
+    let file = TempFixture::empty("half_nodata.tif");
+    let driver = DriverManager::get_driver_by_name("GTiff").unwrap();
+    let dataset = driver
+        .create_with_band_type::<u8, _>(file.path(), 64, 64, 1)
+        .unwrap();
+    let mut band = dataset.rasterband(1).unwrap();
+    band.set_no_data_value(Some(0.0)).unwrap();
+    // The left half is no-data.
+    let values = (0..64 * 64).map(|i| u8::from(i % 64 >= 32)).collect();
+    band.write((0, 0), (64, 64), &Buffer::new((64, 64), values))
+        .unwrap();
+    drop(dataset);
+
+    let mut dataset = Dataset::open(file.path()).unwrap();
+    let band = dataset.rasterband(1).unwrap();
+    assert_eq!(band.valid_data_fraction(false).unwrap(), 0.5);
+    // Without overviews, the full resolution mask is read.
+    assert_eq!(band.valid_data_fraction(true).unwrap(), 0.5);
+
+    dataset.build_overviews("NEAREST", &[2, 4], &[]).unwrap();
+    let band = dataset.rasterband(1).unwrap();
+    let approx = band.valid_data_fraction(true).unwrap();
+    assert!((approx - 0.5).abs() < 0.1, "{approx}");
+
+    assert_eq!(valid_fraction(16, 64).unwrap(), 0.25);
+    assert!(matches!(
+        valid_fraction(0, 0),
+        Err(GdalError::BadArgument(_))
+    ));
+}
+
 #[test]
 fn test_resample_str() {
     assert!(ResampleAlg::from_str("foobar").is_err());
@@ -762,3 +1223,110 @@ fn test_resample_str() {
         assert_eq!(parsed.unwrap(), e, "{stringed}");
     }
 }
+
+/// Poll `future` to completion on the current thread, sleeping until woken in between.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[test]
+fn test_read_async() {
+    let dataset = Dataset::open(fixture("tinymarble.tif")).unwrap();
+    let band = dataset.rasterband(2).unwrap();
+
+    let sync = band
+        .read_as::<u8>((20, 10), (30, 20), (30, 20), None)
+        .unwrap();
+    let read = band.read_async::<u8>((20, 10), (30, 20), (30, 20));
+    let async_ = block_on(read).unwrap();
+    assert_eq!(async_.size, (30, 20));
+    assert_eq!(async_.data, sync.data);
+
+    // Resampled, and converted to another type
+    let sync = band
+        .read_as::<f32>((0, 0), (100, 50), (10, 5), None)
+        .unwrap();
+    let async_ = block_on(band.read_async::<f32>((0, 0), (100, 50), (10, 5))).unwrap();
+    assert_eq!(async_.data, sync.data);
+
+    // Out of the raster
+    assert!(block_on(band.read_async::<u8>((90, 40), (20, 20), (20, 20))).is_err());
+
+    // Read during the first poll, as an in-memory dataset can't be opened again
+    let driver = DriverManager::get_driver_by_name("MEM").unwrap();
+    let mem = driver.create_with_band_type::<u8, _>("", 4, 3, 1).unwrap();
+    let mut mem_band = mem.rasterband(1).unwrap();
+    let values = Buffer::new((4, 3), (0..12).collect());
+    mem_band.write((0, 0), (4, 3), &values).unwrap();
+    let async_ = block_on(mem_band.read_async::<u8>((0, 0), (4, 3), (4, 3))).unwrap();
+    assert_eq!(async_.data, values.data);
+}
+
+#[test]
+#[cfg(any(all(major_ge_3, minor_ge_1), major_ge_4))]
+fn test_validate_cog() {
+    let gtiff = DriverManager::get_driver_by_name("GTiff").unwrap();
+    let plain_file = TempFixture::empty("plain_tiled.tif");
+    let plain = gtiff
+        .create_with_band_type_with_options::<u8, _>(
+            plain_file.path(),
+            1024,
+            1024,
+            1,
+            &[RasterCreationOption {
+                key: "TILED",
+                value: "YES",
+            }],
+        )
+        .unwrap();
+    let values = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    plain
+        .rasterband(1)
+        .unwrap()
+        .write((0, 0), (1024, 1024), &Buffer::new((1024, 1024), values))
+        .unwrap();
+    drop(plain);
+    let mut plain =
+        Dataset::open_with_mode(plain_file.path(), crate::GdalOpenMode::Update).unwrap();
+    plain.build_overviews("AVERAGE", &[2, 4], &[]).unwrap();
+
+    // The overviews are appended after the full resolution image.
+    let validation = plain.validate_cog().unwrap();
+    assert!(!validation.is_valid());
+    assert!(validation
+        .errors
+        .iter()
+        .any(|error| error.contains("main resolution image")));
+
+    let cog_file = TempFixture::empty("valid.tif");
+    let cog_driver = DriverManager::get_driver_by_name("COG").unwrap();
+    let cog = plain
+        .create_copy(&cog_driver, cog_file.path(), &[])
+        .unwrap();
+    let validation = cog.validate_cog().unwrap();
+    assert!(validation.is_valid(), "{validation:?}");
+    assert!(validation.warnings.is_empty(), "{validation:?}");
+
+    let mem = DriverManager::get_driver_by_name("MEM").unwrap();
+    let dataset = mem.create("", 10, 10, 1).unwrap();
+    assert!(!dataset.validate_cog().unwrap().is_valid());
+}
@@ -0,0 +1,163 @@
+use std::path::Path;
+
+use gdal_sys::{OGRFieldType, OGRwkbGeometryType, OSRAxisMappingStrategy};
+
+use crate::errors::{GdalError, Result};
+use crate::spatial_ref::SpatialRef;
+use crate::vector::{FieldValue, Geometry, LayerAccess, LayerOptions};
+use crate::{Dataset, DriverManager, GeoTransformEx};
+
+/// Name of the field holding the path of each tile, as expected by the GTI driver by default.
+const LOCATION_FIELD: &str = "location";
+
+/// Create a tile index over `tiles`, at `index_path`, in the [GeoPackage] format.
+///
+/// The index holds one feature per tile, with the tile footprint as geometry and its path in
+/// a `location` field, in the spatial reference of the first tile. This is the same layout as the
+/// one produced by [`gdaltindex`], and can be opened as a single virtual mosaic with
+/// [`open_tile_index`].
+///
+/// Returns the (vector) tile index dataset.
+///
+/// [GeoPackage]: https://gdal.org/drivers/vector/gpkg.html
+/// [`gdaltindex`]: https://gdal.org/programs/gdaltindex.html
+pub fn create_tile_index<P: AsRef<Path>, T: AsRef<Path>>(
+    index_path: P,
+    tiles: &[T],
+) -> Result<Dataset> {
+    let first = tiles
+        .first()
+        .ok_or_else(|| GdalError::BadArgument("a tile index needs at least one tile".into()))?;
+    let mut index_srs = Dataset::open(first.as_ref())?.spatial_ref()?;
+    index_srs.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+
+    let driver = DriverManager::get_driver_by_name("GPKG")?;
+    let mut index = driver.create_vector_only(index_path.as_ref())?;
+    let mut layer = index.create_layer(LayerOptions {
+        name: "tile_index",
+        srs: Some(&index_srs),
+        ty: OGRwkbGeometryType::wkbPolygon,
+        ..Default::default()
+    })?;
+    layer.create_defn_fields(&[(LOCATION_FIELD, OGRFieldType::OFTString)])?;
+
+    for tile in tiles {
+        let tile = tile.as_ref();
+        let footprint = tile_footprint(&Dataset::open(tile)?, &index_srs)?;
+        layer.create_feature_fields(
+            footprint,
+            &[LOCATION_FIELD],
+            &[FieldValue::StringValue(tile.to_string_lossy().into_owned())],
+        )?;
+    }
+    Ok(index)
+}
+
+/// Open the tile index at `index_path` (e.g. created by [`create_tile_index`]) as a
+/// single raster mosaic of its tiles, with the [GTI] driver.
+///
+/// The GTI driver is available from GDAL 3.9; an error is returned if it is missing.
+///
+/// [GTI]: https://gdal.org/drivers/raster/gti.html
+pub fn open_tile_index<P: AsRef<Path>>(index_path: P) -> Result<Dataset> {
+    if !has_gti_driver() {
+        return Err(GdalError::BadArgument(
+            "opening a tile index requires the GTI driver (GDAL 3.9 or later)".into(),
+        ));
+    }
+    let mut path = "GTI:".to_owned();
+    path.push_str(&index_path.as_ref().to_string_lossy());
+    Dataset::open(path)
+}
+
+/// Whether the GTI driver is available.
+fn has_gti_driver() -> bool {
+    DriverManager::get_driver_by_name("GTI").is_ok()
+}
+
+/// Footprint polygon of `tile`, expressed in `srs`.
+fn tile_footprint(tile: &Dataset, srs: &SpatialRef) -> Result<Geometry> {
+    let gt = tile.geo_transform()?;
+    let (cols, rows) = tile.raster_size();
+    let (cols, rows) = (cols as f64, rows as f64);
+    let corners = [
+        (0.0, 0.0),
+        (cols, 0.0),
+        (cols, rows),
+        (0.0, rows),
+        (0.0, 0.0),
+    ]
+    .iter()
+    .map(|&(pixel, line)| {
+        let (x, y) = gt.apply(pixel, line);
+        format!("{x} {y}")
+    })
+    .collect::<Vec<_>>();
+    let mut footprint = Geometry::from_wkt(&format!("POLYGON (({}))", corners.join(", ")))?;
+
+    let mut tile_srs = tile.spatial_ref()?;
+    if &tile_srs != srs {
+        tile_srs.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+        footprint.set_spatial_ref(tile_srs);
+        footprint.transform_to_inplace(srs)?;
+    }
+    Ok(footprint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::Buffer;
+    use crate::test_utils::TempFixture;
+
+    /// Write a 10x10 GeoTIFF tile filled with `value`, with its upper-left corner at (`x`, 10).
+    fn write_tile(path: &Path, x: f64, value: u8) -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("GTiff")?;
+        let mut ds = driver.create_with_band_type::<u8, _>(path, 10, 10, 1)?;
+        ds.set_geo_transform(&[x, 1.0, 0.0, 10.0, 0.0, -1.0])?;
+        ds.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        ds.rasterband(1)?
+            .write((0, 0), (10, 10), &Buffer::new((10, 10), vec![value; 100]))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_tile_index() -> Result<()> {
+        let index_path = TempFixture::empty("index.gti.gpkg");
+        let left = index_path.path().with_file_name("left.tif");
+        let right = index_path.path().with_file_name("right.tif");
+        write_tile(&left, 0.0, 1)?;
+        write_tile(&right, 10.0, 2)?;
+
+        let index = create_tile_index(index_path.path(), &[&left, &right])?;
+        let mut layer = index.layer(0)?;
+        assert_eq!(layer.feature_count(), 2);
+        let locations: Vec<_> = layer
+            .features()
+            .filter_map(|f| f.field_as_string_by_name(LOCATION_FIELD).ok().flatten())
+            .collect();
+        assert_eq!(
+            locations,
+            [
+                left.to_string_lossy().into_owned(),
+                right.to_string_lossy().into_owned()
+            ]
+        );
+        drop(index);
+
+        assert!(create_tile_index::<_, &Path>(index_path.path(), &[]).is_err());
+
+        if !has_gti_driver() {
+            assert!(open_tile_index(index_path.path()).is_err());
+            return Ok(());
+        }
+        let mosaic = open_tile_index(index_path.path())?;
+        assert_eq!(mosaic.raster_size(), (20, 10));
+        // Across the boundary between the tiles
+        let row = mosaic
+            .rasterband(1)?
+            .read_as::<u8>((8, 5), (4, 1), (4, 1), None)?;
+        assert_eq!(row.data, [1, 1, 2, 2]);
+        Ok(())
+    }
+}
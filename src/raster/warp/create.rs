@@ -0,0 +1,2648 @@
+use std::ffi::CString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use gdal_sys::{self, CPLErr, OSRAxisMappingStrategy};
+
+use super::grid::{contains_pole, output_grid};
+use super::operation::{create_warped_vrt, round_to_type, warp_with, Transformer, WarpOperation};
+use super::reproject::{has_transformer_settings, reproject_image, with_transformer_cache_setting};
+use super::streaming::STREAMING_BLOCK_SIZE;
+use crate::cpl::CslStringList;
+use crate::dataset::Dataset;
+use crate::errors::*;
+use crate::raster::{
+    Buffer, ColorInterpretation, CreateReprojectOptions, GdalDataType, GdalWarpOptions,
+    RasterCreationOption, WarpResampleAlg,
+};
+use crate::spatial_ref::{CoordTransform, SpatialRef};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err, _path_to_c_string};
+use crate::vsi::{call_on_mem_file_bytes, get_vsi_mem_file_bytes_owned, stat, unlink_mem_file};
+use crate::{Driver, DriverManager, GdalOpenMode, GeoTransform, GeoTransformEx, Metadata};
+
+/// Reproject `ds` into a new file at `dst_file`, in the `dst_srs` spatial reference.
+///
+/// The output extent and resolution are computed by GDAL to cover the whole source.
+/// The output format defaults to GeoTIFF; see [`CreateReprojectOptions`] for other settings.
+///
+/// When warping a source containing a pole, e.g. in a polar stereographic projection, to
+/// geographic coordinates, the output extends to that pole and spans all longitudes. Its
+/// other bound in latitude is found on the edges of the source, so sampling them more
+/// densely, with [`GdalWarpOptions::with_sample_steps`], makes it more accurate; with
+/// [`GdalWarpOptions::with_sample_grid`] as well, the whole source is sampled, for sources
+/// whose edges don't hold that bound either.
+///
+/// A north-up source already in `dst_srs` (see [`SpatialRef::is_same`]) is copied instead of
+/// being resampled onto its own grid, unless `options` change its grid, data type, no-data
+/// values, bands or coordinate transformation, or set warp options other than the resampling
+/// algorithm and the performance settings (memory limit, chunking and number of threads).
+///
+/// Returns the grid and spatial reference the output was created with.
+///
+/// See [`GDALCreateAndReprojectImage`](https://gdal.org/api/gdalwarp_cpp.html#_CPPv427GDALCreateAndReprojectImage12GDALDatasetHPKcPKcPKc11GDALDriverHPPc15GDALResampleAlgddP16GDALProgressFuncPvP15GDALWarpOptions).
+pub fn create_and_reproject<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_file: P,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<WarpReport> {
+    let out = create_and_reproject_dataset(ds, dst_file, dst_srs, options)?;
+    WarpReport::from_datasets(ds, &out)
+}
+
+/// Same as [`create_and_reproject`], but returns the output dataset, opened in update mode,
+/// instead of a report of its grid.
+///
+/// The destination no-data value is already set on its bands, unless
+/// [`CreateReprojectOptions::with_skip_nodata_reopen`] is set.
+pub fn create_and_reproject_dataset<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_file: P,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<Dataset> {
+    let dst_file = dst_file.as_ref();
+    match create_and_reproject_image(ds, dst_file, dst_srs, options)? {
+        Some(out) => Ok(out),
+        None => Dataset::open_with_mode(dst_file, GdalOpenMode::Update),
+    }
+}
+
+/// Whether [`create_and_reproject_if_stale`] reprojected its source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReprojectOutcome {
+    /// The destination was missing or stale, and has been (re)created.
+    Reprojected,
+    /// The destination was up to date, and has been left alone.
+    UpToDate,
+}
+
+/// Same as [`create_and_reproject`], unless `dst_file` is up to date: it already exists, was
+/// modified no earlier than the file `ds` was opened from, and is in the `dst_srs` spatial
+/// reference.
+///
+/// Modification times come from [`stat`], so any virtual file system is supported, and are
+/// compared to the second. A source without a file to compare with, e.g. an in-memory
+/// dataset, is always reprojected. Changes to `options` since the destination was created
+/// aren't detected.
+pub fn create_and_reproject_if_stale<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_file: P,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<ReprojectOutcome> {
+    let dst_file = dst_file.as_ref();
+    if is_up_to_date(ds, dst_file, dst_srs)? {
+        return Ok(ReprojectOutcome::UpToDate);
+    }
+    create_and_reproject_image(ds, dst_file, dst_srs, options)?;
+    Ok(ReprojectOutcome::Reprojected)
+}
+
+/// Whether `dst_file` is newer than the file of `ds`, and in the `dst_srs` spatial reference.
+fn is_up_to_date(ds: &Dataset, dst_file: &Path, dst_srs: &SpatialRef) -> Result<bool> {
+    let (src_stat, dst_stat) = match (stat(ds.description()?)?, stat(dst_file)?) {
+        (Some(src_stat), Some(dst_stat)) => (src_stat, dst_stat),
+        _ => return Ok(false),
+    };
+    if dst_stat.modified < src_stat.modified {
+        return Ok(false);
+    }
+    // The destination may not even be a dataset anymore, e.g. if a previous run was aborted.
+    let up_to_date = Dataset::open(dst_file)
+        .and_then(|dst| dst.spatial_ref())
+        .map_or(false, |srs| &srs == dst_srs);
+    Ok(up_to_date)
+}
+
+/// Same as [`create_and_reproject`], then builds the overviews given by `overview_levels`
+/// (decimation factors, e.g. `&[2, 4, 8]`) on the output.
+///
+/// The overviews are computed with the overview resampling algorithm of the warp options
+/// (see [`GdalWarpOptions::with_overview_resampling`]), where GDAL supports it for overviews,
+/// and with nearest neighbour otherwise.
+///
+/// Returns the output dataset, opened in update mode.
+pub fn create_and_reproject_with_overviews<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_file: P,
+    dst_srs: &SpatialRef,
+    overview_levels: &[i32],
+    options: &CreateReprojectOptions,
+) -> Result<Dataset> {
+    let mut out = create_and_reproject_dataset(ds, dst_file, dst_srs, options)?;
+    if !overview_levels.is_empty() {
+        let resampling = overview_resampling(options.warp_options().overview_resampling());
+        out.build_overviews(resampling, overview_levels, &[])?;
+    }
+    Ok(out)
+}
+
+/// Reproject `ds` into a new [Cloud Optimized GeoTIFF][COG] at `dst_file`, in the `dst_srs`
+/// spatial reference.
+///
+/// This is the `gdalwarp -of VRT` then `gdal_translate -of COG` recipe: the source is wrapped
+/// in a warped VRT (see [`GDALCreateWarpedVRT`]), which is then copied with the COG driver.
+/// Only the blocks being written are warped at any time, so this needs much less memory than
+/// [`create_and_reproject`] for huge inputs, and the output grid is the same.
+///
+/// The output format set in `options` is ignored, and source windows, post-warp functions,
+/// scaling and custom transformers are not supported, nor are output data types which can't
+/// hold the source values without losing precision. The creation options (e.g. the
+/// compression) are passed to the COG driver. Returns the output dataset.
+///
+/// [COG]: https://gdal.org/drivers/raster/cog.html
+/// [`GDALCreateWarpedVRT`]: https://gdal.org/api/gdalwarp_cpp.html#_CPPv419GDALCreateWarpedVRT12GDALDatasetHiiPdP15GDALWarpOptions
+pub fn reproject_to_cog<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_file: P,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<Dataset> {
+    check_no_premultiply_alpha(options)?;
+    let warp_options = options.warp_options();
+    if options.source_window().is_some()
+        || options.src_extent().is_some()
+        || options.output_scaling().is_some()
+        || options.has_post_band_fn()
+        || warp_options.has_custom_transformer()
+    {
+        return Err(GdalError::BadArgument(
+            "unsupported option for reprojecting through a warped VRT".into(),
+        ));
+    }
+    let options = &*options.resolved_for(ds, ds.raster_count())?;
+    let grid = output_grid(ds, dst_srs, options)?;
+    let vrt = warped_vrt_on_grid(ds, dst_srs, grid, options)?.ok_or_else(|| {
+        GdalError::BadArgument(
+            "output data type can't hold the source values without losing precision".into(),
+        )
+    })?;
+
+    let driver = DriverManager::get_driver_by_name("COG")?;
+    let mut creation_options = options.creation_options(ds)?;
+    let explicit = creation_options
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("OVERVIEW_RESAMPLING"));
+    if let Some(alg) = warp_options
+        .explicit_overview_resampling()
+        .filter(|_| !explicit)
+    {
+        creation_options.push((
+            "OVERVIEW_RESAMPLING".to_string(),
+            overview_resampling(alg).to_string(),
+        ));
+    }
+    let dst_file = dst_file.as_ref();
+    let out = vrt.create_copy(
+        &driver,
+        dst_file,
+        &to_raster_creation_options(&creation_options),
+    )?;
+    finish_with_pam_stats(out, dst_file, options, || Dataset::open(dst_file))
+}
+
+/// Same as [`create_and_reproject`], but streams the output file into `writer` instead
+/// of leaving it on disk, e.g. to upload it without a temporary file.
+///
+/// The output is created in memory (under `/vsimem/`), then written to `writer` in chunks
+/// and freed. `writer` is only written to sequentially, so it doesn't need to be seekable,
+/// even when the output driver (see [`CreateReprojectOptions::with_output_format`]) requires
+/// seeking while creating a file.
+pub fn reproject_to_writer<W: Write>(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+    mut writer: W,
+) -> Result<()> {
+    const WRITE_CHUNK_SIZE: usize = 1 << 20;
+    static OUTPUT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    // A directory of its own, so that any sidecar file is cleaned up as well.
+    let dir = PathBuf::from(format!(
+        "/vsimem/reproject_to_writer_{}_{}",
+        std::process::id(),
+        OUTPUT_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let path = dir.join("output");
+    let result = create_and_reproject_image(src, &path, dst_srs, options).and_then(|out| {
+        // Make sure everything is written before reading the file back.
+        drop(out);
+        call_on_mem_file_bytes(&path, |bytes| {
+            bytes
+                .chunks(WRITE_CHUNK_SIZE)
+                .try_for_each(|chunk| writer.write_all(chunk))
+                .and_then(|_| writer.flush())
+        })?
+        .map_err(GdalError::from)
+    });
+
+    let c_dir = _path_to_c_string(&dir)?;
+    unsafe { gdal_sys::VSIRmdirRecursive(c_dir.as_ptr()) };
+    result
+}
+
+/// Same as [`create_and_reproject`], but creates the output at `mem_path`, in GDAL's in-memory
+/// file system, and returns its bytes, e.g. to serve it without touching the disk.
+///
+/// `mem_path` must start with `/vsimem/`, and is passed to GDAL unchanged, with forward slashes
+/// on every platform. The in-memory file, and its `.aux.xml` sidecar, if any, are removed
+/// before returning, even on error.
+pub fn create_and_reproject_to_vsimem(
+    ds: &Dataset,
+    mem_path: &str,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<Vec<u8>> {
+    if !mem_path.starts_with("/vsimem/") {
+        return Err(GdalError::BadArgument(format!(
+            "expected a path under `/vsimem/`, got `{mem_path}`"
+        )));
+    }
+    let path = Path::new(mem_path);
+    let result = create_and_reproject_image(ds, path, dst_srs, options).and_then(|out| {
+        // Make sure everything is written before reading the file back.
+        drop(out);
+        get_vsi_mem_file_bytes_owned(path)
+    });
+
+    for file in [mem_path.to_string(), format!("{mem_path}.aux.xml")] {
+        if stat(&file).ok().flatten().is_some() {
+            let _ = unlink_mem_file(&file);
+        }
+    }
+    result
+}
+
+/// Premultiplying alpha is only supported when warping into an existing dataset.
+pub(super) fn check_no_premultiply_alpha(options: &CreateReprojectOptions) -> Result<()> {
+    if options.warp_options().premultiply_alpha() {
+        return Err(GdalError::BadArgument(
+            "premultiplying alpha is only supported by `reproject_into`".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reproject each of `sources`, e.g. the dates of a time series, into a new file in `out_dir`,
+/// on the grid of `reference`: in its spatial reference, with its geotransform and size, so
+/// that all the outputs are pixel-aligned with it, and with each other.
+///
+/// Each output is named after the file of its source (with the extension of the output
+/// format), or after its index in `sources` for sources without a file, e.g. `0.tif`.
+/// Sources whose outputs would have the same name are rejected before anything is warped.
+/// The coordinate transformation between spatial references is resolved once for all the
+/// sources sharing one, unless the cache is disabled with
+/// [`GdalWarpOptions::with_transformer_cache`].
+///
+/// The target resolution set in `options` is ignored. Returns the paths of the outputs, in the
+/// order of `sources`.
+pub fn reproject_stack_like<P: AsRef<Path>>(
+    sources: &[Dataset],
+    reference: &Dataset,
+    out_dir: P,
+    options: &CreateReprojectOptions,
+) -> Result<Vec<PathBuf>> {
+    check_no_premultiply_alpha(options)?;
+    let dst_srs = reference.spatial_ref()?;
+    let (cols, rows) = reference.raster_size();
+    let grid = (reference.geo_transform()?, cols, rows);
+    let extension = output_driver(options)?
+        .metadata_item("DMD_EXTENSION", "")
+        .filter(|extension| !extension.is_empty());
+
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(sources.len());
+    for (i, src) in sources.iter().enumerate() {
+        let description = src.description()?;
+        let mut name = match Path::new(&description).file_stem() {
+            Some(stem) => PathBuf::from(stem),
+            None => PathBuf::from(i.to_string()),
+        };
+        if let Some(extension) = &extension {
+            name.set_extension(extension);
+        }
+        let path = out_dir.as_ref().join(name);
+        if paths.contains(&path) {
+            return Err(GdalError::BadArgument(format!(
+                "several sources would be reprojected to {}",
+                path.display()
+            )));
+        }
+        paths.push(path);
+    }
+
+    let stack_options = options.without_source_window();
+    for (src, path) in sources.iter().zip(&paths) {
+        let window = match options.checked_source_window(src)? {
+            Some(window) => Some(source_window_vrt(src, window)?),
+            None => None,
+        };
+        let src = window.as_ref().unwrap_or(src);
+        let options = &*stack_options.resolved_for(src, src.raster_count())?;
+        let out = create_on_grid(src, path, &dst_srs, grid, options, None)?;
+        finish_with_pam_stats(Some(out), path, options, || Ok(None))?;
+    }
+    Ok(paths)
+}
+
+/// Resampling method name accepted by `GDALBuildOverviews` closest to `alg`.
+fn overview_resampling(alg: WarpResampleAlg) -> &'static str {
+    use WarpResampleAlg::*;
+    match alg {
+        Max | Min | Median | FirstQuartile | ThirdQuartile => "NEAREST",
+        #[cfg(all(major_ge_3, minor_ge_1))]
+        Sum => "NEAREST",
+        alg => alg.to_gdal_option(),
+    }
+}
+
+/// Runs `GDALCreateAndReprojectImage`, then saves the statistics of the output if requested.
+///
+/// Returns the output dataset, opened in update mode, if it had to be reopened to
+/// finish the job.
+pub(super) fn create_and_reproject_image(
+    ds: &Dataset,
+    dst_file: &Path,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<Option<Dataset>> {
+    if let Some(window) = options.checked_source_window(ds)? {
+        let window = source_window_vrt(ds, window)?;
+        let options = options.without_source_window();
+        return create_and_reproject_image(&window, dst_file, dst_srs, &options);
+    }
+    let out = warp_to_new_file(ds, dst_file, dst_srs, options)?;
+    finish_with_pam_stats(out, dst_file, options, || Ok(None))
+}
+
+/// A virtual dataset holding the `(x_off, y_off, x_size, y_size)` window of `ds`, reading
+/// the pixels of `ds` on demand, like `gdal_translate -of VRT -srcwin`.
+pub(super) fn source_window_vrt(
+    ds: &Dataset,
+    window: (usize, usize, usize, usize),
+) -> Result<Dataset> {
+    let (x_off, y_off, x_size, y_size) = window;
+    let mut args = CslStringList::new();
+    args.add_string("-of")?;
+    args.add_string("VRT")?;
+    args.add_string("-srcwin")?;
+    for value in [x_off, y_off, x_size, y_size] {
+        args.add_string(&value.to_string())?;
+    }
+    let vrt = unsafe {
+        let c_options = gdal_sys::GDALTranslateOptionsNew(args.as_ptr(), null_mut());
+        if c_options.is_null() {
+            return Err(_last_null_pointer_err("GDALTranslateOptionsNew"));
+        }
+        let vrt =
+            gdal_sys::GDALTranslate(b"\0".as_ptr().cast(), ds.c_dataset(), c_options, null_mut());
+        gdal_sys::GDALTranslateOptionsFree(c_options);
+        vrt
+    };
+    if vrt.is_null() {
+        return Err(_last_null_pointer_err("GDALTranslate"));
+    }
+    Ok(unsafe { Dataset::from_c_dataset(vrt) })
+}
+
+/// Returns `out`, the output written to `dst_file`, unless `options` ask for PAM statistics:
+/// it is then closed, the statistics are written with [`write_pam_stats`], and the output is
+/// opened again with `reopen`.
+pub(super) fn finish_with_pam_stats<T>(
+    out: T,
+    dst_file: &Path,
+    options: &CreateReprojectOptions,
+    reopen: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    if !options.pam_stats() {
+        return Ok(out);
+    }
+    // Make sure the output is fully written before reopening it.
+    drop(out);
+    write_pam_stats(dst_file)?;
+    reopen()
+}
+
+/// Computes the statistics and the default histogram of every band of the dataset at
+/// `dst_file`, which GDAL saves in its `.aux.xml` sidecar file when closing it.
+fn write_pam_stats(dst_file: &Path) -> Result<()> {
+    // Opened read-only, so that GeoTIFF doesn't store them in the file itself.
+    let out = Dataset::open(dst_file)?;
+    for i in 1..=out.raster_count() {
+        let band = out.rasterband(i)?;
+        band.get_statistics(true, false)?;
+        let (mut min, mut max, mut buckets) = (0.0, 0.0, 0);
+        let mut histogram = null_mut();
+        unsafe {
+            let c_band = band.c_rasterband();
+            let mut rv = gdal_sys::GDALGetDefaultHistogramEx(
+                c_band,
+                &mut min,
+                &mut max,
+                &mut buckets,
+                &mut histogram,
+                1,
+                None,
+                null_mut(),
+            );
+            if rv == CPLErr::CE_None {
+                rv = gdal_sys::GDALSetDefaultHistogramEx(c_band, min, max, buckets, histogram);
+            }
+            gdal_sys::VSIFree(histogram.cast());
+            if rv != CPLErr::CE_None {
+                return Err(_last_cpl_err(rv));
+            }
+        }
+    }
+    out.close()
+}
+
+/// Creates the output with `GDALCreateAndReprojectImage`, with `GDALWarp` when `options` set
+/// the target grid, or on the grid of [`output_grid`] when `options` ask for more than these
+/// support.
+///
+/// Returns the output dataset, opened in update mode, if it had to be reopened to
+/// finish the job.
+fn warp_to_new_file(
+    ds: &Dataset,
+    dst_file: &Path,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<Option<Dataset>> {
+    check_no_premultiply_alpha(options)?;
+    if is_identity_warp(ds, dst_srs, options) {
+        let driver = output_driver(options)?;
+        let creation_options = options.creation_options(ds)?;
+        return ds
+            .create_copy(
+                &driver,
+                dst_file,
+                &to_raster_creation_options(&creation_options),
+            )
+            .map(Some);
+    }
+    let options = &*options.resolved_for(ds, ds.raster_count())?;
+    let driver = output_driver(options)?;
+    let target_grid = options.target_resolution().is_some()
+        || options.target_extent().is_some()
+        || options.target_aligned_pixels();
+    if target_grid && can_warp_with_app(ds, dst_srs, options)? {
+        // Fails early if the target grid is invalid or doesn't intersect the source.
+        output_grid(ds, dst_srs, options)?;
+        return warp_with_app(ds, dst_file, dst_srs, &driver, options).map(Some);
+    }
+    let warp_options = options.clone_and_init_warp_options(ds, ds.raster_count())?;
+    if !can_create(&driver)
+        || options.output_alpha()
+        || options.has_post_band_fn()
+        || warp_options.has_custom_transformer()
+        || has_transformer_settings(&warp_options)
+        || options.target_resolution().is_some()
+        || options.target_extent().is_some()
+        || options.target_aligned_pixels()
+        || options.output_datatype().is_some()
+        || warp_options.sample_steps().is_some()
+        || warp_options.sample_grid()
+        || (dst_srs.is_geographic() && contains_pole(ds, dst_srs, &warp_options)?)
+    {
+        let grid = output_grid(ds, dst_srs, options)?;
+        return create_on_grid(ds, dst_file, dst_srs, grid, options, None).map(Some);
+    }
+
+    let c_dst_file = _path_to_c_string(dst_file)?;
+    let c_dst_wkt = CString::new(dst_srs.to_wkt()?)?;
+    let mut c_creation_options = CslStringList::new();
+    for (key, value) in &options.creation_options(ds)? {
+        c_creation_options.set_name_value(key, value)?;
+    }
+
+    let rv = with_transformer_cache_setting(&warp_options, || unsafe {
+        gdal_sys::GDALCreateAndReprojectImage(
+            ds.c_dataset(),
+            null(),
+            c_dst_file.as_ptr(),
+            c_dst_wkt.as_ptr(),
+            driver.c_driver(),
+            c_creation_options.as_ptr(),
+            warp_options.resampling_alg().to_gdal(),
+            warp_options.memory_limit() as f64,
+            options.max_error().unwrap_or(0.0),
+            None,
+            null_mut(),
+            warp_options.c_options(),
+        )
+    })?;
+    if rv != CPLErr::CE_None {
+        return Err(_last_cpl_err(rv));
+    }
+
+    // `GDALCreateAndReprojectImage` doesn't record the destination no-data value
+    // on the output, so we reopen it and do so ourselves.
+    if options.has_dst_nodata() && !options.skip_nodata_reopen() {
+        let out = Dataset::open_with_mode(dst_file, GdalOpenMode::Update)?;
+        for i in 1..=out.raster_count() {
+            let mut band = out.rasterband(i)?;
+            band.set_no_data_value(options.dst_nodata_of_band(i))?;
+        }
+        return Ok(Some(out));
+    }
+    Ok(None)
+}
+
+/// Whether the warp of `ds` into `dst_srs` with `options` can be expressed with the arguments
+/// of `gdalwarp`, for [`warp_with_app`].
+fn can_warp_with_app(
+    ds: &Dataset,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<bool> {
+    let warp_options = options.warp_options();
+    if options.has_post_band_fn()
+        || options.output_scaling().is_some()
+        || warp_options.has_custom_transformer()
+        || warp_options.band_count() != 0
+        || warp_options.cutline().is_some()
+        || warp_options.sample_steps().is_some()
+        || warp_options.sample_grid()
+        || warp_options.warp_chunk_size().is_some()
+    {
+        return Ok(false);
+    }
+    // Then the extent computed by `gdalwarp` wouldn't reach the pole, unlike `output_grid`.
+    let pole = options.target_extent().is_none()
+        && dst_srs.is_geographic()
+        && contains_pole(ds, dst_srs, warp_options)?;
+    Ok(!pole)
+}
+
+/// Creates the output with `GDALWarp`, the library version of `gdalwarp`, passing it the
+/// target grid and the other settings of `options` as arguments.
+fn warp_with_app(
+    ds: &Dataset,
+    dst_file: &Path,
+    dst_srs: &SpatialRef,
+    driver: &Driver,
+    options: &CreateReprojectOptions,
+) -> Result<Dataset> {
+    let warp_options = options.warp_options();
+    let band_count = ds.raster_count();
+    let mut args = CslStringList::new();
+    let mut add = |arg: &str| args.add_string(arg);
+    add("-overwrite")?;
+    add("-of")?;
+    add(&driver.short_name())?;
+    add("-t_srs")?;
+    add(&dst_srs.to_wkt()?)?;
+    if let Some((x_res, y_res)) = options.target_resolution() {
+        add("-tr")?;
+        add(&x_res.to_string())?;
+        add(&y_res.to_string())?;
+    }
+    if let Some(extent) = options.target_extent() {
+        add("-te")?;
+        for bound in extent {
+            add(&bound.to_string())?;
+        }
+    }
+    if options.target_aligned_pixels() {
+        add("-tap")?;
+    }
+    add("-r")?;
+    add(warp_options.resampling_alg().to_gdal_option())?;
+    // Exact by default, like `GDALCreateAndReprojectImage`, rather than `gdalwarp`'s 0.125.
+    add("-et")?;
+    add(&options.max_error().unwrap_or(0.0).to_string())?;
+    if warp_options.memory_limit() > 0 {
+        // Smaller values are taken as megabytes.
+        add("-wm")?;
+        add(&warp_options.memory_limit().max(10_000).to_string())?;
+    }
+    if let Some(data_type) = options.output_datatype() {
+        add("-ot")?;
+        add(&data_type.name())?;
+    }
+    if warp_options.working_data_type() != GdalDataType::Unknown {
+        add("-wt")?;
+        add(&warp_options.working_data_type().name())?;
+    }
+    let src_nodata = match (options.src_nodata_per_band(), options.src_nodata()) {
+        (Some(nodata), _) => Some(nodata.to_vec()),
+        (None, Some(nodata)) => Some(vec![nodata]),
+        (None, None) => None,
+    };
+    if let Some(nodata) = src_nodata {
+        add("-srcnodata")?;
+        add(&join_values(&nodata))?;
+    }
+    // Otherwise `gdalwarp` would set the source no-data values on the output.
+    add("-dstnodata")?;
+    if options.has_dst_nodata() {
+        let nodata = (1..=band_count)
+            .filter_map(|i| options.dst_nodata_of_band(i))
+            .collect::<Vec<_>>();
+        add(&join_values(&nodata))?;
+    } else {
+        add("None")?;
+    }
+    if options.output_alpha() {
+        add("-dstalpha")?;
+    }
+    for entry in warp_options.warp_option_entries() {
+        add("-wo")?;
+        add(&entry)?;
+    }
+    if let Some(operation) = warp_options.coordinate_operation() {
+        add("-ct")?;
+        add(operation)?;
+    }
+    for entry in warp_options.transformer_options().iter() {
+        add("-to")?;
+        add(&entry.to_string())?;
+    }
+    for (key, value) in options.creation_options(ds)? {
+        add("-co")?;
+        add(&format!("{key}={value}"))?;
+    }
+
+    let c_dst_file = _path_to_c_string(dst_file)?;
+    let out = with_transformer_cache_setting(warp_options, || unsafe {
+        let app_options = gdal_sys::GDALWarpAppOptionsNew(args.as_ptr(), null_mut());
+        if app_options.is_null() {
+            return Err(_last_null_pointer_err("GDALWarpAppOptionsNew"));
+        }
+        let mut src = ds.c_dataset();
+        let out = gdal_sys::GDALWarp(
+            c_dst_file.as_ptr(),
+            null_mut(),
+            1,
+            &mut src,
+            app_options,
+            null_mut(),
+        );
+        gdal_sys::GDALWarpAppOptionsFree(app_options);
+        if out.is_null() {
+            return Err(_last_null_pointer_err("GDALWarp"));
+        }
+        Ok(Dataset::from_c_dataset(out))
+    })??;
+    Ok(out)
+}
+
+/// `values` separated by spaces, as `gdalwarp` takes no-data values.
+fn join_values(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether warping `ds` into `dst_srs` with `options` would only resample it onto its own grid:
+/// it is already north-up in `dst_srs`, and nothing but the output format, creation options
+/// and settings not changing the warped values differs from the source. Copying it then gives
+/// the same pixels, without the resampling.
+fn is_identity_warp(ds: &Dataset, dst_srs: &SpatialRef, options: &CreateReprojectOptions) -> bool {
+    let warp_options = options.warp_options();
+    if options.target_resolution().is_some()
+        || options.target_extent().is_some()
+        || options.target_aligned_pixels()
+        || options.output_datatype().is_some()
+        || options.output_scaling().is_some()
+        || options.output_alpha()
+        || options.has_post_band_fn()
+        || options.src_nodata().is_some()
+        || options.src_nodata_per_band().is_some()
+        || options.has_dst_nodata()
+        || options.auto_dst_nodata()
+        || warp_options.may_change_values()
+        || has_transformer_settings(warp_options)
+    {
+        return false;
+    }
+    let same_srs = ds.spatial_ref().map_or(false, |srs| srs.is_same(dst_srs));
+    let north_up = ds
+        .geo_transform()
+        .map_or(false, |gt| gt[2] == 0.0 && gt[4] == 0.0);
+    same_srs && north_up
+}
+
+/// The driver of the output format, GeoTIFF by default.
+///
+/// Fails if the driver can't write rasters, rather than deep in GDAL.
+pub(super) fn output_driver(options: &CreateReprojectOptions) -> Result<Driver> {
+    let name = options.output_format().unwrap_or("GTiff");
+    let driver = DriverManager::get_driver_by_name(name)?;
+    let has = |capability: &str| driver.metadata_item(capability, "").is_some();
+    let missing = if !has("DCAP_RASTER") {
+        "DCAP_RASTER"
+    } else if !has("DCAP_CREATE") && !has("DCAP_CREATECOPY") {
+        "DCAP_CREATE or DCAP_CREATECOPY"
+    } else {
+        return Ok(driver);
+    };
+    Err(GdalError::BadArgument(format!(
+        "the {name} driver can't be used as output format, as it lacks the {missing} capability"
+    )))
+}
+
+/// Whether `driver` can create a dataset from scratch, rather than only copy one.
+fn can_create(driver: &Driver) -> bool {
+    driver.metadata_item("DCAP_CREATE", "").is_some()
+}
+
+/// Creates the output on `grid` with the driver and creation options of `options`, then
+/// warps into it.
+///
+/// Drivers such as COG can only write a copy of an existing dataset: with those, the source
+/// is warped through a warped VRT, copied with the creation options as it is read, or, when
+/// the VRT can't be used, into an in-memory dataset first.
+///
+/// If given, `transformer` (from the pixels of `ds` to `dst_srs`) is moved onto `grid` and
+/// used, rather than setting up a new one. A VRT can't share it, so the warp then goes
+/// through an in-memory dataset with those drivers.
+pub(super) fn create_on_grid(
+    ds: &Dataset,
+    dst_file: &Path,
+    dst_srs: &SpatialRef,
+    grid: (GeoTransform, usize, usize),
+    options: &CreateReprojectOptions,
+    transformer: Option<&mut Transformer>,
+) -> Result<Dataset> {
+    let transformer = transformer.map(|transformer| {
+        transformer.set_dst_geo_transform(grid.0);
+        &*transformer
+    });
+    let driver = output_driver(options)?;
+    let creation_options = options.creation_options(ds)?;
+    if can_create(&driver) {
+        return create_grid_and_reproject(
+            ds,
+            &driver,
+            dst_file,
+            dst_srs,
+            grid,
+            options,
+            &creation_options,
+            transformer,
+        );
+    }
+    let vrt = match transformer {
+        Some(_) => None,
+        None => warped_vrt_on_grid(ds, dst_srs, grid, options)?,
+    };
+    let warped = match vrt {
+        Some(vrt) => vrt,
+        None => {
+            let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+            create_grid_and_reproject(
+                ds,
+                &mem_driver,
+                Path::new(""),
+                dst_srs,
+                grid,
+                options,
+                &[],
+                transformer,
+            )?
+        }
+    };
+    warped.create_copy(
+        &driver,
+        dst_file,
+        &to_raster_creation_options(&creation_options),
+    )
+}
+
+/// Creates the output on `grid` (usually covering the source extent, at the target resolution
+/// if any) with `driver` and `creation_options`, then warps into it.
+///
+/// `GDALCreateAndReprojectImage` has no control over the output resolution, data type or
+/// extent sampling, so this is used instead when any is requested.
+///
+/// The warp uses `transformer` if given, which must be set up on `grid`.
+#[allow(clippy::too_many_arguments)]
+fn create_grid_and_reproject(
+    ds: &Dataset,
+    driver: &Driver,
+    dst_file: &Path,
+    dst_srs: &SpatialRef,
+    grid: (GeoTransform, usize, usize),
+    options: &CreateReprojectOptions,
+    creation_options: &[(String, String)],
+    transformer: Option<&Transformer>,
+) -> Result<Dataset> {
+    if let (Some(data_type), Some((src_min, src_max))) =
+        (options.output_datatype(), options.output_scaling())
+    {
+        if !data_type.is_integer() {
+            return Err(GdalError::BadArgument(format!(
+                "scaling requires an integer output data type, got {data_type}"
+            )));
+        }
+        if src_min.is_nan() || src_max.is_nan() || src_max <= src_min {
+            return Err(GdalError::BadArgument(format!(
+                "scaling range must be non-empty, got {src_min}..={src_max}"
+            )));
+        }
+    }
+    let output_alpha = options.output_alpha();
+    if output_alpha && options.output_scaling().is_some() {
+        return Err(GdalError::BadArgument(
+            "scaling is not supported with an alpha band".into(),
+        ));
+    }
+    let (gt, cols, rows) = grid;
+
+    let band_count = ds.raster_count();
+    let band_type = output_band_type(ds, options)?;
+    let creation_options = to_raster_creation_options(creation_options);
+    let out_band_count = band_count + usize::from(output_alpha);
+    let mut out = driver._create_with_band_type_with_options(
+        dst_file,
+        cols as isize,
+        rows as isize,
+        out_band_count as isize,
+        band_type,
+        &creation_options,
+    )?;
+    out.set_geo_transform(&gt)?;
+    out.set_spatial_ref(dst_srs)?;
+    if output_alpha {
+        out.rasterband(out_band_count)?
+            .set_color_interpretation(ColorInterpretation::AlphaBand)?;
+    }
+
+    if options.has_dst_nodata() {
+        for i in 1..=band_count {
+            out.rasterband(i)?
+                .set_no_data_value(options.dst_nodata_of_band(i))?;
+        }
+    }
+    let mut warp_options = grid_warp_options(ds, options)?;
+    let max_error = options.max_error().unwrap_or(0.0);
+    if options.post_band_fn().is_none() && options.output_scaling().is_none() {
+        match transformer {
+            Some(transformer) => warp_with(ds, &out, &warp_options, transformer)?,
+            None => reproject_image(ds, None, &out, None, &warp_options, max_error)?,
+        }
+        return Ok(out);
+    }
+
+    let owned;
+    let transformer = match transformer {
+        Some(transformer) => transformer,
+        None => {
+            owned = with_transformer_cache_setting(&warp_options, || {
+                Transformer::new(ds, dst_srs, gt, &warp_options, max_error)
+            })??;
+            &owned
+        }
+    };
+    warp_and_process(ds, &out, &mut warp_options, options, band_type, transformer)?;
+    Ok(out)
+}
+
+/// The data type of the output bands of a warp of `ds` with `options`.
+fn output_band_type(ds: &Dataset, options: &CreateReprojectOptions) -> Result<GdalDataType> {
+    Ok(match (options.output_datatype(), ds.raster_count()) {
+        (Some(band_type), _) => band_type,
+        (None, 0) => GdalDataType::Unknown,
+        (None, _) => ds.rasterband(1)?.band_type(),
+    })
+}
+
+/// The warp options of a warp of `ds` with `options` onto a grid, with the destination
+/// initialized to its no-data values, and an alpha band after the warped ones if requested.
+fn grid_warp_options(ds: &Dataset, options: &CreateReprojectOptions) -> Result<GdalWarpOptions> {
+    let band_count = ds.raster_count();
+    let mut warp_options = options.clone_and_init_warp_options(ds, band_count)?;
+    if options.has_dst_nodata() {
+        warp_options.with_warp_option("INIT_DEST", "NO_DATA")?;
+    }
+    if options.output_alpha() {
+        warp_options.apply_dst_alpha(band_count, band_count + 1)?;
+        if warp_options.warp_option("INIT_DEST").is_none() {
+            warp_options.with_warp_option("INIT_DEST", "0")?;
+        }
+    }
+    Ok(warp_options)
+}
+
+/// A warped VRT of `ds` on `grid`, set up as [`create_grid_and_reproject`] would set up the
+/// warp, so that it can be copied into the output instead.
+///
+/// Returns `None` when the VRT can't be used: with a post-warp function or scaling, which it
+/// can't apply, or when its bands couldn't be of the output data type without losing
+/// precision (see [`create_warped_vrt`]).
+fn warped_vrt_on_grid(
+    ds: &Dataset,
+    dst_srs: &SpatialRef,
+    grid: (GeoTransform, usize, usize),
+    options: &CreateReprojectOptions,
+) -> Result<Option<Dataset>> {
+    if options.has_post_band_fn() || options.output_scaling().is_some() || ds.raster_count() == 0 {
+        return Ok(None);
+    }
+    let mut warp_options = grid_warp_options(ds, options)?;
+    warp_options.init_default_band_mapping(ds.raster_count());
+    let mut vrt = match create_warped_vrt(
+        ds,
+        dst_srs,
+        grid,
+        &warp_options,
+        options.max_error().unwrap_or(0.0),
+        output_band_type(ds, options)?,
+    )? {
+        Some(vrt) => vrt,
+        None => return Ok(None),
+    };
+    vrt.set_spatial_ref(dst_srs)?;
+    for i in 1..=ds.raster_count() {
+        // Rather than the no-data values the VRT copies from the source bands.
+        vrt.rasterband(i)?
+            .set_no_data_value(options.dst_nodata_of_band(i))?;
+    }
+    Ok(Some(vrt))
+}
+
+/// Warps `ds` into `out` block by block, applying the post-warp function and the scaling of
+/// `options` to the warped values of each block before writing them. When rescaling, they are
+/// warped as floating point values, so that no precision is lost before scaling.
+///
+/// The warp uses `transformer`, which must be set up on the grid of `out`.
+fn warp_and_process(
+    ds: &Dataset,
+    out: &Dataset,
+    warp_options: &mut GdalWarpOptions,
+    options: &CreateReprojectOptions,
+    band_type: GdalDataType,
+    transformer: &Transformer,
+) -> Result<()> {
+    let (cols, rows) = out.raster_size();
+    warp_options.init_default_band_mapping(ds.raster_count());
+    let warped_type = match options.output_scaling() {
+        Some(_) => GdalDataType::Float64,
+        None => band_type,
+    };
+    let mut operation =
+        WarpOperation::with_transformer(ds, transformer, Some(out), warp_options, warped_type)?;
+    let mut bands = warp_options
+        .dst_bands()
+        .into_iter()
+        .map(|i| Ok((i, out.rasterband(i)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut warped = Vec::new();
+    let mut block = Buffer::new((0, 0), Vec::new());
+    for y_off in (0..rows).step_by(STREAMING_BLOCK_SIZE) {
+        for x_off in (0..cols).step_by(STREAMING_BLOCK_SIZE) {
+            block.size = (
+                STREAMING_BLOCK_SIZE.min(cols - x_off),
+                STREAMING_BLOCK_SIZE.min(rows - y_off),
+            );
+            operation.warp_region((x_off, y_off), block.size, &mut warped)?;
+            let band_values = warped.chunks_mut(block.size.0 * block.size.1);
+            for ((i, band), values) in bands.iter_mut().zip(band_values) {
+                if options.output_scaling().is_none() {
+                    // As the values would be read back from the output.
+                    round_to_type(values, band_type);
+                }
+                if let Some(post_band_fn) = options.post_band_fn() {
+                    post_band_fn.call(*i, values);
+                }
+                if let Some(src_range) = options.output_scaling() {
+                    let nodata = options.dst_nodata_of_band(*i);
+                    scale_values(values, src_range, band_type, nodata);
+                }
+                block.data.clear();
+                block.data.extend_from_slice(values);
+                band.write((x_off as isize, y_off as isize), block.size, &block)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Borrow `options` as creation options for [`Driver`] and [`Dataset`] methods.
+fn to_raster_creation_options(options: &[(String, String)]) -> Vec<RasterCreationOption<'_>> {
+    options
+        .iter()
+        .map(|(key, value)| RasterCreationOption { key, value })
+        .collect()
+}
+
+/// Whether `value` is the `nodata` value, which may be NaN.
+pub(super) fn is_nodata_value(value: f64, nodata: Option<f64>) -> bool {
+    match nodata {
+        Some(nodata) if nodata.is_nan() => value.is_nan(),
+        Some(nodata) => value == nodata,
+        None => false,
+    }
+}
+
+/// Linearly maps `values` from `src_range` to the range of `band_type`, rounded. The `nodata`
+/// values are kept as is.
+fn scale_values(
+    values: &mut [f64],
+    src_range: (f64, f64),
+    band_type: GdalDataType,
+    nodata: Option<f64>,
+) {
+    let (src_min, src_max) = src_range;
+    let dst_min: f64 = band_type.adjust_value(f64::MIN).into();
+    let dst_max: f64 = band_type.adjust_value(f64::MAX).into();
+    let scale = (dst_max - dst_min) / (src_max - src_min);
+    for v in values.iter_mut() {
+        if !is_nodata_value(*v, nodata) {
+            *v = ((*v - src_min) * scale + dst_min)
+                .round()
+                .clamp(dst_min, dst_max);
+        }
+    }
+}
+
+/// Grid and spatial reference of the output of [`create_and_reproject`].
+#[derive(Debug, Clone)]
+pub struct WarpReport {
+    /// Geotransform of the output.
+    pub geo_transform: GeoTransform,
+    /// Size (columns, rows) of the output, in pixels.
+    pub size: (usize, usize),
+    /// Spatial reference of the output.
+    pub spatial_ref: SpatialRef,
+    /// Ground sample distance of the source, in the units of the output spatial reference:
+    /// the side of the square with the area of the central source pixel, once reprojected.
+    ///
+    /// `None` if the source has no geotransform or spatial reference.
+    pub src_gsd: Option<f64>,
+    /// Ground sample distance of the output, the side of the square with the area of its pixels.
+    pub output_gsd: f64,
+    /// Ratio of the output ground sample distance to the source one, e.g. `2.0` if the output
+    /// pixels are twice as large, in each dimension, as the source ones.
+    pub gsd_ratio: Option<f64>,
+}
+
+impl WarpReport {
+    fn from_datasets(src: &Dataset, out: &Dataset) -> Result<Self> {
+        let geo_transform = out.geo_transform()?;
+        let spatial_ref = out.spatial_ref()?;
+        let output_gsd = (geo_transform[1] * geo_transform[5]
+            - geo_transform[2] * geo_transform[4])
+            .abs()
+            .sqrt();
+        let src_gsd = src_gsd(src, &spatial_ref).ok();
+        Ok(Self {
+            geo_transform,
+            size: out.raster_size(),
+            spatial_ref,
+            src_gsd,
+            output_gsd,
+            gsd_ratio: src_gsd.map(|src_gsd| output_gsd / src_gsd),
+        })
+    }
+}
+
+/// Side of the square with the area of the central pixel of `src`, once reprojected to
+/// `dst_srs`.
+fn src_gsd(src: &Dataset, dst_srs: &SpatialRef) -> Result<f64> {
+    let gt = src.geo_transform()?;
+    let mut src_srs = src.spatial_ref()?;
+    let mut dst_srs = dst_srs.clone();
+    src_srs.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+    dst_srs.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+
+    let (cols, rows) = src.raster_size();
+    let (x, y) = ((cols / 2) as f64, (rows / 2) as f64);
+    let (mut xs, mut ys): (Vec<_>, Vec<_>) = [(x, y), (x + 1.0, y), (x, y + 1.0)]
+        .into_iter()
+        .map(|(pixel, line)| gt.apply(pixel, line))
+        .unzip();
+    CoordTransform::new(&src_srs, &dst_srs)?.transform_coords(&mut xs, &mut ys, &mut [])?;
+    let area = ((xs[1] - xs[0]) * (ys[2] - ys[0]) - (ys[1] - ys[0]) * (xs[2] - xs[0])).abs();
+    Ok(area.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::create_and_reproject_with_mask;
+    use crate::raster::reproject_into;
+    use crate::raster::ReprojectIntoOptions;
+    use crate::test_utils::{fixture, TempFixture};
+
+    #[test]
+    fn test_create_and_reproject() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dest = TempFixture::empty("labels_proj.tif");
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(255.0)
+            .warp_options_mut()
+            .with_resampling_alg(WarpResampleAlg::NearestNeighbour);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.raster_count(), 1);
+        assert_eq!(result.spatial_ref()?.auth_code()?, 4326);
+        assert_eq!(result.rasterband(1)?.no_data_value(), Some(255.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_output_format() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dest = TempFixture::empty("labels_output_format");
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_output_format("ESRI Shapefile");
+        match create_and_reproject(&source, dest.path(), &dst_srs, &opts) {
+            Err(GdalError::BadArgument(msg)) => {
+                assert!(
+                    msg.contains("ESRI Shapefile") && msg.contains("DCAP_RASTER"),
+                    "{msg}"
+                )
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+        assert!(!dest.path().exists());
+
+        opts.with_output_format("GTiff");
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(Dataset::open(dest.path())?.driver().short_name(), "GTiff");
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_dataset() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let report_dest = TempFixture::empty("labels_report.tif");
+        let report = create_and_reproject(
+            &source,
+            report_dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+
+        let dest = TempFixture::empty("labels_dataset.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(0.0);
+        let out = create_and_reproject_dataset(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(out.raster_count(), source.raster_count());
+        assert_eq!(out.geo_transform()?, report.geo_transform);
+        assert_eq!(out.raster_size(), report.size);
+        assert_eq!(out.rasterband(1)?.no_data_value(), Some(0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_target_extent() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let full = create_and_reproject_to_vsimem(
+            &source,
+            "/vsimem/labels_target_extent_full.tif",
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        crate::vsi::create_mem_file("/vsimem/labels_target_extent_full.tif", full)?;
+        let full = Dataset::open("/vsimem/labels_target_extent_full.tif")?;
+        let gt = full.geo_transform()?;
+        let (cols, rows) = full.raster_size();
+
+        // The western half of the reprojected source.
+        let dest = TempFixture::empty("labels_target_extent.tif");
+        let mut opts = CreateReprojectOptions::new();
+        let half = cols / 2;
+        let bounds = [
+            gt[0],
+            gt[3] + gt[5] * rows as f64,
+            gt[0] + gt[1] * half as f64,
+            gt[3],
+        ];
+        opts.with_target_extent(bounds[0], bounds[1], bounds[2], bounds[3]);
+        let out = create_and_reproject_dataset(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(out.raster_size(), (half, rows));
+        assert_eq!(out.geo_transform()?[0], gt[0]);
+
+        // Bounds in the Gulf of Guinea don't intersect a source in North America.
+        let dest = TempFixture::empty("labels_target_extent_empty.tif");
+        opts.with_target_extent(0.0, 0.0, 1.0, 1.0);
+        let result = create_and_reproject(&source, dest.path(), &dst_srs, &opts);
+        assert!(matches!(result, Err(GdalError::EmptyOutput)));
+        assert!(!dest.path().exists());
+
+        opts.with_target_extent(1.0, 0.0, 0.0, 1.0);
+        let result = create_and_reproject(&source, dest.path(), &dst_srs, &opts);
+        assert!(matches!(result, Err(GdalError::BadArgument(_))));
+        drop(full);
+        crate::vsi::unlink_mem_file("/vsimem/labels_target_extent_full.tif")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_target_resolution_and_extent() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+
+        let dest = TempFixture::empty("labels_tr_te.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_resolution(0.01, 0.01)
+            .with_target_extent(-79.0, 38.0, -78.5, 38.5);
+        let out = create_and_reproject_dataset(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(out.raster_size(), (50, 50));
+        let gt = out.geo_transform()?;
+        assert_eq!((gt[0], gt[3]), (-79.0, 38.5));
+        assert_eq!((gt[1], gt[5]), (0.01, -0.01));
+
+        // Same grid when warping onto it without `GDALWarp`, as with a post-warp function.
+        let dest = TempFixture::empty("labels_tr_te_post_band_fn.tif");
+        opts.with_post_band_fn(|_, _| {});
+        let grid_out = create_and_reproject_dataset(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(grid_out.raster_size(), out.raster_size());
+        assert_eq!(grid_out.geo_transform()?, gt);
+
+        // Without an extent, the computed one is snapped to multiples of the resolution.
+        let dest = TempFixture::empty("labels_tap.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_resolution(0.01, 0.01)
+            .with_target_aligned_pixels(true);
+        let out = create_and_reproject_dataset(&source, dest.path(), &dst_srs, &opts)?;
+        let gt = out.geo_transform()?;
+        let (cols, rows) = out.raster_size();
+        for value in [
+            gt[0],
+            gt[3],
+            gt[0] + 0.01 * cols as f64,
+            gt[3] - 0.01 * rows as f64,
+        ] {
+            assert!(((value / 0.01).round() * 0.01 - value).abs() < 1e-9);
+        }
+        assert!(cols <= 10 && rows <= 10);
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_aligned_pixels(true);
+        let dest = TempFixture::empty("labels_tap_no_tr.tif");
+        assert!(create_and_reproject(&source, dest.path(), &dst_srs, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_target_aligned_pixels() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+
+        // The extent grows to the enclosing multiples of the resolution.
+        let dest = TempFixture::empty("labels_target_aligned_pixels.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_resolution(0.01, 0.01)
+            .with_target_extent(-78.987, 38.013, -78.512, 38.4999)
+            .with_target_aligned_pixels(true);
+        let out = create_and_reproject_dataset(&source, dest.path(), &dst_srs, &opts)?;
+        let gt = out.geo_transform()?;
+        for origin in [gt[0], gt[3]] {
+            let multiple = origin / 0.01;
+            assert!((multiple - multiple.round()).abs() < 1e-6, "{gt:?}");
+        }
+        assert!((gt[0] + 78.99).abs() < 1e-9 && (gt[3] - 38.5).abs() < 1e-9);
+        assert_eq!(out.raster_size(), (48, 49));
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_aligned_pixels(true);
+        let dest = TempFixture::empty("labels_target_aligned_pixels_no_res.tif");
+        match create_and_reproject(&source, dest.path(), &dst_srs, &opts) {
+            Err(GdalError::BadArgument(msg)) => assert!(msg.contains("target resolution")),
+            result => panic!("unexpected result: {result:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_skip_nodata_reopen() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dest = TempFixture::empty("labels_proj.tif");
+
+        let mut opts = CreateReprojectOptions::new();
+        assert!(!opts.skip_nodata_reopen());
+        opts.with_dst_nodata(0.0).with_skip_nodata_reopen(true);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+
+        // `GDALCreateAndReprojectImage` doesn't record it, nor the one of the source.
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.spatial_ref()?.auth_code()?, 4326);
+        assert_eq!(result.rasterband(1)?.no_data_value(), None);
+
+        // Unlike when not skipping the reopen.
+        let dest = TempFixture::empty("labels_proj_reopen.tif");
+        opts.with_skip_nodata_reopen(false);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.rasterband(1)?.no_data_value(), Some(0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_with_overviews() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dest = TempFixture::empty("labels_proj_ovr.tif");
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.warp_options_mut()
+            .with_resampling_alg(WarpResampleAlg::Max);
+        let result =
+            create_and_reproject_with_overviews(&source, dest.path(), &dst_srs, &[2, 4], &opts)?;
+        assert_eq!(result.spatial_ref()?.auth_code()?, 4326);
+        assert_eq!(result.rasterband(1)?.overview_count()?, 2);
+        drop(result);
+
+        // Overviews are persisted in the output.
+        let result = Dataset::open(dest.path())?;
+        let band = result.rasterband(1)?;
+        assert_eq!(band.overview_count()?, 2);
+        let (cols, rows) = result.raster_size();
+        assert_eq!(band.overview(0)?.size(), ((cols + 1) / 2, (rows + 1) / 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_overview_resampling() -> Result<()> {
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let overview = |overview_alg: WarpResampleAlg| -> Result<Vec<u8>> {
+            let dest = TempFixture::empty("naip_overview_resampling.tif");
+            let mut opts = CreateReprojectOptions::new();
+            opts.warp_options_mut()
+                .with_resampling_alg(WarpResampleAlg::Cubic)
+                .with_overview_resampling(overview_alg);
+            let out =
+                create_and_reproject_with_overviews(&source, dest.path(), &dst_srs, &[2], &opts)?;
+            let data = out.rasterband(1)?.overview(0)?.read_band_as::<u8>()?.data;
+            Ok(data)
+        };
+        let nearest = overview(WarpResampleAlg::NearestNeighbour)?;
+        let cubic = overview(WarpResampleAlg::Cubic)?;
+        assert_eq!(nearest.len(), cubic.len());
+        assert_ne!(nearest, cubic);
+
+        let mut warp_options = GdalWarpOptions::new();
+        warp_options.with_resampling_alg(WarpResampleAlg::Bilinear);
+        assert_eq!(
+            warp_options.overview_resampling(),
+            WarpResampleAlg::Bilinear
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_prototype() -> Result<()> {
+        use crate::raster::Compression;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let driver = DriverManager::get_driver_by_name("GTiff")?;
+        let prototype_file = TempFixture::empty("prototype.tif");
+        let prototype = source.create_copy(
+            &driver,
+            prototype_file.path(),
+            &[
+                RasterCreationOption {
+                    key: "COMPRESS",
+                    value: "DEFLATE",
+                },
+                RasterCreationOption {
+                    key: "TILED",
+                    value: "YES",
+                },
+                RasterCreationOption {
+                    key: "BLOCKXSIZE",
+                    value: "64",
+                },
+                RasterCreationOption {
+                    key: "BLOCKYSIZE",
+                    value: "32",
+                },
+            ],
+        )?;
+
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let dest = TempFixture::empty("labels_like_prototype.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_output_format("COG").with_prototype(&prototype)?;
+        assert_eq!(opts.output_format(), Some("GTiff"));
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.driver().short_name(), "GTiff");
+        assert_eq!(
+            result.metadata_item("COMPRESSION", "IMAGE_STRUCTURE"),
+            prototype.metadata_item("COMPRESSION", "IMAGE_STRUCTURE")
+        );
+        assert_eq!(
+            result.rasterband(1)?.block_size(),
+            prototype.rasterband(1)?.block_size()
+        );
+
+        // Explicit settings win.
+        opts.with_compression(Compression::Lzw);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(
+            result.metadata_item("COMPRESSION", "IMAGE_STRUCTURE"),
+            Some("LZW".into())
+        );
+        assert_eq!(result.rasterband(1)?.block_size(), (64, 32));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_match_source_tiling() -> Result<()> {
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let driver = DriverManager::get_driver_by_name("GTiff")?;
+        let tiled = TempFixture::empty("tiled_source.tif");
+        let source = source.create_copy(
+            &driver,
+            tiled.path(),
+            &[
+                RasterCreationOption {
+                    key: "TILED",
+                    value: "YES",
+                },
+                RasterCreationOption {
+                    key: "BLOCKXSIZE",
+                    value: "256",
+                },
+                RasterCreationOption {
+                    key: "BLOCKYSIZE",
+                    value: "256",
+                },
+            ],
+        )?;
+        assert_eq!(source.rasterband(1)?.block_size(), (256, 256));
+
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let dest = TempFixture::empty("tiled_proj.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_match_source_tiling(true);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.rasterband(1)?.block_size(), (256, 256));
+
+        // Also when GDAL doesn't create the output itself.
+        opts.with_target_resolution(1e-5, 1e-5);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.rasterband(1)?.block_size(), (256, 256));
+
+        // By default, GDAL picks the layout.
+        create_and_reproject(
+            &source,
+            dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        let result = Dataset::open(dest.path())?;
+        let (cols, _) = result.raster_size();
+        assert_eq!(result.rasterband(1)?.block_size().0, cols);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_compression() -> Result<()> {
+        use crate::raster::Compression;
+        use crate::Metadata;
+
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let uncompressed = TempFixture::empty("uncompressed.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_compression(Compression::None);
+        create_and_reproject(&source, uncompressed.path(), &dst_srs, &opts)?;
+
+        let zstd = TempFixture::empty("zstd.tif");
+        opts.with_compression(Compression::Zstd { level: 9 });
+        create_and_reproject(&source, zstd.path(), &dst_srs, &opts)?;
+
+        let size = |path: &Path| std::fs::metadata(path).unwrap().len();
+        assert!(size(zstd.path()) < size(uncompressed.path()));
+        let result = Dataset::open(zstd.path())?;
+        assert_eq!(
+            result.metadata_item("COMPRESSION", "IMAGE_STRUCTURE"),
+            Some("ZSTD".into())
+        );
+        assert!(result.raster_equals(&Dataset::open(uncompressed.path())?, 0.0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_creation_options() -> Result<()> {
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let dest = TempFixture::empty("deflate.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_creation_options([
+            ("COMPRESS".to_string(), "DEFLATE".to_string()),
+            ("TILED".to_string(), "YES".to_string()),
+        ])
+        .with_creation_options([("BLOCKXSIZE", "128"), ("BLOCKYSIZE", "128")]);
+        assert_eq!(opts.extra_creation_options().len(), 4);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(
+            result.metadata_item("COMPRESSION", "IMAGE_STRUCTURE"),
+            Some("DEFLATE".into())
+        );
+        assert_eq!(result.rasterband(1)?.block_size(), (128, 128));
+        Ok(())
+    }
+
+    /// Whether the TIFF file at `path` is a BigTIFF: the version number following the byte
+    /// order mark is 42 for TIFF, 43 for BigTIFF.
+    fn is_bigtiff(path: &Path) -> Result<bool> {
+        let mut header = [0u8; 4];
+        std::io::Read::read_exact(&mut std::fs::File::open(path)?, &mut header)?;
+        let version = match &header[..2] {
+            b"II" => u16::from_le_bytes([header[2], header[3]]),
+            b"MM" => u16::from_be_bytes([header[2], header[3]]),
+            _ => panic!("not a TIFF file: {header:?}"),
+        };
+        Ok(version == 43)
+    }
+
+    #[test]
+    fn test_create_and_reproject_bigtiff() -> Result<()> {
+        use crate::raster::BigTiffMode;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let srs = source.spatial_ref()?;
+        let mut opts = CreateReprojectOptions::new();
+        assert_eq!(opts.bigtiff(), Some(BigTiffMode::IfSafer));
+        let dest = TempFixture::empty("not_bigtiff.tif");
+        create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        assert!(!is_bigtiff(dest.path())?);
+
+        opts.with_bigtiff(BigTiffMode::Yes);
+        let dest = TempFixture::empty("bigtiff.tif");
+        create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        assert!(is_bigtiff(dest.path())?);
+        let warped = Dataset::open(dest.path())?
+            .rasterband(1)?
+            .read_band_as::<u8>()?;
+        assert_eq!(
+            warped.data,
+            source.rasterband(1)?.read_band_as::<u8>()?.data
+        );
+
+        // Only the TIFF based drivers default to it.
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_output_format("MEM");
+        assert_eq!(opts.bigtiff(), None);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "creates two 50000x50000 outputs"]
+    fn test_create_and_reproject_bigtiff_if_safer() -> Result<()> {
+        use crate::raster::BigTiffMode;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let srs = source.spatial_ref()?;
+        let gt = source.geo_transform()?;
+        // A 50000x50000 byte output, i.e. 2.5 GB uncompressed, mostly left unwritten.
+        let (min_x, max_y) = (gt[0] - 740000.0, gt[3] + 740000.0);
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_resolution(30.0, 30.0)
+            .with_target_extent(min_x, max_y - 1500000.0, min_x + 1500000.0, max_y)
+            .with_sparse(true);
+        opts.warp_options_mut()
+            .with_warp_option("SKIP_NOSOURCE", "YES")?;
+
+        let dest = TempFixture::empty("bigtiff_if_safer.tif");
+        create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        assert!(is_bigtiff(dest.path())?);
+        assert_eq!(Dataset::open(dest.path())?.raster_size(), (50000, 50000));
+
+        // Classic TIFF files can hold up to 4 GB of uncompressed data.
+        opts.with_bigtiff(BigTiffMode::IfNeeded);
+        let dest = TempFixture::empty("bigtiff_if_needed.tif");
+        create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        assert!(!is_bigtiff(dest.path())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_interleave() -> Result<()> {
+        use crate::raster::Interleave;
+
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut opts = CreateReprojectOptions::new();
+        for (interleave, expected) in [(Interleave::Band, "BAND"), (Interleave::Pixel, "PIXEL")] {
+            let dest = TempFixture::empty("interleaved.tif");
+            opts.with_interleave(interleave);
+            assert_eq!(opts.interleave(), Some(interleave));
+            create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+            let result = Dataset::open(dest.path())?;
+            assert_eq!(result.raster_count(), 4);
+            assert_eq!(
+                result.metadata_item("INTERLEAVE", "IMAGE_STRUCTURE"),
+                Some(expected.into())
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_cmyk() -> Result<()> {
+        use crate::raster::Photometric;
+        use ColorInterpretation::*;
+
+        let source_file = TempFixture::empty("cmyk.tif");
+        {
+            let driver = DriverManager::get_driver_by_name("GTiff")?;
+            let mut source = driver.create_with_band_type_with_options::<u8, _>(
+                source_file.path(),
+                20,
+                20,
+                4,
+                &[RasterCreationOption {
+                    key: "PHOTOMETRIC",
+                    value: "CMYK",
+                }],
+            )?;
+            source.set_geo_transform(&[500000.0, 10.0, 0.0, 4500000.0, 0.0, -10.0])?;
+            source.set_spatial_ref(&SpatialRef::from_epsg(32631)?)?;
+            for i in 1..=4 {
+                source.rasterband(i)?.write(
+                    (0, 0),
+                    (20, 20),
+                    &Buffer::new((20, 20), vec![i as u8 * 50; 400]),
+                )?;
+            }
+        }
+        let source = Dataset::open(source_file.path())?;
+        let color_interpretations = |ds: &Dataset| -> Result<Vec<ColorInterpretation>> {
+            (1..=ds.raster_count())
+                .map(|i| Ok(ds.rasterband(i)?.color_interpretation()))
+                .collect()
+        };
+        let cmyk = vec![CyanBand, MagentaBand, YellowBand, BlackBand];
+        assert_eq!(color_interpretations(&source)?, cmyk);
+
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let dest = TempFixture::empty("cmyk_4326.tif");
+        let mut opts = CreateReprojectOptions::new();
+        assert_eq!(opts.photometric(), None);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(color_interpretations(&result)?, cmyk);
+        assert_eq!(
+            result
+                .rasterband(2)?
+                .read_as::<u8>((5, 5), (1, 1), (1, 1), None)?
+                .data,
+            [100]
+        );
+
+        let dest = TempFixture::empty("gray_4326.tif");
+        opts.with_photometric(Photometric::MinIsBlack);
+        assert_eq!(opts.photometric(), Some(Photometric::MinIsBlack));
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.rasterband(1)?.color_interpretation(), GrayIndex);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_output_datatype_scaled() -> Result<()> {
+        // Float32 reflectances in [0, 1]
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<f32, _>("", 5, 1, 1)?;
+        source.set_geo_transform(&[0.0, 1.0, 0.0, 1.0, 0.0, -1.0])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        let reflectances = vec![0.0f32, 0.25, 0.5, 0.75, 1.0];
+        source
+            .rasterband(1)?
+            .write((0, 0), (5, 1), &Buffer::new((5, 1), reflectances))?;
+
+        let dest = TempFixture::empty("reflectance_u8.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_output_datatype_scaled(GdalDataType::UInt8, 0.0, 1.0);
+        create_and_reproject(&source, dest.path(), &source.spatial_ref()?, &opts)?;
+
+        let result = Dataset::open(dest.path())?;
+        let band = result.rasterband(1)?;
+        assert_eq!(band.band_type(), GdalDataType::UInt8);
+        assert_eq!(band.read_band_as::<u8>()?.data, [0, 64, 128, 191, 255]);
+
+        // Without scaling, values are only converted.
+        opts.with_output_datatype(GdalDataType::UInt8);
+        create_and_reproject(&source, dest.path(), &source.spatial_ref()?, &opts)?;
+        let result = Dataset::open(dest.path())?;
+        let data = result.rasterband(1)?.read_band_as::<u8>()?.data;
+        assert_eq!((data[0], data[4]), (0, 1));
+        assert!(data.iter().all(|&v| v <= 1));
+
+        opts.with_output_datatype_scaled(GdalDataType::Float32, 0.0, 1.0);
+        assert!(create_and_reproject(&source, dest.path(), &source.spatial_ref()?, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_to_vsimem() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(255.0);
+        let path = "/vsimem/create_and_reproject_to_vsimem.tif";
+        let bytes = create_and_reproject_to_vsimem(&source, path, &dst_srs, &opts)?;
+
+        assert!(bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*"));
+        assert!(stat(path)?.is_none());
+
+        assert!(create_and_reproject_to_vsimem(&source, "output.tif", &dst_srs, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_to_writer() -> Result<()> {
+        use crate::vsi::{create_mem_file, unlink_mem_file};
+        use std::io::Cursor;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(255.0);
+        let mut cursor = Cursor::new(Vec::new());
+        reproject_to_writer(&source, &dst_srs, &opts, &mut cursor)?;
+
+        let bytes = cursor.into_inner();
+        assert_eq!(&bytes[..2], b"II");
+        let path = "/vsimem/reproject_to_writer_test.tif";
+        create_mem_file(path, bytes)?;
+        let result = Dataset::open(path)?;
+        assert_eq!(result.spatial_ref()?.auth_code()?, 4326);
+        assert_eq!(result.rasterband(1)?.no_data_value(), Some(255.0));
+        drop(result);
+        unlink_mem_file(path)?;
+
+        // Writer errors are reported.
+        struct FailingWriter;
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = reproject_to_writer(&source, &dst_srs, &opts, FailingWriter).unwrap_err();
+        assert!(matches!(
+            err,
+            GdalError::IoError {
+                kind: std::io::ErrorKind::BrokenPipe,
+                ..
+            }
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_sparse() -> Result<()> {
+        // Mostly empty, except for a small patch in a corner.
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 1024, 1024, 1)?;
+        source.set_geo_transform(&[0.0, 0.001, 0.0, 1.0, 0.0, -0.001])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        source.rasterband(1)?.write(
+            (0, 0),
+            (32, 32),
+            &Buffer::new((32, 32), vec![7u8; 32 * 32]),
+        )?;
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(0.0);
+        let dense = TempFixture::empty("dense.tif");
+        create_and_reproject(&source, dense.path(), &source.spatial_ref()?, &opts)?;
+        let sparse = TempFixture::empty("sparse.tif");
+        opts.with_sparse(true);
+        create_and_reproject(&source, sparse.path(), &source.spatial_ref()?, &opts)?;
+
+        let size = |path: &Path| std::fs::metadata(path).unwrap().len();
+        assert!(
+            size(sparse.path()) * 10 < size(dense.path()),
+            "{} vs {}",
+            size(sparse.path()),
+            size(dense.path())
+        );
+        let dense = Dataset::open(dense.path())?;
+        assert!(Dataset::open(sparse.path())?.raster_equals(&dense, 0.0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_with_sidecar_georeferencing() -> Result<()> {
+        use crate::{GeoTransform, GeoTransformEx};
+
+        // Make an ungeoreferenced PNG out of the tinymarble pixels.
+        let tiny = Dataset::open(fixture("tinymarble.tif"))?;
+        let (cols, rows) = tiny.raster_size();
+        let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+        let mem = mem_driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 3)?;
+        for i in 1..=3 {
+            let data = tiny.rasterband(i)?.read_band_as::<u8>()?;
+            mem.rasterband(i)?.write((0, 0), (cols, rows), &data)?;
+        }
+        let png_path = TempFixture::empty("tinymarble.png");
+        let png_driver = DriverManager::get_driver_by_name("PNG")?;
+        drop(mem.create_copy(&png_driver, png_path.path(), &[])?);
+        assert!(Dataset::open(png_path.path())?.spatial_ref().is_err());
+
+        // Sidecars with names GDAL doesn't look for by itself.
+        let world_file = png_path.path().with_file_name("georef.txt");
+        std::fs::write(&world_file, "0.1\n0\n0\n-0.1\n-9.95\n49.95\n").unwrap();
+        let prj_file = png_path.path().with_file_name("georef.prj");
+        std::fs::write(&prj_file, SpatialRef::from_epsg(4326)?.to_esri_wkt()?).unwrap();
+
+        let gt = GeoTransform::from_world_file(&world_file)?;
+        let expected = [-10.0, 0.1, 0.0, 50.0, 0.0, -0.1];
+        for (a, e) in gt.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{gt:?} != {expected:?}");
+        }
+        let srs = SpatialRef::from_prj_file(&prj_file)?;
+
+        let png = Dataset::open(png_path.path())?;
+        let mut source = png.create_copy(&mem_driver, "", &[])?;
+        source.set_geo_transform(&gt)?;
+        source.set_spatial_ref(&srs)?;
+
+        let dest = TempFixture::empty("tinymarble_3857.tif");
+        let dst_srs = SpatialRef::from_epsg(3857)?;
+        create_and_reproject(
+            &source,
+            dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+
+        let result = Dataset::open(dest.path())?;
+        assert_eq!(result.spatial_ref()?.auth_code()?, 3857);
+        let (x, _) = result.geo_transform()?.apply(0.0, 0.0);
+        // 10°W in Web Mercator
+        assert!((x + 1113194.9).abs() < 1.0, "{x}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_resolution_in_feet() -> Result<()> {
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let src_srs = source.spatial_ref()?;
+        assert_eq!(src_srs.linear_units(), 1.0);
+        // 300 x 300 pixels of 1 metre
+        assert_eq!(source.raster_size(), (300, 300));
+
+        // NAD83 / North Carolina (ftUS)
+        let dst_srs = SpatialRef::from_epsg(2264)?;
+        let us_foot = 1200.0 / 3937.0;
+        assert!((dst_srs.linear_units() - us_foot).abs() < 1e-12);
+
+        let dest = TempFixture::empty("nc_feet.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_resolution(1.0, 1.0);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+
+        let result = Dataset::open(dest.path())?;
+        let result_srs = result.spatial_ref()?;
+        assert!((result_srs.linear_units() - us_foot).abs() < 1e-12);
+        assert!(result_srs.linear_units_name()?.contains("foot"));
+
+        // One foot per pixel, so ~984 pixels across the 300 m of the source.
+        let gt = result.geo_transform()?;
+        assert_eq!((gt[1], gt[5]), (1.0, -1.0));
+        let (cols, rows) = result.raster_size();
+        let expected = 300.0 / us_foot;
+        assert!(
+            cols as f64 >= expected && (cols as f64) < expected * 1.1,
+            "{cols}"
+        );
+        assert!(
+            rows as f64 >= expected && (rows as f64) < expected * 1.1,
+            "{rows}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_auto_dst_nodata() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 16, 16, 1)?;
+        source.set_geo_transform(&[0.0, 1.0, 0.0, 16.0, 0.0, -1.0])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(32631)?)?;
+        let values: Vec<u8> = (0..256).map(|i| (i % 191 + 10) as u8).collect();
+        source
+            .rasterband(1)?
+            .write((0, 0), (16, 16), &Buffer::new((16, 16), values.clone()))?;
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_auto_dst_nodata(true);
+        let dest = TempFixture::empty("auto_nodata.tif");
+        create_and_reproject(&source, dest.path(), &SpatialRef::from_epsg(4326)?, &opts)?;
+        let nodata = Dataset::open(dest.path())?.rasterband(1)?.no_data_value();
+        assert_eq!(nodata, Some(9.0));
+        assert!(values.iter().all(|&v| v as f64 != 9.0));
+
+        // With every byte value in use, there is none left.
+        let values: Vec<u8> = (0..256).map(|i| i as u8).collect();
+        source
+            .rasterband(1)?
+            .write((0, 0), (16, 16), &Buffer::new((16, 16), values))?;
+        let dest = TempFixture::empty("auto_nodata_full.tif");
+        assert!(
+            create_and_reproject(&source, dest.path(), &SpatialRef::from_epsg(4326)?, &opts)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_nodata_per_band() -> Result<()> {
+        let source = Dataset::open(fixture("tinymarble.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(3857)?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_src_nodata_per_band(&[0.0, 1.0, 2.0])
+            .with_dst_nodata_per_band(&[253.0, 254.0, 255.0]);
+        assert_eq!(opts.src_nodata(), None);
+        assert_eq!(opts.dst_nodata_per_band(), Some(&[253.0, 254.0, 255.0][..]));
+
+        let dest = TempFixture::empty("nodata_per_band.tif");
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let out = Dataset::open(dest.path())?;
+        let nodata = (1..=3)
+            .map(|i| Ok(out.rasterband(i)?.no_data_value()))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(nodata, [Some(253.0), Some(254.0), Some(255.0)]);
+
+        opts.with_dst_nodata_per_band(&[255.0, 255.0]);
+        let dest = TempFixture::empty("nodata_per_band_mismatch.tif");
+        assert!(create_and_reproject(&source, dest.path(), &dst_srs, &opts).is_err());
+
+        // A single value replaces the ones of each band.
+        opts.with_dst_nodata(0.0);
+        assert_eq!(opts.dst_nodata_per_band(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_nodata_u8() -> Result<()> {
+        // A 20x20 RGB source, rotated by 45 degrees, leaving the corners of the output uncovered.
+        let srs = SpatialRef::from_epsg(32631)?;
+        let step = 100.0 * 45f64.to_radians().cos();
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 20, 20, 3)?;
+        source.set_geo_transform(&[500000.0, step, step, 4500000.0, step, -step])?;
+        source.set_spatial_ref(&srs)?;
+        for (i, value) in [(1, 200u8), (2, 150), (3, 100)] {
+            source.rasterband(i)?.write(
+                (0, 0),
+                (20, 20),
+                &Buffer::new((20, 20), vec![value; 400]),
+            )?;
+        }
+
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata_u8(&[0, 0, 0]);
+        assert_eq!(opts.dst_nodata_per_band(), Some(&[0.0, 0.0, 0.0][..]));
+        let dest = TempFixture::empty("nodata_u8.tif");
+        create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        let out = Dataset::open(dest.path())?;
+        for (i, value) in [(1, 200u8), (2, 150), (3, 100)] {
+            let band = out.rasterband(i)?;
+            assert_eq!(band.no_data_value(), Some(0.0));
+            let data = band.read_band_as::<u8>()?.data;
+            assert_eq!(data[0], 0);
+            assert_eq!(*data.last().unwrap(), 0);
+            assert!(data.iter().all(|&v| v == 0 || v == value));
+            assert!(data.contains(&value));
+        }
+
+        // The values must be of the data type of the output.
+        opts.with_output_datatype(GdalDataType::UInt16);
+        let dest = TempFixture::empty("nodata_u8_uint16.tif");
+        assert!(create_and_reproject(&source, dest.path(), &srs, &opts).is_err());
+        opts.with_dst_nodata_u16(&[0, 0, 0]);
+        create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_polar_source() -> Result<()> {
+        // 6000 km wide, around the south pole, in Antarctic polar stereographic.
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 100, 100, 1)?;
+        source.set_geo_transform(&[-3e6, 6e4, 0.0, 3e6, 0.0, -6e4])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(3031)?)?;
+        source.rasterband(1)?.write(
+            (0, 0),
+            (100, 100),
+            &Buffer::new((100, 100), vec![1; 10_000]),
+        )?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+
+        let dest = TempFixture::empty("antarctica.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(0.0);
+        for sample in [false, true] {
+            if sample {
+                opts.warp_options_mut()
+                    .with_sample_steps(100)?
+                    .with_sample_grid(true)?;
+            }
+            let report = create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+            let gt = report.geo_transform;
+            let (cols, rows) = report.size;
+            let (min_lon, max_lon) = (gt[0], gt[0] + gt[1] * cols as f64);
+            let (min_lat, max_lat) = (gt[3] + gt[5] * rows as f64, gt[3]);
+            assert!((min_lon + 180.0).abs() < 1e-9, "{min_lon}");
+            assert!((max_lon - 180.0).abs() < gt[1], "{max_lon}");
+            assert!((min_lat + 90.0).abs() < -gt[5], "{min_lat}");
+            // The corners of the source, the farthest from the pole, are at about 50°S.
+            assert!(-60.0 < max_lat && max_lat < -40.0, "{max_lat}");
+
+            // Data all the way down to the pole.
+            let out = Dataset::open(dest.path())?;
+            let last_row = out.rasterband(1)?.read_as::<u8>(
+                (0, rows as isize - 1),
+                (cols, 1),
+                (cols, 1),
+                None,
+            )?;
+            assert!(last_row.data.iter().all(|&v| v == 1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_if_stale() -> Result<()> {
+        let source = Dataset::open(TempFixture::fixture("labels.tif"))?;
+        let dest = TempFixture::empty("labels_cached.tif");
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let opts = CreateReprojectOptions::new();
+
+        let outcome = create_and_reproject_if_stale(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(outcome, ReprojectOutcome::Reprojected);
+        let first = stat(dest.path())?.unwrap();
+
+        // Nothing to do the second time around.
+        let outcome = create_and_reproject_if_stale(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(outcome, ReprojectOutcome::UpToDate);
+        assert_eq!(stat(dest.path())?, Some(first));
+
+        // Not in the expected spatial reference.
+        let dst_srs = SpatialRef::from_epsg(3857)?;
+        let outcome = create_and_reproject_if_stale(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(outcome, ReprojectOutcome::Reprojected);
+        assert_eq!(
+            Dataset::open(dest.path())?.spatial_ref()?.auth_code()?,
+            3857
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_source_window() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let srs = source.spatial_ref()?;
+        let gt = source.geo_transform()?;
+        let mut opts = CreateReprojectOptions::new();
+        // The top left quarter.
+        opts.with_source_window(0, 0, 93, 84);
+        assert_eq!(opts.source_window(), Some((0, 0, 93, 84)));
+
+        let dest = TempFixture::empty("source_window.tif");
+        let report = create_and_reproject(&source, dest.path(), &srs, &opts)?;
+        assert_eq!(report.size, (93, 84));
+        for (actual, expected) in report.geo_transform.iter().zip(&gt) {
+            assert!((actual - expected).abs() < 1e-6, "{actual} vs {expected}");
+        }
+        let out = Dataset::open(dest.path())?;
+        let warped = out.rasterband(1)?.read_band_as::<u8>()?;
+        let expected = source
+            .rasterband(1)?
+            .read_as::<u8>((0, 0), (93, 84), (93, 84), None)?;
+        assert_eq!(warped.data, expected.data);
+
+        opts.with_source_window(100, 0, 93, 84);
+        let dest = TempFixture::empty("source_window_outside.tif");
+        assert!(create_and_reproject(&source, dest.path(), &srs, &opts).is_err());
+        opts.with_source_window(0, 0, 0, 84);
+        assert!(create_and_reproject(&source, dest.path(), &srs, &opts).is_err());
+        opts.with_source_window(usize::MAX, 0, 93, 84);
+        assert!(create_and_reproject(&source, dest.path(), &srs, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_src_extent() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let gt = source.geo_transform()?;
+        // The top left quarter, as in `test_create_and_reproject_source_window`.
+        let (min_x, max_y) = (gt[0], gt[3]);
+        let (max_x, min_y) = gt.apply(93.0, 84.0);
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_source_window(1, 1, 2, 2)
+            .with_src_extent(min_x, min_y, max_x, max_y);
+        assert_eq!(opts.src_extent(), Some([min_x, min_y, max_x, max_y]));
+        assert_eq!(opts.source_window(), None);
+
+        let dest = TempFixture::empty("src_extent.tif");
+        let report = create_and_reproject(&source, dest.path(), &source.spatial_ref()?, &opts)?;
+        assert_eq!(report.size, (93, 84));
+
+        // Reprojected, the footprint of the quarter lies within the one of the whole source.
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let extent = |report: &WarpReport| {
+            let gt = report.geo_transform;
+            let (cols, rows) = report.size;
+            (
+                gt[0],
+                gt[3] + gt[5] * rows as f64,
+                gt[0] + gt[1] * cols as f64,
+                gt[3],
+            )
+        };
+        let dest = TempFixture::empty("src_extent_4326.tif");
+        let clipped = create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let dest = TempFixture::empty("full_4326.tif");
+        let full = create_and_reproject(
+            &source,
+            dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        let (c_min_x, c_min_y, c_max_x, c_max_y) = extent(&clipped);
+        let (f_min_x, f_min_y, f_max_x, f_max_y) = extent(&full);
+        let tolerance = full.geo_transform[1];
+        assert!((c_min_x - f_min_x).abs() < tolerance && (c_max_y - f_max_y).abs() < tolerance);
+        assert!(c_max_x < f_max_x - tolerance && c_min_y > f_min_y + tolerance);
+        let area =
+            |(min_x, min_y, max_x, max_y): (f64, f64, f64, f64)| (max_x - min_x) * (max_y - min_y);
+        let ratio = area(extent(&clipped)) / area(extent(&full));
+        assert!((0.2..0.3).contains(&ratio), "{ratio}");
+
+        // Outside of the source.
+        opts.with_src_extent(min_x - 1000.0, min_y, min_x - 500.0, max_y);
+        let dest = TempFixture::empty("src_extent_outside.tif");
+        assert!(create_and_reproject(&source, dest.path(), &dst_srs, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_report_gsd() -> Result<()> {
+        // A 20x20 source with 100 m pixels, rotated by 45 degrees, whose north-up output
+        // has larger pixels (by up to sqrt(2)), to keep as many of them along its diagonal.
+        let srs = SpatialRef::from_epsg(32631)?;
+        let step = 100.0 * 45f64.to_radians().cos();
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+        source.set_geo_transform(&[500000.0, step, step, 4500000.0, step, -step])?;
+        source.set_spatial_ref(&srs)?;
+
+        let dest = TempFixture::empty("report_gsd.tif");
+        let report =
+            create_and_reproject(&source, dest.path(), &srs, &CreateReprojectOptions::new())?;
+        let src_gsd = report.src_gsd.unwrap();
+        assert!((src_gsd - 100.0).abs() < 1e-6, "{src_gsd}");
+        assert_eq!(report.output_gsd, report.geo_transform[1]);
+        let ratio = report.gsd_ratio.unwrap();
+        assert!(ratio > 1.2, "{ratio}");
+
+        // In degrees, once reprojected to geographic coordinates.
+        let dest = TempFixture::empty("report_gsd_4326.tif");
+        let report = create_and_reproject(
+            &source,
+            dest.path(),
+            &SpatialRef::from_epsg(4326)?,
+            &CreateReprojectOptions::new(),
+        )?;
+        let src_gsd = report.src_gsd.unwrap();
+        assert!(src_gsd > 0.0005 && src_gsd < 0.005, "{src_gsd}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_report() -> Result<()> {
+        use crate::cpl::CslStringList;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dest = TempFixture::empty("labels_report.tif");
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let report = create_and_reproject(
+            &source,
+            dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+
+        let out = Dataset::open(dest.path())?;
+        assert_eq!(report.size, out.raster_size());
+        assert_eq!(report.geo_transform, out.geo_transform()?);
+        assert_eq!(report.spatial_ref, dst_srs);
+
+        // Same grid as `gdalwarp -t_srs EPSG:4326`.
+        let mut args = CslStringList::new();
+        for arg in ["-of", "MEM", "-t_srs", "EPSG:4326"] {
+            args.add_string(arg)?;
+        }
+        let warped = unsafe {
+            let app_options = gdal_sys::GDALWarpAppOptionsNew(args.as_ptr(), null_mut());
+            let mut src = source.c_dataset();
+            let warped = gdal_sys::GDALWarp(
+                b"\0".as_ptr().cast(),
+                null_mut(),
+                1,
+                &mut src,
+                app_options,
+                null_mut(),
+            );
+            gdal_sys::GDALWarpAppOptionsFree(app_options);
+            assert!(!warped.is_null());
+            Dataset::from_c_dataset(warped)
+        };
+        assert_eq!(report.size, warped.raster_size());
+        let expected = warped.geo_transform()?;
+        for (actual, expected) in report.geo_transform.iter().zip(&expected) {
+            assert!((actual - expected).abs() < 1e-9, "{actual} vs {expected}");
+        }
+        assert_eq!(report.spatial_ref, warped.spatial_ref()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_cog_creation_options() -> Result<()> {
+        use crate::raster::Compression;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+
+        // COG only supports `GDALCreateCopy`, and takes the options from the copy.
+        let dest = TempFixture::empty("labels_cog.tif");
+        let mut options = CreateReprojectOptions::new();
+        options
+            .with_output_format("COG")
+            .with_creation_option("BLOCKSIZE", "64")
+            .with_creation_option("OVERVIEWS", "IGNORE_EXISTING");
+        create_and_reproject(&source, dest.path(), &dst_srs, &options)?;
+        let out = Dataset::open(dest.path())?;
+        assert_eq!(
+            out.metadata_item("LAYOUT", "IMAGE_STRUCTURE").as_deref(),
+            Some("COG")
+        );
+        let band = out.rasterband(1)?;
+        assert_eq!(band.block_size(), (64, 64));
+        assert!(band.overview_count()? > 0);
+
+        // Later options win, also over the derived ones.
+        let dest = TempFixture::empty("labels_cog_no_overviews.tif");
+        options
+            .with_compression(Compression::None)
+            .with_creation_option("OVERVIEWS", "NONE")
+            .with_creation_option("compress", "LZW");
+        assert_eq!(options.extra_creation_options().len(), 3);
+        create_and_reproject(&source, dest.path(), &dst_srs, &options)?;
+        let out = Dataset::open(dest.path())?;
+        assert_eq!(out.rasterband(1)?.overview_count()?, 0);
+        assert_eq!(
+            out.metadata_item("COMPRESSION", "IMAGE_STRUCTURE")
+                .as_deref(),
+            Some("LZW")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_add_alpha() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 20, 20, 3)?;
+        source.set_geo_transform(&[0.0, 0.5, 0.0, 70.0, 0.0, -0.5])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        let colors = [10, 20, 30];
+        for (i, &color) in colors.iter().enumerate() {
+            source.rasterband(i + 1)?.write(
+                (0, 0),
+                (20, 20),
+                &Buffer::new((20, 20), vec![color; 400]),
+            )?;
+        }
+        // Polar stereographic, so that the grid is rotated and leaves uncovered corners.
+        let dst_srs = SpatialRef::from_epsg(3413)?;
+
+        let dest = TempFixture::empty("rgba.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_add_alpha(true);
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let out = Dataset::open(dest.path())?;
+        assert_eq!(out.raster_count(), 4);
+        let alpha_band = out.rasterband(4)?;
+        assert_eq!(
+            alpha_band.color_interpretation(),
+            ColorInterpretation::AlphaBand
+        );
+        let alpha = alpha_band.read_band_as::<u8>()?.data;
+        assert!(alpha.contains(&0));
+        assert!(alpha.contains(&255));
+        for (i, &color) in colors.iter().enumerate() {
+            let values = out.rasterband(i + 1)?.read_band_as::<u8>()?.data;
+            for (&v, &a) in values.iter().zip(&alpha) {
+                match a {
+                    255 => assert_eq!(v, color),
+                    0 => assert_eq!(v, 0),
+                    a => panic!("unexpected alpha {a}"),
+                }
+            }
+        }
+
+        // Same coverage into an existing RGBA dataset.
+        let (cols, rows) = (out.raster_size().0 as isize, out.raster_size().1 as isize);
+        let mut rgba = driver.create_with_band_type::<u8, _>("", cols, rows, 4)?;
+        rgba.set_geo_transform(&out.geo_transform()?)?;
+        rgba.set_spatial_ref(&dst_srs)?;
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut().with_dst_alpha_band(true);
+        reproject_into(&source, &rgba, &opts)?;
+        assert_eq!(rgba.rasterband(4)?.read_band_as::<u8>()?.data, alpha);
+
+        // No band left for alpha.
+        let mut rgb = driver.create_with_band_type::<u8, _>("", cols, rows, 3)?;
+        rgb.set_geo_transform(&out.geo_transform()?)?;
+        rgb.set_spatial_ref(&dst_srs)?;
+        assert!(reproject_into(&source, &rgb, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_dst_alpha_band() -> Result<()> {
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let dest = TempFixture::empty("dst_alpha.tif");
+        // Same as `gdalwarp -dstalpha`.
+        let mut opts = CreateReprojectOptions::new();
+        opts.warp_options_mut().with_dst_alpha_band(true);
+        create_and_reproject(&source, dest.path(), &SpatialRef::from_epsg(4326)?, &opts)?;
+
+        let out = Dataset::open(dest.path())?;
+        assert_eq!(out.raster_count(), source.raster_count() + 1);
+        let alpha_band = out.rasterband(out.raster_count())?;
+        assert_eq!(alpha_band.band_type(), GdalDataType::UInt8);
+        assert_eq!(
+            alpha_band.color_interpretation(),
+            ColorInterpretation::AlphaBand
+        );
+        let alpha = alpha_band.read_band_as::<u8>()?.data;
+        assert!(alpha.contains(&0));
+        assert!(alpha.contains(&255));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_to_cog() -> Result<()> {
+        use crate::raster::Compression;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(255.0)
+            .with_compression(Compression::Lzw);
+
+        let cog_path = TempFixture::empty("labels_cog.tif");
+        let cog = reproject_to_cog(&source, cog_path.path(), &dst_srs, &opts)?;
+        drop(cog);
+        let cog = Dataset::open(cog_path.path())?;
+        assert_eq!(cog.driver().short_name(), "GTiff");
+        assert_eq!(
+            cog.metadata_item("LAYOUT", "IMAGE_STRUCTURE").as_deref(),
+            Some("COG")
+        );
+        assert_eq!(
+            cog.metadata_item("COMPRESSION", "IMAGE_STRUCTURE")
+                .as_deref(),
+            Some("LZW")
+        );
+
+        let direct_path = TempFixture::empty("labels_direct.tif");
+        create_and_reproject(&source, direct_path.path(), &dst_srs, &opts)?;
+        let direct = Dataset::open(direct_path.path())?;
+        assert_eq!(cog.raster_size(), direct.raster_size());
+        assert_eq!(cog.geo_transform()?, direct.geo_transform()?);
+        let cog_band = cog.rasterband(1)?;
+        assert_eq!(cog_band.no_data_value(), Some(255.0));
+        let stats = cog_band.get_statistics(true, false)?.unwrap();
+        let expected = direct.rasterband(1)?.get_statistics(true, false)?.unwrap();
+        assert_eq!(stats, expected);
+
+        let res = direct.geo_transform()?[1] * 2.0;
+        opts.with_target_resolution(res, res);
+        let cog = reproject_to_cog(&source, cog_path.path(), &dst_srs, &opts)?;
+        create_and_reproject(&source, direct_path.path(), &dst_srs, &opts)?;
+        let direct = Dataset::open(direct_path.path())?;
+        assert_eq!(cog.raster_size(), direct.raster_size());
+        assert_eq!(cog.geo_transform()?, direct.geo_transform()?);
+
+        opts.with_output_datatype(GdalDataType::Int8);
+        assert!(reproject_to_cog(&source, cog_path.path(), &dst_srs, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_pam_stats() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let dest = TempFixture::empty("labels_pam.tif");
+        let sidecar = dest.path().with_extension("tif.aux.xml");
+
+        create_and_reproject(
+            &source,
+            dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        assert!(!sidecar.exists());
+
+        let mut options = CreateReprojectOptions::new();
+        options.with_pam_stats(true);
+        assert!(options.pam_stats());
+        create_and_reproject(&source, dest.path(), &dst_srs, &options)?;
+        let xml = std::fs::read_to_string(&sidecar).unwrap();
+        assert!(xml.contains("STATISTICS_MEAN"), "{xml}");
+        assert!(xml.contains("<Histograms>"), "{xml}");
+
+        // The saved statistics are picked up without forcing them to be computed.
+        let out = Dataset::open(dest.path())?;
+        let band = out.rasterband(1)?;
+        let stats = band.get_statistics(false, false)?.unwrap();
+        let expected = band.compute_raster_min_max(false)?;
+        assert_eq!((stats.min, stats.max), (expected.min, expected.max));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_same_srs() -> Result<()> {
+        use crate::raster::{Compression, NumThreads};
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        // The same spatial reference, but not the same object.
+        let dst_srs = SpatialRef::from_wkt(&source.spatial_ref()?.to_wkt()?)?;
+        assert!(dst_srs.is_same(&source.spatial_ref()?));
+
+        let dest = TempFixture::empty("labels_copy.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_compression(Compression::Lzw);
+        let report = create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!(report.size, source.raster_size());
+        assert_eq!(report.geo_transform, source.geo_transform()?);
+        let out = Dataset::open(dest.path())?;
+        assert!(out.raster_equals(&source, 0.0)?);
+        assert_eq!(
+            out.metadata_item("COMPRESSION", "IMAGE_STRUCTURE"),
+            Some("LZW".into())
+        );
+
+        // Not with warp options changing the warped values, here writing the coverage of the
+        // output into its last (and only) band.
+        opts.warp_options_mut()
+            .with_num_threads(NumThreads::Count(2))?
+            .with_dst_alpha_band(true);
+        let dest = TempFixture::empty("labels_alpha.tif");
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        assert!(!Dataset::open(dest.path())?.raster_equals(&source, 0.0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_proj4() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let web_mercator = SpatialRef::from_proj4(
+            "+proj=merc +a=6378137 +b=6378137 +lat_ts=0 +lon_0=0 +x_0=0 +y_0=0 +k=1 +units=m \
+             +nadgrids=@null +wktext +no_defs",
+        )?;
+        let from_proj4 = TempFixture::empty("labels_proj4.tif");
+        let report = create_and_reproject(
+            &source,
+            from_proj4.path(),
+            &web_mercator,
+            &CreateReprojectOptions::new(),
+        )?;
+        let from_epsg = TempFixture::empty("labels_3857.tif");
+        let expected = create_and_reproject(
+            &source,
+            from_epsg.path(),
+            &SpatialRef::from_epsg(3857)?,
+            &CreateReprojectOptions::new(),
+        )?;
+        assert_eq!(report.size, expected.size);
+        for (a, b) in report.geo_transform.iter().zip(&expected.geo_transform) {
+            assert!((a - b).abs() < 1e-6, "{a} != {b}");
+        }
+        let out = Dataset::open(from_proj4.path())?;
+        assert!(out.spatial_ref()?.to_proj4()?.contains("+proj=merc"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_stack_like() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+        let reference_file = TempFixture::empty("reference.tif");
+        let mut options = CreateReprojectOptions::new();
+        options.with_target_resolution(0.0005, 0.0005);
+        create_and_reproject(
+            &source,
+            reference_file.path(),
+            &SpatialRef::from_epsg(4326)?,
+            &options,
+        )?;
+        let reference = Dataset::open(reference_file.path())?;
+
+        // One source in the spatial reference of the reference, but on another grid.
+        let (in_mem, _) = create_and_reproject_with_mask(
+            &source,
+            &SpatialRef::from_epsg(4326)?,
+            &CreateReprojectOptions::new(),
+        )?;
+        let sources = [Dataset::open(fixture("labels.tif"))?, in_mem];
+        let out_dir = tempfile::tempdir().unwrap();
+        let paths = reproject_stack_like(&sources, &reference, out_dir.path(), &options)?;
+        assert_eq!(
+            paths,
+            [
+                out_dir.path().join("labels.tif"),
+                out_dir.path().join("1.tif")
+            ]
+        );
+        for path in &paths {
+            let out = Dataset::open(path)?;
+            assert_eq!(out.raster_size(), reference.raster_size());
+            assert_eq!(out.geo_transform()?, reference.geo_transform()?);
+            assert_eq!(out.spatial_ref()?, reference.spatial_ref()?);
+        }
+
+        // Both would be written to `labels.tif`.
+        let sources = [
+            Dataset::open(fixture("labels.tif"))?,
+            source.create_copy(&mem_driver, "labels", &[])?,
+        ];
+        let out_dir = tempfile::tempdir().unwrap();
+        assert!(reproject_stack_like(&sources, &reference, out_dir.path(), &options).is_err());
+        assert_eq!(std::fs::read_dir(out_dir.path()).unwrap().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_post_band_fn() -> Result<()> {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let plain = TempFixture::empty("labels_plain.tif");
+        create_and_reproject(
+            &source,
+            plain.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        let expected = Dataset::open(plain.path())?
+            .rasterband(1)?
+            .read_band_as::<u8>()?;
+
+        let unexpected_call = Arc::new(AtomicBool::new(false));
+        let mut options = CreateReprojectOptions::new();
+        options.with_post_band_fn({
+            let unexpected_call = unexpected_call.clone();
+            move |band_index, values| {
+                // One warped block at a time.
+                if band_index != 1 || values.len() > STREAMING_BLOCK_SIZE * STREAMING_BLOCK_SIZE {
+                    unexpected_call.store(true, Ordering::Relaxed);
+                }
+                values.iter_mut().for_each(|v| *v += 1.0);
+            }
+        });
+        assert!(options.has_post_band_fn());
+        let shifted = TempFixture::empty("labels_shifted.tif");
+        create_and_reproject(&source, shifted.path(), &dst_srs, &options)?;
+        assert!(!unexpected_call.load(Ordering::Relaxed));
+
+        let out = Dataset::open(shifted.path())?;
+        let actual = out.rasterband(1)?.read_band_as::<u8>()?;
+        assert_eq!(actual.size, expected.size);
+        for (&actual, &expected) in actual.data.iter().zip(&expected.data) {
+            // Byte values saturate.
+            assert_eq!(actual, expected.saturating_add(1));
+        }
+
+        assert!(reproject_to_cog(&source, shifted.path(), &dst_srs, &options).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+use std::ptr::null_mut;
+
+use gdal_sys::{self, CPLErr};
+
+use super::create::source_window_vrt;
+use super::reproject::add_transformer_settings;
+use crate::cpl::CslStringList;
+use crate::dataset::Dataset;
+use crate::errors::*;
+use crate::raster::{CreateReprojectOptions, GdalWarpOptions};
+use crate::spatial_ref::SpatialRef;
+use crate::utils::{_last_cpl_err, _last_null_pointer_err};
+use crate::GeoTransform;
+
+/// Compute the geotransform and size (columns, rows) GDAL suggests for warping
+/// `src` into `dst_srs`.
+///
+/// If sample steps or a sample grid are set in `warp_options`, the extent is refined by
+/// sampling the source accordingly, keeping the resolution suggested by GDAL.
+pub(super) fn suggested_grid(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    warp_options: &GdalWarpOptions,
+) -> Result<(GeoTransform, (usize, usize))> {
+    let transformer = transformer_to(src, dst_srs, warp_options)?;
+    let grid = unsafe { suggested_grid_with(src, dst_srs, warp_options, transformer) };
+    unsafe { gdal_sys::GDALDestroyGenImgProjTransformer(transformer) };
+    grid
+}
+
+/// Same as [`suggested_grid`], with the `transformer` created by [`transformer_to`] for `src`,
+/// `dst_srs` and `warp_options`, which is left as is.
+///
+/// # Safety
+/// `transformer` must be valid, without a destination geotransform.
+pub(super) unsafe fn suggested_grid_with(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    warp_options: &GdalWarpOptions,
+    transformer: *mut libc::c_void,
+) -> Result<(GeoTransform, (usize, usize))> {
+    let mut gt = GeoTransform::default();
+    let (mut cols, mut rows) = (0, 0);
+    let rv = gdal_sys::GDALSuggestedWarpOutput(
+        src.c_dataset(),
+        Some(gdal_sys::GDALGenImgProjTransform),
+        transformer,
+        gt.as_mut_ptr(),
+        &mut cols,
+        &mut rows,
+    );
+    let sampled = if rv == CPLErr::CE_None
+        && (warp_options.sample_steps().is_some() || warp_options.sample_grid())
+    {
+        Some(sampled_extent(
+            src,
+            transformer,
+            warp_options.sample_steps().unwrap_or(21),
+            warp_options.sample_grid(),
+        ))
+    } else {
+        None
+    };
+    let poles = if rv == CPLErr::CE_None && dst_srs.is_geographic() {
+        Some(poles_in_source(src, transformer))
+    } else {
+        None
+    };
+    if rv != CPLErr::CE_None {
+        return Err(_last_cpl_err(rv));
+    }
+
+    let extent = sampled.transpose()?;
+    let extent = match poles {
+        Some((north, south)) if north || south => {
+            let [_, mut min_y, _, mut max_y] = extent.unwrap_or([
+                gt[0],
+                gt[3] + gt[5] * rows as f64,
+                gt[0] + gt[1] * cols as f64,
+                gt[3],
+            ]);
+            if north {
+                max_y = 90.0;
+            }
+            if south {
+                min_y = -90.0;
+            }
+            Some([-180.0, min_y, 180.0, max_y])
+        }
+        _ => extent,
+    };
+    match extent {
+        Some([min_x, min_y, max_x, max_y]) => {
+            let (x_res, y_res) = (gt[1], -gt[5]);
+            let cols = ((max_x - min_x) / x_res).ceil().max(1.0) as usize;
+            let rows = ((max_y - min_y) / y_res).ceil().max(1.0) as usize;
+            Ok(([min_x, x_res, 0.0, max_y, 0.0, -y_res], (cols, rows)))
+        }
+        None => Ok((gt, (cols as usize, rows as usize))),
+    }
+}
+
+/// Creates a `GDALGenImgProjTransform` transformer from the pixels of `src` to coordinates
+/// in `dst_srs`, to be destroyed with `GDALDestroyGenImgProjTransformer`.
+pub(super) fn transformer_to(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    warp_options: &GdalWarpOptions,
+) -> Result<*mut libc::c_void> {
+    let mut transformer_options = CslStringList::new();
+    transformer_options.set_name_value("DST_SRS", &dst_srs.to_wkt()?)?;
+    add_transformer_settings(&mut transformer_options, warp_options)?;
+    let transformer = unsafe {
+        gdal_sys::GDALCreateGenImgProjTransformer2(
+            src.c_dataset(),
+            null_mut(),
+            transformer_options.as_ptr(),
+        )
+    };
+    if transformer.is_null() {
+        return Err(_last_null_pointer_err("GDALCreateGenImgProjTransformer2"));
+    }
+    #[cfg(test)]
+    TRANSFORMERS_CREATED.with(|count| count.set(count.get() + 1));
+    Ok(transformer)
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Number of transformers created by [`transformer_to`] on the current thread.
+    pub(crate) static TRANSFORMERS_CREATED: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// Whether `src` contains the north or south pole, `dst_srs` being geographic.
+pub(super) fn contains_pole(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    warp_options: &GdalWarpOptions,
+) -> Result<bool> {
+    let transformer = transformer_to(src, dst_srs, warp_options)?;
+    let (north, south) = poles_in_source(src, transformer);
+    unsafe { gdal_sys::GDALDestroyGenImgProjTransformer(transformer) };
+    Ok(north || south)
+}
+
+/// Whether the north and south poles fall within `src`, according to the (source pixels to
+/// geographic destination coordinates) `transformer`.
+///
+/// The edges of a source containing a pole, e.g. in a polar stereographic projection, don't
+/// reach it once transformed, so the extent GDAL suggests doesn't either.
+fn poles_in_source(src: &Dataset, transformer: *mut libc::c_void) -> (bool, bool) {
+    let (cols, rows) = src.raster_size();
+    let mut xs = [0.0, 0.0];
+    let mut ys = [90.0, -90.0];
+    let mut zs = [0.0, 0.0];
+    let mut success = [0; 2];
+    unsafe {
+        gdal_sys::GDALGenImgProjTransform(
+            transformer,
+            1,
+            2,
+            xs.as_mut_ptr(),
+            ys.as_mut_ptr(),
+            zs.as_mut_ptr(),
+            success.as_mut_ptr(),
+        )
+    };
+    let inside = |i: usize| {
+        success[i] != 0
+            && (0.0..=cols as f64).contains(&xs[i])
+            && (0.0..=rows as f64).contains(&ys[i])
+    };
+    (inside(0), inside(1))
+}
+
+/// Bounding box (min x, min y, max x, max y) of the pixels of `src` sampled with `steps`
+/// points along each edge (or, with `grid`, `steps` × `steps` points over the whole raster),
+/// as transformed by the (source pixels to destination coordinates) `transformer`.
+fn sampled_extent(
+    src: &Dataset,
+    transformer: *mut libc::c_void,
+    steps: usize,
+    grid: bool,
+) -> Result<[f64; 4]> {
+    let (cols, rows) = src.raster_size();
+    let ratios: Vec<f64> = (0..steps).map(|i| i as f64 / (steps - 1) as f64).collect();
+    let (mut xs, mut ys) = (Vec::new(), Vec::new());
+    for (i, &ry) in ratios.iter().enumerate() {
+        for (j, &rx) in ratios.iter().enumerate() {
+            let on_edge = i == 0 || j == 0 || i == steps - 1 || j == steps - 1;
+            if grid || on_edge {
+                xs.push(rx * cols as f64);
+                ys.push(ry * rows as f64);
+            }
+        }
+    }
+    let mut zs = vec![0.0; xs.len()];
+    let mut success = vec![0; xs.len()];
+    unsafe {
+        gdal_sys::GDALGenImgProjTransform(
+            transformer,
+            0,
+            xs.len() as libc::c_int,
+            xs.as_mut_ptr(),
+            ys.as_mut_ptr(),
+            zs.as_mut_ptr(),
+            success.as_mut_ptr(),
+        )
+    };
+
+    let mut extent = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    for ((&x, &y), _) in xs.iter().zip(&ys).zip(&success).filter(|(_, &ok)| ok != 0) {
+        extent = [
+            extent[0].min(x),
+            extent[1].min(y),
+            extent[2].max(x),
+            extent[3].max(y),
+        ];
+    }
+    if extent[0] > extent[2] {
+        return Err(GdalError::BadArgument(
+            "none of the sampled source points could be transformed".into(),
+        ));
+    }
+    Ok(extent)
+}
+
+/// Size and geotransform of the output of reprojecting a dataset, as computed by
+/// [`suggested_warp_output`], before anything is warped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedWarpOutput {
+    /// Number of columns of the output.
+    pub width: usize,
+    /// Number of rows of the output.
+    pub height: usize,
+    /// Geotransform of the output.
+    pub geo_transform: GeoTransform,
+}
+
+/// Compute the size and geotransform of the output
+/// [`create_and_reproject`](crate::raster::create_and_reproject) would create for `src`,
+/// `dst_srs` and `options`, without warping nor writing anything, e.g. to decide how to split a
+/// reprojection in tiles.
+///
+/// This is the grid suggested by GDAL, adjusted for the target resolution, extent and
+/// aligned pixels set in `options`, if any.
+///
+/// See [`GDALSuggestedWarpOutput`](https://gdal.org/api/gdal_alg.html#_CPPv423GDALSuggestedWarpOutput12GDALDatasetH19GDALTransformerFuncPvPdPiPi).
+pub fn suggested_warp_output(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<SuggestedWarpOutput> {
+    if let Some(window) = options.checked_source_window(src)? {
+        let window = source_window_vrt(src, window)?;
+        return suggested_warp_output(&window, dst_srs, &options.without_source_window());
+    }
+    let (geo_transform, width, height) = output_grid(src, dst_srs, options)?;
+    Ok(SuggestedWarpOutput {
+        width,
+        height,
+        geo_transform,
+    })
+}
+
+/// Grid (geotransform, columns and rows) covering the extent of `ds` in `dst_srs`, at the
+/// target resolution set in `options` if any, otherwise at the resolution suggested by GDAL.
+pub(super) fn output_grid(
+    ds: &Dataset,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<(GeoTransform, usize, usize)> {
+    output_grid_from(
+        suggested_grid(ds, dst_srs, options.warp_options())?,
+        options,
+    )
+}
+
+/// Same as [`output_grid`], from the grid suggested by [`suggested_grid`].
+pub(super) fn output_grid_from(
+    suggested: (GeoTransform, (usize, usize)),
+    options: &CreateReprojectOptions,
+) -> Result<(GeoTransform, usize, usize)> {
+    let (suggested_gt, (suggested_cols, suggested_rows)) = suggested;
+    let resolution = options.target_resolution();
+    if let Some(resolution) = resolution {
+        let (x_res, y_res) = resolution;
+        if !(x_res > 0.0 && y_res > 0.0) {
+            return Err(GdalError::BadArgument(format!(
+                "target resolution must be positive, got {resolution:?}"
+            )));
+        }
+    }
+    let aligned = options.target_aligned_pixels();
+    if aligned && resolution.is_none() {
+        return Err(GdalError::BadArgument(
+            "aligning pixels requires a target resolution".into(),
+        ));
+    }
+
+    let src_min_x = suggested_gt[0];
+    let src_max_x = suggested_gt[0] + suggested_gt[1] * suggested_cols as f64;
+    let src_max_y = suggested_gt[3];
+    let src_min_y = suggested_gt[3] + suggested_gt[5] * suggested_rows as f64;
+    let extent = options.target_extent();
+    let [mut min_x, mut min_y, mut max_x, mut max_y] = match extent {
+        Some(extent) => {
+            let [min_x, min_y, max_x, max_y] = extent;
+            if !(min_x < max_x && min_y < max_y) {
+                return Err(GdalError::BadArgument(format!(
+                    "invalid target extent: {extent:?}"
+                )));
+            }
+            if max_x <= src_min_x || min_x >= src_max_x || max_y <= src_min_y || min_y >= src_max_y
+            {
+                return Err(GdalError::EmptyOutput);
+            }
+            extent
+        }
+        None if resolution.is_none() => {
+            return Ok((suggested_gt, suggested_cols, suggested_rows));
+        }
+        None => [src_min_x, src_min_y, src_max_x, src_max_y],
+    };
+
+    let (x_res, y_res) = resolution.unwrap_or((suggested_gt[1], -suggested_gt[5]));
+    if aligned {
+        min_x = (min_x / x_res).floor() * x_res;
+        min_y = (min_y / y_res).floor() * y_res;
+        max_x = (max_x / x_res).ceil() * x_res;
+        max_y = (max_y / y_res).ceil() * y_res;
+    }
+    let (cols, rows) = if extent.is_some() || aligned {
+        // Rounded as gdalwarp does, so that an extent on the pixel grid isn't off by one.
+        (
+            ((max_x - min_x) / x_res).round().max(1.0) as usize,
+            ((max_y - min_y) / y_res).round().max(1.0) as usize,
+        )
+    } else {
+        // Covering at least the computed extent.
+        (
+            ((max_x - min_x) / x_res).ceil().max(1.0) as usize,
+            ((max_y - min_y) / y_res).ceil().max(1.0) as usize,
+        )
+    };
+    Ok(([min_x, x_res, 0.0, max_y, 0.0, -y_res], cols, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::create_and_reproject;
+    use crate::test_utils::{fixture, TempFixture};
+    use crate::DriverManager;
+
+    #[test]
+    fn test_suggested_warp_output_sample_steps() -> Result<()> {
+        // The north polar cap, to polar stereographic: the output extent is reached on the
+        // 60°N parallel, which the edge samples approximate with a polygon.
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 360, 30, 1)?;
+        source.set_geo_transform(&[-180.0, 1.0, 0.0, 90.0, 0.0, -1.0])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        let dst_srs = SpatialRef::from_epsg(3413)?;
+
+        let width = |steps: usize, grid: bool| -> Result<f64> {
+            let mut warp_options = GdalWarpOptions::new();
+            warp_options
+                .with_sample_steps(steps)?
+                .with_sample_grid(grid)?;
+            let (gt, (cols, _)) = suggested_grid(&source, &dst_srs, &warp_options)?;
+            Ok(gt[1] * cols as f64)
+        };
+        let reference = width(2000, false)?;
+        let few = width(3, false)?;
+        let many = width(200, false)?;
+        assert!(few < reference * 0.95, "{few} vs {reference}");
+        assert!(
+            (many - reference).abs() < reference * 1e-3,
+            "{many} vs {reference}"
+        );
+        assert!((width(200, true)? - reference).abs() < reference * 1e-3);
+
+        // Used when creating the output.
+        let dest = TempFixture::empty("polar.tif");
+        let mut opts = CreateReprojectOptions::new();
+        opts.warp_options_mut().with_sample_steps(200)?;
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let out = Dataset::open(dest.path())?;
+        let out_width = out.geo_transform()?[1] * out.raster_size().0 as f64;
+        assert!((out_width - many).abs() < reference * 1e-3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggested_warp_output() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4269)?;
+        let opts = CreateReprojectOptions::new();
+        let suggested = suggested_warp_output(&source, &dst_srs, &opts)?;
+
+        let dest = TempFixture::empty("labels_4269.tif");
+        create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        let out = Dataset::open(dest.path())?;
+        assert_eq!((suggested.width, suggested.height), out.raster_size());
+        for (suggested, actual) in suggested.geo_transform.iter().zip(&out.geo_transform()?) {
+            assert!((suggested - actual).abs() < 1e-9, "{suggested} vs {actual}");
+        }
+
+        // With the target resolution of the options.
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_target_resolution(0.001, 0.001);
+        let suggested = suggested_warp_output(&source, &dst_srs, &opts)?;
+        assert_eq!(suggested.geo_transform[1], 0.001);
+        let dest = TempFixture::empty("labels_4269_resolution.tif");
+        let report = create_and_reproject(&source, dest.path(), &dst_srs, &opts)?;
+        assert_eq!((suggested.width, suggested.height), report.size);
+        assert_eq!(suggested.geo_transform, report.geo_transform);
+        Ok(())
+    }
+}
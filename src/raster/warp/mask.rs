@@ -0,0 +1,274 @@
+use std::path::Path;
+
+use gdal_sys::{self, OGRwkbGeometryType};
+
+use super::create::{check_no_premultiply_alpha, is_nodata_value, source_window_vrt};
+use super::grid::output_grid;
+use super::reproject::reproject_image;
+use crate::dataset::Dataset;
+use crate::errors::*;
+use crate::raster::{polygonize, CreateReprojectOptions, GdalDataType, PolygonizeOptions};
+use crate::spatial_ref::SpatialRef;
+use crate::vector::{Geometry, LayerAccess, LayerOptions};
+use crate::DriverManager;
+
+/// Reproject `src` into an in-memory raster in the `dst_srs` spatial reference, and return it
+/// along with its valid-data mask.
+///
+/// The output grid and data type are the same as for
+/// [`create_and_reproject`](crate::raster::create_and_reproject); the output format,
+/// compression and creation options set in `options` are ignored, and neither a scaled output
+/// type nor [`with_add_alpha`](CreateReprojectOptions::with_add_alpha) is supported. Both
+/// datasets are created with the `MEM` driver.
+///
+/// The mask is a single `UInt8` band on the same grid, `255` where the raster holds warped
+/// data, and `0` where it doesn't: where no source pixel contributes (according to the
+/// destination alpha computed during the warp), and where every band holds the destination
+/// no-data value, if any.
+pub fn create_and_reproject_with_mask(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<(Dataset, Dataset)> {
+    check_no_premultiply_alpha(options)?;
+    if options.output_scaling().is_some() {
+        return Err(GdalError::BadArgument(
+            "scaling is not supported when reprojecting with a mask".into(),
+        ));
+    }
+    if options.add_alpha() {
+        return Err(GdalError::BadArgument(
+            "an alpha band is not supported when reprojecting with a mask, use the mask instead"
+                .into(),
+        ));
+    }
+    if let Some(window) = options.checked_source_window(src)? {
+        let window = source_window_vrt(src, window)?;
+        let options = options.without_source_window();
+        return create_and_reproject_with_mask(&window, dst_srs, &options);
+    }
+    let band_count = src.raster_count();
+    if band_count == 0 {
+        return Err(GdalError::BadArgument(
+            "source dataset has no raster bands".into(),
+        ));
+    }
+    let band_type = match options.output_datatype() {
+        Some(band_type) => band_type,
+        None => src.rasterband(1)?.band_type(),
+    };
+    let options = &*options.resolved_for(src, band_count)?;
+
+    let (gt, cols, rows) = output_grid(src, dst_srs, options)?;
+    let driver = DriverManager::get_driver_by_name("MEM")?;
+    let create = |bands: usize, band_type: GdalDataType| -> Result<Dataset> {
+        let mut ds = driver._create_with_band_type_with_options(
+            Path::new(""),
+            cols as isize,
+            rows as isize,
+            bands as isize,
+            band_type,
+            &[],
+        )?;
+        ds.set_geo_transform(&gt)?;
+        ds.set_spatial_ref(dst_srs)?;
+        Ok(ds)
+    };
+
+    // Warp with an extra alpha band, split off into the mask afterwards.
+    let warped = create(band_count + 1, band_type)?;
+    let mut warp_options = options.clone_and_init_warp_options(src, band_count)?;
+    warp_options.init_default_band_mapping(band_count);
+    warp_options.set_dst_alpha_band(band_count + 1);
+    match options.has_dst_nodata() {
+        true => warp_options.with_warp_option("INIT_DEST", "NO_DATA")?,
+        false => warp_options.with_warp_option("INIT_DEST", "0")?,
+    };
+    for i in 1..=band_count {
+        warped
+            .rasterband(i)?
+            .set_no_data_value(options.dst_nodata_of_band(i))?;
+    }
+    reproject_image(
+        src,
+        None,
+        &warped,
+        None,
+        &warp_options,
+        options.max_error().unwrap_or(0.0),
+    )?;
+
+    let raster = create(band_count, band_type)?;
+    let mask = create(1, GdalDataType::UInt8)?;
+    let mut raster_bands = (1..=band_count)
+        .map(|i| {
+            let nodata = options.dst_nodata_of_band(i);
+            let mut band = raster.rasterband(i)?;
+            band.set_no_data_value(nodata)?;
+            Ok((i, warped.rasterband(i)?, band, nodata))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let warped_alpha = warped.rasterband(band_count + 1)?;
+    let mut mask_band = mask.rasterband(1)?;
+    for line in 0..rows as isize {
+        let mut valid = warped_alpha.read_as::<u8>((0, line), (cols, 1), (cols, 1), None)?;
+        let mut all_nodata = vec![options.has_dst_nodata(); cols];
+        for (band_index, warped_band, band, nodata) in &mut raster_bands {
+            let mut data = warped_band.read_as::<f64>((0, line), (cols, 1), (cols, 1), None)?;
+            for (is_nodata, &v) in all_nodata.iter_mut().zip(&data.data) {
+                *is_nodata &= is_nodata_value(v, *nodata);
+            }
+            if let Some(post_band_fn) = options.post_band_fn() {
+                post_band_fn.call(*band_index, &mut data.data);
+            }
+            band.write((0, line), (cols, 1), &data)?;
+        }
+        for (v, is_nodata) in valid.data.iter_mut().zip(all_nodata) {
+            *v = if *v > 0 && !is_nodata { 255 } else { 0 };
+        }
+        mask_band.write((0, line), (cols, 1), &valid)?;
+    }
+    Ok((raster, mask))
+}
+
+/// Same as [`create_and_reproject_with_mask`], but returns the valid-data footprint of the
+/// output, instead of its mask, e.g. to ingest it in a catalog.
+///
+/// The footprint is a multipolygon in `dst_srs`, made of the polygonized valid regions of the
+/// mask. It follows the pixel edges of the output, so, for a rotated source, its outline is
+/// stair-stepped.
+pub fn create_and_reproject_with_footprint(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    options: &CreateReprojectOptions,
+) -> Result<(Dataset, Geometry)> {
+    let (raster, mask) = create_and_reproject_with_mask(src, dst_srs, options)?;
+    let mask_band = mask.rasterband(1)?;
+    let mut polygons = DriverManager::get_driver_by_name("Memory")?.create_vector_only("")?;
+    let mut layer = polygons.create_layer(LayerOptions {
+        name: "footprint",
+        srs: Some(dst_srs),
+        ty: OGRwkbGeometryType::wkbPolygon,
+        ..Default::default()
+    })?;
+    // The mask is its own mask, so that only the valid regions are polygonized.
+    polygonize(
+        &mask_band,
+        Some(&mask_band),
+        &mut layer,
+        None,
+        PolygonizeOptions::default(),
+    )?;
+
+    let mut footprint = Geometry::empty(OGRwkbGeometryType::wkbMultiPolygon)?;
+    for feature in layer.features() {
+        if let Some(polygon) = feature.geometry() {
+            footprint.add_geometry(polygon.clone())?;
+        }
+    }
+    footprint.set_spatial_ref(dst_srs.clone());
+    Ok((raster, footprint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::Buffer;
+    use crate::test_utils::fixture;
+
+    #[test]
+    fn test_create_and_reproject_with_footprint() -> Result<()> {
+        // A 20x20 source, rotated by 30 degrees.
+        let srs = SpatialRef::from_epsg(32631)?;
+        let (cos, sin) = (30f64.to_radians().cos(), 30f64.to_radians().sin());
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+        source.set_geo_transform(&[
+            500000.0,
+            100.0 * cos,
+            100.0 * sin,
+            4500000.0,
+            100.0 * sin,
+            -100.0 * cos,
+        ])?;
+        source.set_spatial_ref(&srs)?;
+        source
+            .rasterband(1)?
+            .write((0, 0), (20, 20), &Buffer::new((20, 20), vec![1u8; 400]))?;
+
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(0.0);
+        let (raster, footprint) = create_and_reproject_with_footprint(&source, &dst_srs, &opts)?;
+        assert_eq!(raster.raster_count(), 1);
+        assert_eq!(
+            footprint.geometry_type(),
+            OGRwkbGeometryType::wkbMultiPolygon
+        );
+        assert_eq!(footprint.spatial_ref().unwrap().auth_code()?, 4326);
+
+        let gt = raster.geo_transform()?;
+        let (cols, rows) = raster.raster_size();
+        let extent_area = (gt[1] * cols as f64) * (-gt[5] * rows as f64);
+        let area = footprint.area();
+        assert!(
+            area > 0.0 && area < 0.9 * extent_area,
+            "{area} {extent_area}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_with_mask() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(255.0);
+        let (raster, mask) =
+            create_and_reproject_with_mask(&source, &SpatialRef::from_epsg(4326)?, &opts)?;
+
+        assert_eq!(raster.raster_count(), source.raster_count());
+        assert_eq!(mask.raster_count(), 1);
+        assert_eq!(mask.raster_size(), raster.raster_size());
+        assert_eq!(mask.geo_transform()?, raster.geo_transform()?);
+        let mask_band = mask.rasterband(1)?;
+        assert_eq!(mask_band.band_type(), GdalDataType::UInt8);
+
+        let band = raster.rasterband(1)?;
+        assert_eq!(band.no_data_value(), Some(255.0));
+        let values = band.read_band_as::<u8>()?;
+        let mask = mask_band.read_band_as::<u8>()?;
+        assert!(mask.data.contains(&255));
+        for (&v, &m) in values.data.iter().zip(&mask.data) {
+            assert_eq!(m == 0, v == 255, "value {v}, mask {m}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_with_mask_add_alpha() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_add_alpha(true);
+        let result = create_and_reproject_with_mask(&source, &SpatialRef::from_epsg(4326)?, &opts);
+        assert!(matches!(result, Err(GdalError::BadArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_with_mask_source_window() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let mut opts = CreateReprojectOptions::new();
+        // The top left quarter, in the spatial reference of the source.
+        opts.with_source_window(0, 0, 93, 84);
+        let (raster, mask) =
+            create_and_reproject_with_mask(&source, &source.spatial_ref()?, &opts)?;
+        assert_eq!(raster.raster_size(), (93, 84));
+        assert_eq!(mask.raster_size(), (93, 84));
+        let warped = raster.rasterband(1)?.read_band_as::<u8>()?;
+        let expected = source
+            .rasterband(1)?
+            .read_as::<u8>((0, 0), (93, 84), (93, 84), None)?;
+        assert_eq!(warped.data, expected.data);
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! Raster warping (reprojection) API.
+//!
+//! See the [GDAL Warp API tutorial](https://gdal.org/tutorials/warp_tut.html) for background.
+
+/// Implements, on the `$builder` wrapper of `$options`, an owned counterpart of each listed
+/// `&mut self` setter of `$options`, which calls it and returns the builder (or an error for
+/// the `fallible` ones).
+macro_rules! owned_setters {
+    (
+        $builder:ident => $options:ident {
+            $(fn $name:ident($($arg:ident: $ty:ty),*) => $setter:ident;)*
+        }
+        $(fallible {
+            $(fn $f_name:ident($($f_arg:ident: $f_ty:ty),*) => $f_setter:ident;)*
+        })?
+    ) => {
+        impl $builder {
+            $(
+                #[doc = concat!(
+                    "Owned counterpart of [`", stringify!($options), "::",
+                    stringify!($setter), "`]."
+                )]
+                pub fn $name(mut self, $($arg: $ty),*) -> Self {
+                    self.0.$setter($($arg),*);
+                    self
+                }
+            )*
+            $($(
+                #[doc = concat!(
+                    "Owned counterpart of [`", stringify!($options), "::",
+                    stringify!($f_setter), "`]."
+                )]
+                pub fn $f_name(mut self, $($f_arg: $f_ty),*) -> Result<Self> {
+                    self.0.$f_setter($($f_arg),*)?;
+                    Ok(self)
+                }
+            )*)?
+        }
+    };
+}
+
+mod create;
+mod grid;
+mod mask;
+mod operation;
+mod reproject;
+mod reproject_options;
+mod resample;
+mod streaming;
+mod tiles;
+mod warp_options;
+
+pub use create::{
+    create_and_reproject, create_and_reproject_dataset, create_and_reproject_if_stale,
+    create_and_reproject_to_vsimem, create_and_reproject_with_overviews, reproject_stack_like,
+    reproject_to_cog, reproject_to_writer, ReprojectOutcome, WarpReport,
+};
+pub use grid::{suggested_warp_output, SuggestedWarpOutput};
+pub use mask::{create_and_reproject_with_footprint, create_and_reproject_with_mask};
+pub use reproject::{merge_into_mosaic, reproject, reproject_into};
+pub use reproject_options::{
+    BigTiffMode, Compression, CreateReprojectOptions, CreateReprojectOptionsBuilder, Interleave,
+    Photometric, Predictor, ReprojectIntoOptions, ReprojectIntoOptionsBuilder,
+};
+pub use resample::WarpResampleAlg;
+pub use streaming::{create_and_reproject_to_buffer, reproject_streaming, STREAMING_BLOCK_SIZE};
+pub use tiles::{create_and_reproject_pyramid, create_and_reproject_to_tile_matrix};
+pub use warp_options::{GdalWarpOptions, GdalWarpOptionsBuilder, InitDest, NumThreads};
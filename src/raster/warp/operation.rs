@@ -0,0 +1,516 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ptr::null_mut;
+use std::sync::Arc;
+
+use gdal_sys::{CPLErr, GDALDataType, GDALWarpOperationH};
+use libc::{c_int, c_void};
+
+use super::grid::transformer_to;
+use super::reproject::with_transformer_cache_setting;
+use super::warp_options::{call_custom_transformer, CustomTransformer};
+use crate::dataset::Dataset;
+use crate::errors::{GdalError, Result};
+use crate::raster::{ColorInterpretation, GdalDataType, GdalType, GdalWarpOptions};
+use crate::spatial_ref::SpatialRef;
+use crate::utils::{_last_cpl_err, _last_null_pointer_err};
+use crate::GeoTransform;
+
+/// A `GDALWarpOperation` warping a source onto a destination grid one region at a time,
+/// into buffers, so that the warped values can be processed before they are written (if at
+/// all), without staging the whole output in memory.
+///
+/// The operation and its transformer are set up once for the whole grid, and shared by all
+/// the regions. It is set up as `GDALReprojectImage` would: a last source band with the
+/// alpha color interpretation masks the others, and the no-data values of the source bands
+/// are honored unless others are set in the warp options.
+pub(crate) struct WarpOperation<'a> {
+    operation: GDALWarpOperationH,
+    // Used by `operation`, which is destroyed first, unless borrowed.
+    _transformer: Option<Transformer>,
+    band_count: usize,
+    working_type: GDALDataType::Type,
+    scratch: Vec<f64>,
+    // The source and destination, and the borrowed transformer, are used by `operation`.
+    _datasets: PhantomData<(&'a Dataset, &'a Transformer)>,
+}
+
+impl<'a> WarpOperation<'a> {
+    /// Set up the warp of `src` onto the grid of `gt` in `dst_srs`, with `warp_options`, whose
+    /// band mapping must be set up.
+    ///
+    /// If given, `dst` is the dataset on that grid the regions are going to be written to:
+    /// GDAL writes its alpha band itself, if the warp options have one. Unless the warp options
+    /// set one, the working data type is chosen as for destination bands of `buffer_type`, and
+    /// the destination is initialized to its no-data values, if any, or else to zero.
+    pub(crate) fn new(
+        src: &'a Dataset,
+        dst_srs: &SpatialRef,
+        gt: GeoTransform,
+        dst: Option<&'a Dataset>,
+        warp_options: &GdalWarpOptions,
+        max_error: f64,
+        buffer_type: GdalDataType,
+    ) -> Result<Self> {
+        let transformer = with_transformer_cache_setting(warp_options, || {
+            Transformer::new(src, dst_srs, gt, warp_options, max_error)
+        })??;
+        let mut operation = Self::build(src, &transformer, dst, warp_options, buffer_type)?;
+        operation._transformer = Some(transformer);
+        Ok(operation)
+    }
+
+    /// Same as [`new`](Self::new), warping onto the grid `transformer` is set up for, which is
+    /// used as is.
+    pub(crate) fn with_transformer(
+        src: &'a Dataset,
+        transformer: &'a Transformer,
+        dst: Option<&'a Dataset>,
+        warp_options: &GdalWarpOptions,
+        buffer_type: GdalDataType,
+    ) -> Result<Self> {
+        Self::build(src, transformer, dst, warp_options, buffer_type)
+    }
+
+    /// Sets up the operation with `transformer`, which must outlive it.
+    fn build(
+        src: &'a Dataset,
+        transformer: &Transformer,
+        dst: Option<&'a Dataset>,
+        warp_options: &GdalWarpOptions,
+        buffer_type: GdalDataType,
+    ) -> Result<Self> {
+        let band_count = warp_options.band_count();
+        if band_count == 0 {
+            return Err(GdalError::BadArgument(
+                "band mapping must be initialized before warping regions".into(),
+            ));
+        }
+
+        let mut options = warp_options.clone();
+        if options.warp_option("INIT_DEST").is_none() {
+            // Nothing is read back from the destination.
+            let has_dst_nodata = unsafe { !(*options.c_options()).padfDstNoDataReal.is_null() };
+            options.with_warp_option("INIT_DEST", if has_dst_nodata { "NO_DATA" } else { "0" })?;
+        }
+        let operation = unsafe {
+            let c_options = options.c_options();
+            init_as_reproject_image(c_options, src)?;
+            if (*c_options).eWorkingDataType == GDALDataType::GDT_Unknown {
+                (*c_options).eWorkingDataType =
+                    resolve_working_type(c_options, src, dst, buffer_type)?;
+            }
+            (*c_options).hSrcDS = src.c_dataset();
+            (*c_options).hDstDS = dst.map_or(null_mut(), |dst| dst.c_dataset());
+            (*c_options).pfnTransformer = transformer.func();
+            (*c_options).pTransformerArg = transformer.arg();
+            let operation = gdal_sys::GDALCreateWarpOperation(c_options);
+            (*c_options).hSrcDS = null_mut();
+            (*c_options).hDstDS = null_mut();
+            (*c_options).pTransformerArg = null_mut();
+            operation
+        };
+        if operation.is_null() {
+            return Err(_last_null_pointer_err("GDALCreateWarpOperation"));
+        }
+        Ok(WarpOperation {
+            operation,
+            _transformer: None,
+            band_count,
+            working_type: unsafe { (*options.c_options()).eWorkingDataType },
+            scratch: Vec::new(),
+            _datasets: PhantomData,
+        })
+    }
+
+    /// Warp the region of `size` (columns, rows) pixels at `offset` on the destination grid
+    /// into `data`, replacing its contents with the values of each warped band in turn,
+    /// converted to `T`. Only the window of the source the region covers is read.
+    pub(crate) fn warp_region<T: GdalType + Copy>(
+        &mut self,
+        offset: (usize, usize),
+        size: (usize, usize),
+        data: &mut Vec<T>,
+    ) -> Result<()> {
+        let len = size.0 * size.1 * self.band_count;
+        let word_size = unsafe { gdal_sys::GDALGetDataTypeSizeBytes(self.working_type) } as usize;
+        data.clear();
+        data.reserve(len);
+        let direct = T::gdal_ordinal() == self.working_type;
+        let buffer: *mut c_void = if direct {
+            data.as_mut_ptr().cast()
+        } else {
+            self.scratch.clear();
+            self.scratch.resize((len * word_size + 7) / 8, 0.0);
+            self.scratch.as_mut_ptr().cast()
+        };
+        // An empty source window has GDAL compute the one the region needs.
+        let rv = unsafe {
+            gdal_sys::GDALWarpRegionToBuffer(
+                self.operation,
+                offset.0 as c_int,
+                offset.1 as c_int,
+                size.0 as c_int,
+                size.1 as c_int,
+                buffer,
+                self.working_type,
+                0,
+                0,
+                0,
+                0,
+            )
+        };
+        if rv != CPLErr::CE_None {
+            return Err(_last_cpl_err(rv));
+        }
+        unsafe {
+            if !direct {
+                gdal_sys::GDALCopyWords(
+                    buffer,
+                    self.working_type,
+                    word_size as c_int,
+                    data.as_mut_ptr().cast(),
+                    T::gdal_ordinal(),
+                    size_of::<T>() as c_int,
+                    len as c_int,
+                );
+            }
+            // Every value has been initialized by the warp, or copied from its buffer.
+            data.set_len(len);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WarpOperation<'_> {
+    fn drop(&mut self) {
+        unsafe { gdal_sys::GDALDestroyWarpOperation(self.operation) };
+    }
+}
+
+/// Creates a warped VRT of `src` on `grid` (geotransform, columns and rows) in `dst_srs`, with
+/// `warp_options`, whose band mapping must be set up, as `GDALCreateWarpedVRT` does. The warp
+/// is set up as for a [`WarpOperation`], and the bands of the VRT are of `band_type`.
+///
+/// Returns `None` when the VRT can't be used: when warping in `band_type` could lose
+/// precision, because the source bands, their no-data values or the working data type of the
+/// warp options don't fit in it, or with a custom transformer, which the VRT can't own.
+pub(crate) fn create_warped_vrt(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    grid: (GeoTransform, usize, usize),
+    warp_options: &GdalWarpOptions,
+    max_error: f64,
+    band_type: GdalDataType,
+) -> Result<Option<Dataset>> {
+    if warp_options.has_custom_transformer() || warp_options.band_count() == 0 {
+        return Ok(None);
+    }
+    let options = warp_options.clone();
+    let c_options = unsafe { options.c_options() };
+    unsafe {
+        init_as_reproject_image(c_options, src)?;
+        let working_type = resolve_working_type(c_options, src, None, band_type)?;
+        let explicit_type = (*c_options).eWorkingDataType;
+        if working_type != band_type.gdal_ordinal()
+            || (explicit_type != GDALDataType::GDT_Unknown && explicit_type != working_type)
+        {
+            return Ok(None);
+        }
+        // The VRT bands are of the working data type.
+        (*c_options).eWorkingDataType = working_type;
+    }
+    let (mut gt, cols, rows) = grid;
+    let transformer = with_transformer_cache_setting(warp_options, || {
+        Transformer::new(src, dst_srs, gt, warp_options, max_error)
+    })??;
+    let (func, arg) = match transformer.into_gdal() {
+        Some(transformer) => transformer,
+        None => return Ok(None),
+    };
+    let vrt = unsafe {
+        (*c_options).hSrcDS = src.c_dataset();
+        (*c_options).pfnTransformer = func;
+        (*c_options).pTransformerArg = arg;
+        // The VRT references the source and owns the transformer.
+        let vrt = gdal_sys::GDALCreateWarpedVRT(
+            src.c_dataset(),
+            cols as c_int,
+            rows as c_int,
+            gt.as_mut_ptr(),
+            c_options,
+        );
+        (*c_options).hSrcDS = null_mut();
+        (*c_options).hDstDS = null_mut();
+        (*c_options).pTransformerArg = null_mut();
+        vrt
+    };
+    if vrt.is_null() {
+        // Whether the transformer has been destroyed with the VRT depends on how far GDAL got,
+        // so it's leaked rather than risking destroying it twice.
+        return Err(_last_null_pointer_err("GDALCreateWarpedVRT"));
+    }
+    Ok(Some(unsafe { Dataset::from_c_dataset(vrt) }))
+}
+
+/// The transformer of a [`WarpOperation`], between the pixels of its destination grid and
+/// the ones of its source.
+pub(crate) enum Transformer {
+    /// One of GDAL's, with its argument, to be destroyed with `GDALDestroyTransformer`, and the
+    /// `GDALGenImgProjTransform` transformer it is (or approximates, and owns).
+    Gdal(gdal_sys::GDALTransformerFunc, *mut c_void, *mut c_void),
+    /// The custom transformer of the warp options, boxed so that GDAL can be handed a thin
+    /// pointer to it.
+    Custom(Box<Arc<CustomTransformer>>),
+}
+
+impl Transformer {
+    /// The custom transformer of `warp_options` if any, otherwise a `GDALGenImgProjTransform`
+    /// transformer from the pixels of `src` to the ones of the grid of `gt` in `dst_srs`,
+    /// approximated within `max_error` pixels if positive.
+    pub(crate) fn new(
+        src: &Dataset,
+        dst_srs: &SpatialRef,
+        gt: GeoTransform,
+        warp_options: &GdalWarpOptions,
+        max_error: f64,
+    ) -> Result<Self> {
+        if let Some(transformer) = warp_options.custom_transformer() {
+            return Ok(Transformer::Custom(Box::new(Arc::clone(transformer))));
+        }
+        let transformer = transformer_to(src, dst_srs, warp_options)?;
+        unsafe { Self::from_gen_img_proj(transformer, gt, warp_options, max_error) }
+    }
+
+    /// Same as [`new`](Self::new), from the `GDALGenImgProjTransform` `transformer` created by
+    /// [`transformer_to`], which is taken ownership of (and destroyed right away if `warp_options`
+    /// have a custom transformer).
+    ///
+    /// # Safety
+    /// `transformer` must be valid, and not used afterwards but through the returned one.
+    pub(crate) unsafe fn from_gen_img_proj(
+        transformer: *mut c_void,
+        gt: GeoTransform,
+        warp_options: &GdalWarpOptions,
+        max_error: f64,
+    ) -> Result<Self> {
+        if let Some(custom) = warp_options.custom_transformer() {
+            gdal_sys::GDALDestroyGenImgProjTransformer(transformer);
+            return Ok(Transformer::Custom(Box::new(Arc::clone(custom))));
+        }
+        gdal_sys::GDALSetGenImgProjTransformerDstGeoTransform(transformer, gt.as_ptr());
+        if max_error <= 0.0 {
+            return Ok(Transformer::Gdal(
+                Some(gdal_sys::GDALGenImgProjTransform),
+                transformer,
+                transformer,
+            ));
+        }
+        let approx = gdal_sys::GDALCreateApproxTransformer(
+            Some(gdal_sys::GDALGenImgProjTransform),
+            transformer,
+            max_error,
+        );
+        if approx.is_null() {
+            gdal_sys::GDALDestroyGenImgProjTransformer(transformer);
+            return Err(_last_null_pointer_err("GDALCreateApproxTransformer"));
+        }
+        gdal_sys::GDALApproxTransformerOwnsSubtransformer(approx, 1);
+        Ok(Transformer::Gdal(
+            Some(gdal_sys::GDALApproxTransform),
+            approx,
+            transformer,
+        ))
+    }
+
+    /// Move the destination grid to the one of `gt`, as the coordinate transformation doesn't
+    /// depend on it. Custom transformers are left as they are.
+    pub(crate) fn set_dst_geo_transform(&mut self, gt: GeoTransform) {
+        if let Transformer::Gdal(_, _, gen_img_proj) = self {
+            unsafe {
+                gdal_sys::GDALSetGenImgProjTransformerDstGeoTransform(*gen_img_proj, gt.as_ptr())
+            };
+        }
+    }
+
+    /// Releases one of GDAL's transformers, to be destroyed by its new owner with
+    /// `GDALDestroyTransformer`. Custom transformers can't be, and are kept.
+    fn into_gdal(self) -> Option<(gdal_sys::GDALTransformerFunc, *mut c_void)> {
+        match self {
+            Transformer::Gdal(func, arg, _) => {
+                std::mem::forget(self);
+                Some((func, arg))
+            }
+            Transformer::Custom(_) => None,
+        }
+    }
+
+    fn func(&self) -> gdal_sys::GDALTransformerFunc {
+        match self {
+            Transformer::Gdal(func, _, _) => *func,
+            Transformer::Custom(_) => Some(call_custom_transformer),
+        }
+    }
+
+    fn arg(&self) -> *mut c_void {
+        match self {
+            Transformer::Gdal(_, arg, _) => *arg,
+            Transformer::Custom(transformer) => {
+                &**transformer as *const Arc<CustomTransformer> as *mut c_void
+            }
+        }
+    }
+}
+
+impl Drop for Transformer {
+    fn drop(&mut self) {
+        if let Transformer::Gdal(_, arg, _) = self {
+            unsafe { gdal_sys::GDALDestroyTransformer(*arg) };
+        }
+    }
+}
+
+/// Warps `src` into the whole of `dst` with `warp_options` and `transformer`, set up on the
+/// grid of `dst`, completing the options the way `GDALReprojectImage` does.
+pub(crate) fn warp_with(
+    src: &Dataset,
+    dst: &Dataset,
+    warp_options: &GdalWarpOptions,
+    transformer: &Transformer,
+) -> Result<()> {
+    let mut options = warp_options.clone();
+    options.init_default_band_mapping(src.raster_count());
+    unsafe {
+        init_as_reproject_image(options.c_options(), src)?;
+        options.warp_with_transformer(src, dst, transformer.func(), transformer.arg())
+    }
+}
+
+/// Completes `c_options` the way `GDALReprojectImage` does: the last band of `src` is used as
+/// source alpha band if it has the alpha color interpretation, and the no-data values of the
+/// warped source bands are used, unless the options set these already.
+///
+/// # Safety
+/// `c_options` must be valid, with its band mapping set up.
+unsafe fn init_as_reproject_image(
+    c_options: *mut gdal_sys::GDALWarpOptions,
+    src: &Dataset,
+) -> Result<()> {
+    let band_count = src.raster_count();
+    if (*c_options).nSrcAlphaBand == 0 && band_count > 0 {
+        let last_band = src.rasterband(band_count)?;
+        if last_band.color_interpretation() == ColorInterpretation::AlphaBand {
+            (*c_options).nSrcAlphaBand = band_count as c_int;
+        }
+    }
+    if (*c_options).padfSrcNoDataReal.is_null() {
+        let src_bands =
+            std::slice::from_raw_parts((*c_options).panSrcBands, (*c_options).nBandCount as usize);
+        for (i, &band) in src_bands.iter().enumerate() {
+            if let Some(nodata) = src.rasterband(band as usize)?.no_data_value() {
+                if (*c_options).padfSrcNoDataReal.is_null() {
+                    // The value GDAL uses for the bands without any.
+                    gdal_sys::GDALWarpInitSrcNoDataReal(c_options, -1.1e20);
+                }
+                *(*c_options).padfSrcNoDataReal.add(i) = nodata;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The working data type of a warp with `c_options` from `src` into `dst`, or into bands of
+/// `buffer_type` without `dst`, as `GDALWarpResolveWorkingDataType` would choose it: the
+/// smallest one able to hold the values of the warped bands and their no-data values.
+///
+/// # Safety
+/// `c_options` must be valid, with its band mapping set up.
+unsafe fn resolve_working_type(
+    c_options: *const gdal_sys::GDALWarpOptions,
+    src: &Dataset,
+    dst: Option<&Dataset>,
+    buffer_type: GdalDataType,
+) -> Result<GDALDataType::Type> {
+    let band_count = (*c_options).nBandCount as usize;
+    let band_type = |ds: &Dataset, band: c_int| -> Result<GDALDataType::Type> {
+        let band = ds.rasterband(band as usize)?;
+        Ok(gdal_sys::GDALGetRasterDataType(band.c_rasterband()))
+    };
+    let with_nodata = |data_type: GDALDataType::Type, nodata: *const f64, i: usize| {
+        if nodata.is_null() {
+            data_type
+        } else {
+            gdal_sys::GDALDataTypeUnionWithValue(data_type, *nodata.add(i), 0)
+        }
+    };
+    let mut working_type =
+        gdal_sys::GDALDataTypeUnion(GDALDataType::GDT_Byte, buffer_type.gdal_ordinal());
+    for i in 0..band_count {
+        let src_band = *(*c_options).panSrcBands.add(i);
+        working_type = gdal_sys::GDALDataTypeUnion(working_type, band_type(src, src_band)?);
+        if let Some(dst) = dst {
+            let dst_band = *(*c_options).panDstBands.add(i);
+            working_type = gdal_sys::GDALDataTypeUnion(working_type, band_type(dst, dst_band)?);
+        }
+        working_type = with_nodata(working_type, (*c_options).padfSrcNoDataReal, i);
+        working_type = with_nodata(working_type, (*c_options).padfDstNoDataReal, i);
+    }
+    Ok(working_type)
+}
+
+/// Runs `f` on the values of `data`, converted to `f64`, as GDAL converts pixel values, then
+/// converts them back.
+pub(crate) fn update_as_f64<T: GdalType + Copy>(data: &mut [T], f: impl FnOnce(&mut [f64])) {
+    let mut values = vec![0.0; data.len()];
+    unsafe {
+        gdal_sys::GDALCopyWords(
+            data.as_ptr().cast(),
+            T::gdal_ordinal(),
+            size_of::<T>() as c_int,
+            values.as_mut_ptr().cast(),
+            GDALDataType::GDT_Float64,
+            size_of::<f64>() as c_int,
+            data.len() as c_int,
+        );
+    }
+    f(&mut values);
+    unsafe {
+        gdal_sys::GDALCopyWords(
+            values.as_ptr().cast(),
+            GDALDataType::GDT_Float64,
+            size_of::<f64>() as c_int,
+            data.as_mut_ptr().cast(),
+            T::gdal_ordinal(),
+            size_of::<T>() as c_int,
+            data.len() as c_int,
+        );
+    }
+}
+
+/// Rounds `values` to values of `data_type`, clamped to its range, as GDAL converts pixel
+/// values.
+pub(crate) fn round_to_type(values: &mut [f64], data_type: GdalDataType) {
+    let word_size = data_type.bytes() as usize;
+    // Aligned for any data type.
+    let mut words = vec![0.0f64; (values.len() * word_size + 7) / 8];
+    unsafe {
+        gdal_sys::GDALCopyWords(
+            values.as_ptr().cast(),
+            GDALDataType::GDT_Float64,
+            size_of::<f64>() as c_int,
+            words.as_mut_ptr().cast(),
+            data_type.gdal_ordinal(),
+            word_size as c_int,
+            values.len() as c_int,
+        );
+        gdal_sys::GDALCopyWords(
+            words.as_ptr().cast(),
+            data_type.gdal_ordinal(),
+            word_size as c_int,
+            values.as_mut_ptr().cast(),
+            GDALDataType::GDT_Float64,
+            size_of::<f64>() as c_int,
+            values.len() as c_int,
+        );
+    }
+}
@@ -0,0 +1,1385 @@
+use std::ffi::{CStr, CString};
+use std::ptr::{null, null_mut};
+
+use gdal_sys::{self, CPLErr, GDALResampleAlg};
+
+use crate::config::{
+    clear_thread_local_config_option, get_thread_local_config_option,
+    set_thread_local_config_option,
+};
+use crate::cpl::CslStringList;
+use crate::dataset::Dataset;
+use crate::errors::*;
+use crate::raster::{GdalWarpOptions, ReprojectIntoOptions};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err};
+use crate::vector::Geometry;
+use crate::Metadata;
+
+/// Reproject `src` into the already existing `dst`, using bilinear resampling.
+///
+/// The spatial references and geotransforms of both datasets determine the transformation.
+/// Stale `RPC` metadata is removed from `dst`, as with [`reproject_into`].
+pub fn reproject(src: &Dataset, dst: &Dataset) -> Result<()> {
+    let rv = unsafe {
+        gdal_sys::GDALReprojectImage(
+            src.c_dataset(),
+            null(),
+            dst.c_dataset(),
+            null(),
+            GDALResampleAlg::GRA_Bilinear,
+            0.0,
+            0.0,
+            None,
+            null_mut(),
+            null_mut(),
+        )
+    };
+    if rv != CPLErr::CE_None {
+        return Err(_last_cpl_err(rv));
+    }
+    drop_stale_rpc(dst)
+}
+
+/// Reproject `src` into the already existing `dst`, using the settings in `options`.
+///
+/// Unless overridden in `options`, the spatial references of the datasets are used.
+///
+/// If `dst` has a geotransform, warping puts it on that grid, so any `RPC` metadata it
+/// carries (e.g. when created as a copy of an RPC-georeferenced `src`) is stale, and removed.
+///
+/// See [`GDALReprojectImage`](https://gdal.org/api/gdalwarp_cpp.html#_CPPv418GDALReprojectImage12GDALDatasetHPKc12GDALDatasetHPKc15GDALResampleAlgddP16GDALProgressFuncPvP15GDALWarpOptions).
+pub fn reproject_into(src: &Dataset, dst: &Dataset, options: &ReprojectIntoOptions) -> Result<()> {
+    let c_src_wkt = options
+        .src_srs()
+        .map(|srs| srs.to_wkt().and_then(|wkt| Ok(CString::new(wkt)?)))
+        .transpose()?;
+    let c_dst_wkt = options
+        .dst_srs()
+        .map(|srs| srs.to_wkt().and_then(|wkt| Ok(CString::new(wkt)?)))
+        .transpose()?;
+
+    let src_band_count = src.raster_count();
+    let mut warp_options = options.clone_and_init_warp_options(src, src_band_count)?;
+    if let Some(offset) = options.dst_band_offset() {
+        if offset + src_band_count > dst.raster_count() {
+            return Err(GdalError::BadArgument(format!(
+                "cannot write {src_band_count} band(s) after band {offset} of a dataset with {} band(s)",
+                dst.raster_count()
+            )));
+        }
+        if warp_options.premultiply_alpha() {
+            return Err(GdalError::BadArgument(
+                "premultiplying alpha is not supported with a destination band offset".into(),
+            ));
+        }
+        warp_options.init_default_band_mapping(src_band_count);
+        warp_options.offset_dst_bands(offset)?;
+    }
+    if warp_options.dst_alpha_band() {
+        warp_options.apply_dst_alpha(src_band_count, dst.raster_count())?;
+    }
+    let alpha_bands = if warp_options.premultiply_alpha() {
+        Some(warp_options.map_alpha_bands(src, dst)?)
+    } else {
+        None
+    };
+
+    reproject_image(
+        src,
+        c_src_wkt.as_deref(),
+        dst,
+        c_dst_wkt.as_deref(),
+        &warp_options,
+        options.max_error().unwrap_or(0.0),
+    )?;
+    if let Some((alpha_band, color_bands)) = alpha_bands {
+        let alpha_max = match warp_options.warp_option("DST_ALPHA_MAX") {
+            Some(max) => max.parse().map_err(|_| {
+                GdalError::BadArgument(format!("invalid DST_ALPHA_MAX warp option: {max}"))
+            })?,
+            None => 255.0,
+        };
+        premultiply_alpha(dst, alpha_band, &color_bands, alpha_max)?;
+    }
+    drop_stale_rpc(dst)
+}
+
+/// Warp `tile` into the existing `mosaic`, on its grid, blending the edges of the tile over
+/// `feather_pixels` tile pixels into the values already in the mosaic, to reduce seams when
+/// building a mosaic one tile at a time.
+///
+/// The weight of the tile goes from 0 on its outer edges to 1 at `feather_pixels` inside (see
+/// [`GdalWarpOptions::with_cutline_blend_dist`]); where the mosaic holds no data yet, i.e.
+/// the no-data value of its bands, the tile is written as is. `0` gives sharp edges, the tile
+/// overwriting the mosaic. The no-data values of the bands of the tile are honored as well.
+///
+/// The bands of the tile are written to the first bands of the mosaic, which must have as many
+/// at least, and the tile must have a geotransform.
+pub fn merge_into_mosaic(tile: &Dataset, mosaic: &mut Dataset, feather_pixels: f64) -> Result<()> {
+    if !(feather_pixels.is_finite() && feather_pixels >= 0.0) {
+        return Err(GdalError::BadArgument(format!(
+            "feathering distance must be finite and non-negative, got {feather_pixels}"
+        )));
+    }
+    let band_count = tile.raster_count();
+    if band_count > mosaic.raster_count() {
+        return Err(GdalError::BadArgument(format!(
+            "cannot merge a tile of {band_count} band(s) into a mosaic of {} band(s)",
+            mosaic.raster_count()
+        )));
+    }
+    let nodata = |ds: &Dataset| -> Result<Option<Vec<f64>>> {
+        (1..=band_count)
+            .map(|i| Ok(ds.rasterband(i)?.no_data_value()))
+            .collect::<Result<Option<Vec<_>>>>()
+    };
+    let mut options = ReprojectIntoOptions::new();
+    if let Some(nodata) = nodata(tile)? {
+        options.with_src_nodata_per_band(&nodata);
+    }
+    if let Some(nodata) = nodata(mosaic)? {
+        options.with_dst_nodata_per_band(&nodata);
+    }
+
+    if feather_pixels > 0.0 {
+        // Blending straddles the cutline, so it is set halfway through the feathered edges.
+        let inset = feather_pixels / 2.0;
+        let (cols, rows) = tile.raster_size();
+        let (cols, rows) = (cols as f64, rows as f64);
+        if 2.0 * inset >= cols || 2.0 * inset >= rows {
+            return Err(GdalError::BadArgument(format!(
+                "cannot feather the edges of a {cols}x{rows} tile over {feather_pixels} pixels"
+            )));
+        }
+        // In pixel/line coordinates of the tile.
+        let cutline = Geometry::from_wkt(&format!(
+            "POLYGON (({inset} {inset}, {right} {inset}, {right} {bottom}, {inset} {bottom}, \
+             {inset} {inset}))",
+            right = cols - inset,
+            bottom = rows - inset,
+        ))?;
+        options
+            .warp_options_mut()
+            .with_cutline(&cutline)?
+            .with_cutline_blend_dist(inset);
+    }
+    reproject_into(tile, mosaic, &options)
+}
+
+/// Remove the `RPC` metadata of `ds` if it has a geotransform, which takes precedence in
+/// warping, making the RPCs stale.
+fn drop_stale_rpc(ds: &Dataset) -> Result<()> {
+    if ds.metadata_domain("RPC").is_none() || ds.geo_transform().is_err() {
+        return Ok(());
+    }
+    let rv =
+        unsafe { gdal_sys::GDALSetMetadata(ds.c_dataset(), null_mut(), b"RPC\0".as_ptr().cast()) };
+    if rv != CPLErr::CE_None {
+        return Err(_last_cpl_err(rv));
+    }
+    Ok(())
+}
+
+/// Multiply the `color_bands` of `ds` by its `alpha_band`, over `alpha_max`, line by line.
+fn premultiply_alpha(
+    ds: &Dataset,
+    alpha_band: usize,
+    color_bands: &[usize],
+    alpha_max: f64,
+) -> Result<()> {
+    let (cols, rows) = ds.raster_size();
+    let alpha_band = ds.rasterband(alpha_band)?;
+    let mut color_bands = color_bands
+        .iter()
+        .map(|&i| ds.rasterband(i))
+        .collect::<Result<Vec<_>>>()?;
+    for row in 0..rows as isize {
+        let alpha = alpha_band.read_as::<f64>((0, row), (cols, 1), (cols, 1), None)?;
+        for band in &mut color_bands {
+            let mut line = band.read_as::<f64>((0, row), (cols, 1), (cols, 1), None)?;
+            for (value, a) in line.data.iter_mut().zip(&alpha.data) {
+                *value *= a / alpha_max;
+            }
+            band.write((0, row), (cols, 1), &line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `GDALReprojectImage`.
+pub(super) fn reproject_image(
+    src: &Dataset,
+    c_src_wkt: Option<&CStr>,
+    dst: &Dataset,
+    c_dst_wkt: Option<&CStr>,
+    warp_options: &GdalWarpOptions,
+    max_error: f64,
+) -> Result<()> {
+    if warp_options.has_custom_transformer() {
+        return with_transformer_cache_setting(warp_options, || {
+            warp_options.warp_with_custom_transformer(src, dst)
+        })?;
+    }
+    if has_transformer_settings(warp_options) {
+        return reproject_image_with_transformer(src, c_src_wkt, dst, c_dst_wkt, warp_options);
+    }
+    let rv = with_transformer_cache_setting(warp_options, || unsafe {
+        gdal_sys::GDALReprojectImage(
+            src.c_dataset(),
+            c_src_wkt.map_or(null(), |s| s.as_ptr()),
+            dst.c_dataset(),
+            c_dst_wkt.map_or(null(), |s| s.as_ptr()),
+            warp_options.resampling_alg().to_gdal(),
+            warp_options.memory_limit() as f64,
+            max_error,
+            None,
+            null_mut(),
+            warp_options.c_options(),
+        )
+    })?;
+    if rv != CPLErr::CE_None {
+        return Err(_last_cpl_err(rv));
+    }
+    Ok(())
+}
+
+/// Whether `warp_options` force a coordinate operation or set transformer options, which
+/// `GDALReprojectImage` and the other high-level warp functions can't take.
+pub(super) fn has_transformer_settings(warp_options: &GdalWarpOptions) -> bool {
+    warp_options.coordinate_operation().is_some() || !warp_options.transformer_options().is_empty()
+}
+
+/// Adds the coordinate operation and the transformer options of `warp_options` to
+/// `transformer_options`.
+pub(super) fn add_transformer_settings(
+    transformer_options: &mut CslStringList,
+    warp_options: &GdalWarpOptions,
+) -> Result<()> {
+    if let Some(operation) = warp_options.coordinate_operation() {
+        transformer_options.set_name_value("COORDINATE_OPERATION", operation)?;
+    }
+    for entry in warp_options.transformer_options().iter() {
+        transformer_options.add_entry(&entry)?;
+    }
+    Ok(())
+}
+
+/// Same as [`reproject_image`], with the coordinate operation forced or transformer options
+/// set, which requires setting up the transformer ourselves. The approximation error is ignored.
+fn reproject_image_with_transformer(
+    src: &Dataset,
+    c_src_wkt: Option<&CStr>,
+    dst: &Dataset,
+    c_dst_wkt: Option<&CStr>,
+    warp_options: &GdalWarpOptions,
+) -> Result<()> {
+    let mut transformer_options = CslStringList::new();
+    if let Some(wkt) = c_src_wkt {
+        transformer_options.set_name_value("SRC_SRS", &wkt.to_string_lossy())?;
+    }
+    if let Some(wkt) = c_dst_wkt {
+        transformer_options.set_name_value("DST_SRS", &wkt.to_string_lossy())?;
+    }
+    add_transformer_settings(&mut transformer_options, warp_options)?;
+    with_transformer_cache_setting(warp_options, || {
+        let transformer = unsafe {
+            gdal_sys::GDALCreateGenImgProjTransformer2(
+                src.c_dataset(),
+                dst.c_dataset(),
+                transformer_options.as_ptr(),
+            )
+        };
+        if transformer.is_null() {
+            return Err(_last_null_pointer_err("GDALCreateGenImgProjTransformer2"));
+        }
+        let rv = unsafe {
+            warp_options.warp_with_transformer(
+                src,
+                dst,
+                Some(gdal_sys::GDALGenImgProjTransform),
+                transformer,
+            )
+        };
+        unsafe { gdal_sys::GDALDestroyGenImgProjTransformer(transformer) };
+        rv
+    })?
+}
+
+const TRANSFORMER_CACHE_OPTION: &str = "OSR_CT_USE_CACHE";
+
+/// Runs `f` with the coordinate transformation cache enabled or not, as set in `warp_options`.
+///
+/// The cache is only disabled for the current thread, and the previous setting is restored
+/// afterwards, even if `f` panics. As every warp goes through here, `f` isn't run at all if
+/// the linked GDAL doesn't support the resampling algorithm.
+pub(super) fn with_transformer_cache_setting<T>(
+    warp_options: &GdalWarpOptions,
+    f: impl FnOnce() -> T,
+) -> Result<T> {
+    warp_options.resampling_alg().check_available()?;
+    if warp_options.transformer_cache() {
+        return Ok(f());
+    }
+    let _guard = TransformerCacheGuard::disable()?;
+    Ok(f())
+}
+
+/// Disables the coordinate transformation cache for the current thread while it lives.
+struct TransformerCacheGuard {
+    previous: String,
+}
+
+impl TransformerCacheGuard {
+    fn disable() -> Result<Self> {
+        let previous = get_thread_local_config_option(TRANSFORMER_CACHE_OPTION, "")?;
+        set_thread_local_config_option(TRANSFORMER_CACHE_OPTION, "NO")?;
+        Ok(TransformerCacheGuard { previous })
+    }
+}
+
+impl Drop for TransformerCacheGuard {
+    fn drop(&mut self) {
+        // Neither can fail, the key being a valid C string, and the previous value too.
+        let _ = if self.previous.is_empty() {
+            clear_thread_local_config_option(TRANSFORMER_CACHE_OPTION)
+        } else {
+            set_thread_local_config_option(TRANSFORMER_CACHE_OPTION, &self.previous)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::grid::suggested_grid;
+    use super::*;
+    use crate::raster::create_and_reproject;
+    use crate::raster::{Buffer, ColorInterpretation, CreateReprojectOptions, WarpResampleAlg};
+    use crate::spatial_ref::SpatialRef;
+    use crate::test_utils::{fixture, TempFixture};
+    use crate::DriverManager;
+    use gdal_sys::OSRAxisMappingStrategy;
+
+    #[test]
+    fn test_reproject_into() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let (cols, rows) = source.raster_size();
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut dest =
+            driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 1)?;
+        dest.set_spatial_ref(&source.spatial_ref()?)?;
+        dest.set_geo_transform(&source.geo_transform()?)?;
+
+        // Identical grids: a nearest-neighbour warp is a plain copy.
+        let opts = ReprojectIntoOptions::new();
+        reproject_into(&source, &dest, &opts)?;
+
+        let expected = source.rasterband(1)?.read_band_as::<u8>()?;
+        let actual = dest.rasterband(1)?.read_band_as::<u8>()?;
+        assert_eq!(actual.data, expected.data);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(major_ge_3, minor_ge_1))]
+    fn test_reproject_into_sum() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let srs = SpatialRef::from_epsg(32631)?;
+        let mut source = driver.create_with_band_type::<f32, _>("", 4, 4, 1)?;
+        source.set_geo_transform(&[500000.0, 10.0, 0.0, 4500000.0, 0.0, -10.0])?;
+        source.set_spatial_ref(&srs)?;
+        source
+            .rasterband(1)?
+            .write((0, 0), (4, 4), &Buffer::new((4, 4), vec![1.5f32; 16]))?;
+        let mut dest = driver.create_with_band_type::<f32, _>("", 2, 2, 1)?;
+        dest.set_geo_transform(&[500000.0, 20.0, 0.0, 4500000.0, 0.0, -20.0])?;
+        dest.set_spatial_ref(&srs)?;
+
+        // Downsampling by 2 sums 4 source pixels into each destination one.
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_resampling_alg(WarpResampleAlg::Sum);
+        reproject_into(&source, &dest, &opts)?;
+        let actual = dest.rasterband(1)?.read_band_as::<f32>()?;
+        assert_eq!(actual.data, vec![6.0; 4]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(major_ge_3, minor_ge_3))]
+    fn test_reproject_into_rms() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let srs = SpatialRef::from_epsg(32631)?;
+        let mut source = driver.create_with_band_type::<f32, _>("", 4, 4, 1)?;
+        source.set_geo_transform(&[500000.0, 10.0, 0.0, 4500000.0, 0.0, -10.0])?;
+        source.set_spatial_ref(&srs)?;
+        // A checkerboard of 1 and 3.
+        let values = (0..16)
+            .map(|i| if (i % 4 + i / 4) % 2 == 0 { 1.0 } else { 3.0 })
+            .collect::<Vec<f32>>();
+        source
+            .rasterband(1)?
+            .write((0, 0), (4, 4), &Buffer::new((4, 4), values))?;
+
+        let downsample = |alg: WarpResampleAlg| -> Result<Vec<f32>> {
+            let mut dest = driver.create_with_band_type::<f32, _>("", 2, 2, 1)?;
+            dest.set_geo_transform(&[500000.0, 20.0, 0.0, 4500000.0, 0.0, -20.0])?;
+            dest.set_spatial_ref(&srs)?;
+            let mut opts = ReprojectIntoOptions::new();
+            opts.warp_options_mut().with_resampling_alg(alg);
+            reproject_into(&source, &dest, &opts)?;
+            Ok(dest.rasterband(1)?.read_band_as::<f32>()?.data)
+        };
+        let average = downsample(WarpResampleAlg::Average)?;
+        let rms = downsample(WarpResampleAlg::RootMeanSquare)?;
+        for (average, rms) in average.into_iter().zip(rms) {
+            assert!((average - 2.0).abs() < 1e-5, "{average}");
+            assert!((rms - 5f32.sqrt()).abs() < 1e-5, "{rms}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_srs_overrides() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let expected_file = TempFixture::empty("labels_4326.tif");
+        create_and_reproject(
+            &source,
+            expected_file.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        let expected = Dataset::open(expected_file.path())?;
+
+        // Same grids, without any spatial reference on the datasets.
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let (cols, rows) = source.raster_size();
+        let mut bare_source =
+            driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 1)?;
+        bare_source.set_geo_transform(&source.geo_transform()?)?;
+        bare_source
+            .rasterband(1)?
+            .set_no_data_value(source.rasterband(1)?.no_data_value())?;
+        bare_source.rasterband(1)?.write(
+            (0, 0),
+            (cols, rows),
+            &source.rasterband(1)?.read_band_as::<u8>()?,
+        )?;
+        let (cols, rows) = expected.raster_size();
+        let mut dest =
+            driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 1)?;
+        dest.set_geo_transform(&expected.geo_transform()?)?;
+        dest.rasterband(1)?
+            .set_no_data_value(expected.rasterband(1)?.no_data_value())?;
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.with_src_srs(&source.spatial_ref()?)
+            .with_dst_srs(&dst_srs);
+        reproject_into(&bare_source, &dest, &opts)?;
+        assert!(dest.raster_equals(&expected, 0.0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_windows_transformer_cache_setting() -> Result<()> {
+        use crate::GeoTransformEx;
+
+        let source = Dataset::open(fixture("m_3607824_se_17_1_20160620_sub.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let (gt, (cols, rows)) = suggested_grid(&source, &dst_srs, &GdalWarpOptions::new())?;
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+
+        // Warp the source into a 10 x 10 grid of small windows, one at a time.
+        let (win_cols, win_rows) = (cols / 10, rows / 10);
+        let warp_windows = |opts: &ReprojectIntoOptions| -> Result<Vec<u8>> {
+            let mut values = Vec::new();
+            for i in 0..10 {
+                for j in 0..10 {
+                    let mut window = driver.create_with_band_type::<u8, _>(
+                        "",
+                        win_cols as isize,
+                        win_rows as isize,
+                        1,
+                    )?;
+                    let (x, y) = gt.apply((i * win_cols) as f64, (j * win_rows) as f64);
+                    window.set_geo_transform(&[x, gt[1], 0.0, y, 0.0, gt[5]])?;
+                    window.set_spatial_ref(&dst_srs)?;
+                    reproject_into(&source, &window, opts)?;
+                    let center = (win_cols / 2, win_rows / 2);
+                    let value = window.rasterband(1)?.read_as::<u8>(
+                        (center.0 as isize, center.1 as isize),
+                        (1, 1),
+                        (1, 1),
+                        None,
+                    )?;
+                    values.push(value.data[0]);
+                }
+            }
+            Ok(values)
+        };
+
+        // Whether GDAL reuses cached transformations isn't observable from here: this only
+        // checks that turning the cache off gives the same pixels, and restores the setting.
+        let mut uncached = ReprojectIntoOptions::new();
+        uncached.warp_options_mut().with_transformer_cache(false);
+        assert_eq!(
+            warp_windows(&uncached)?,
+            warp_windows(&ReprojectIntoOptions::new())?
+        );
+
+        // The cache is disabled while warping, and the process-wide setting is left alone.
+        let current_setting = || get_thread_local_config_option(TRANSFORMER_CACHE_OPTION, "unset");
+        let setting = with_transformer_cache_setting(uncached.warp_options(), current_setting)??;
+        assert_eq!(setting, "NO");
+        assert_eq!(current_setting()?, "unset");
+        let setting = with_transformer_cache_setting(&GdalWarpOptions::new(), current_setting)??;
+        assert_eq!(setting, "unset");
+
+        // Even if the warp panics.
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            with_transformer_cache_setting(uncached.warp_options(), || panic!("warp failed"))
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(current_setting()?, "unset");
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_cutline_from_layer() -> Result<()> {
+        use crate::vector::{FieldValue, Geometry, LayerAccess, LayerOptions, OGRFieldType};
+
+        let srs = SpatialRef::from_epsg(4326)?;
+        let gt = [0.0, 1.0, 0.0, 20.0, 0.0, -1.0];
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+        source.set_geo_transform(&gt)?;
+        source.set_spatial_ref(&srs)?;
+        source
+            .rasterband(1)?
+            .write((0, 0), (20, 20), &Buffer::new((20, 20), vec![1u8; 400]))?;
+
+        // Two squares to keep, and one to filter out.
+        let vector_driver = DriverManager::get_driver_by_name("Memory")?;
+        let mut cutlines = vector_driver.create_vector_only("")?;
+        let mut layer = cutlines.create_layer(LayerOptions {
+            name: "cutlines",
+            srs: Some(&srs),
+            ty: gdal_sys::OGRwkbGeometryType::wkbPolygon,
+            ..Default::default()
+        })?;
+        layer.create_defn_fields(&[("keep", OGRFieldType::OFTInteger)])?;
+        for (wkt, keep) in [
+            ("POLYGON ((0 20, 5 20, 5 15, 0 15, 0 20))", 1),
+            ("POLYGON ((15 5, 20 5, 20 0, 15 0, 15 5))", 1),
+            ("POLYGON ((15 20, 20 20, 20 15, 15 15, 15 20))", 0),
+        ] {
+            layer.create_feature_fields(
+                Geometry::from_wkt(wkt)?,
+                &["keep"],
+                &[FieldValue::IntegerValue(keep)],
+            )?;
+        }
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_cutline_from_layer(&mut layer, Some("keep = 1"))?;
+        assert_eq!(opts.warp_options().cutline().unwrap().geometry_count(), 2);
+        // The filter doesn't stick.
+        assert_eq!(layer.feature_count(), 3);
+
+        let mut dest = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+        dest.set_geo_transform(&gt)?;
+        dest.set_spatial_ref(&srs)?;
+        reproject_into(&source, &dest, &opts)?;
+
+        let result = dest.rasterband(1)?.read_band_as::<u8>()?;
+        let value = |col: usize, row: usize| result.data[row * 20 + col];
+        // Both kept squares contribute, and nothing else.
+        assert_eq!(value(2, 2), 1);
+        assert_eq!(value(17, 17), 1);
+        assert_eq!(value(17, 2), 0);
+        assert_eq!(value(10, 10), 0);
+        assert_eq!(result.data.iter().filter(|&&v| v == 1).count(), 50);
+
+        assert!(GdalWarpOptions::new()
+            .with_cutline_from_layer(&mut layer, Some("keep = 2"))
+            .is_err());
+        assert!(GdalWarpOptions::new()
+            .with_cutline(&Geometry::from_wkt("POINT (1 1)")?)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_cutline_src_srs_override() -> Result<()> {
+        let mut wgs84 = SpatialRef::from_epsg(4326)?;
+        wgs84.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+        let gt = [0.0, 1.0, 0.0, 20.0, 0.0, -1.0];
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        // Without a spatial reference of its own.
+        let mut source = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+        source.set_geo_transform(&gt)?;
+        source
+            .rasterband(1)?
+            .write((0, 0), (20, 20), &Buffer::new((20, 20), vec![1u8; 400]))?;
+
+        // The upper left square of the source, in Web Mercator.
+        let mut square = Geometry::from_wkt("POLYGON ((0 20, 5 20, 5 15, 0 15, 0 20))")?;
+        square.set_spatial_ref(wgs84.clone());
+        let square = square.transform_to(&SpatialRef::from_epsg(3857)?)?;
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.with_src_srs(&wgs84)
+            .warp_options_mut()
+            .with_cutline(&square)?;
+        let mut dest = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+        dest.set_geo_transform(&gt)?;
+        dest.set_spatial_ref(&wgs84)?;
+        reproject_into(&source, &dest, &opts)?;
+
+        // The cutline is reprojected to the overridden source spatial reference.
+        let result = dest.rasterband(1)?.read_band_as::<u8>()?;
+        let value = |col: usize, row: usize| result.data[row * 20 + col];
+        assert_eq!(value(2, 2), 1);
+        assert_eq!(value(10, 10), 0);
+        assert_eq!(value(17, 17), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_cutline_triangle() -> Result<()> {
+        use crate::vector::Geometry;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let srs = source.spatial_ref()?;
+        let gt = source.geo_transform()?;
+        let (cols, rows) = source.raster_size();
+        let (right, bottom) = (gt[0] + gt[1] * cols as f64, gt[3] + gt[5] * rows as f64);
+        // The upper left half of the source.
+        let triangle = Geometry::from_wkt(&format!(
+            "POLYGON (({left} {top}, {right} {top}, {left} {bottom}, {left} {top}))",
+            left = gt[0],
+            top = gt[3],
+        ))?;
+
+        let mut options = CreateReprojectOptions::new();
+        options.with_dst_nodata(255.0);
+        let dest = TempFixture::empty("labels_full.tif");
+        create_and_reproject(&source, dest.path(), &srs, &options)?;
+        let full = Dataset::open(dest.path())?
+            .rasterband(1)?
+            .read_band_as::<u8>()?;
+
+        options
+            .warp_options_mut()
+            .with_cutline(&triangle)?
+            .with_warp_option("INIT_DEST", "NO_DATA")?;
+        options.warp_options_mut().with_cutline_srs(&srs);
+        assert!(options.warp_options().cutline_srs().is_some());
+        let dest = TempFixture::empty("labels_triangle.tif");
+        create_and_reproject(&source, dest.path(), &srs, &options)?;
+        let out = Dataset::open(dest.path())?;
+        let clipped = out.rasterband(1)?.read_band_as::<u8>()?;
+
+        let check = |clipped: &Buffer<u8>| {
+            let (cols, rows) = clipped.size;
+            let mut inside = 0;
+            for (i, (&value, &expected)) in clipped.data.iter().zip(&full.data).enumerate() {
+                let (col, row) = ((i % cols) as f64 + 0.5, (i / cols) as f64 + 0.5);
+                let position = col / cols as f64 + row / rows as f64;
+                if position > 1.05 {
+                    assert_eq!(value, 255, "pixel {i} is outside of the cutline");
+                } else if position < 0.95 {
+                    assert_eq!(value, expected, "pixel {i} is inside of the cutline");
+                    inside += 1;
+                }
+            }
+            assert!(inside > 0);
+        };
+        assert_eq!(clipped.size, full.size);
+        check(&clipped);
+
+        // Same through `reproject_into`.
+        let mut into_options = ReprojectIntoOptions::new();
+        into_options
+            .with_warp_options(options.warp_options().clone())
+            .with_dst_nodata(255.0);
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let (out_cols, out_rows) = out.raster_size();
+        let mut into =
+            driver.create_with_band_type::<u8, _>("", out_cols as isize, out_rows as isize, 1)?;
+        into.set_geo_transform(&out.geo_transform()?)?;
+        into.set_spatial_ref(&srs)?;
+        reproject_into(&source, &into, &into_options)?;
+        check(&into.rasterband(1)?.read_band_as::<u8>()?);
+
+        // The same triangle, in source pixel/line coordinates.
+        let triangle = Geometry::from_wkt(&format!("POLYGON ((0 0, {cols} 0, 0 {rows}, 0 0))"))?;
+        let mut pixel_options = ReprojectIntoOptions::new();
+        pixel_options
+            .with_dst_nodata(255.0)
+            .warp_options_mut()
+            .with_cutline(&triangle)?
+            .with_warp_option("INIT_DEST", "NO_DATA")?;
+        let mut into =
+            driver.create_with_band_type::<u8, _>("", out_cols as isize, out_rows as isize, 1)?;
+        into.set_geo_transform(&out.geo_transform()?)?;
+        into.set_spatial_ref(&srs)?;
+        reproject_into(&source, &into, &pixel_options)?;
+        check(&into.rasterband(1)?.read_band_as::<u8>()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_cutline_blend() -> Result<()> {
+        use crate::vector::Geometry;
+
+        let srs = SpatialRef::from_epsg(4326)?;
+        let gt = [0.0, 1.0, 0.0, 20.0, 0.0, -1.0];
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let create = |value: u8| -> Result<Dataset> {
+            let mut ds = driver.create_with_band_type::<u8, _>("", 20, 20, 1)?;
+            ds.set_geo_transform(&gt)?;
+            ds.set_spatial_ref(&srs)?;
+            ds.rasterband(1)?
+                .write((0, 0), (20, 20), &Buffer::new((20, 20), vec![value; 400]))?;
+            Ok(ds)
+        };
+        let source = create(100)?;
+        // A square in the middle of the source, in pixel/line coordinates.
+        let square = Geometry::from_wkt("POLYGON ((5 5, 15 5, 15 15, 5 15, 5 5))")?;
+        let warp = |blend_dist: f64| -> Result<Vec<u8>> {
+            let mut options = ReprojectIntoOptions::new();
+            options
+                .warp_options_mut()
+                .with_cutline(&square)?
+                .with_cutline_blend_dist(blend_dist);
+            let dest = create(0)?;
+            reproject_into(&source, &dest, &options)?;
+            // The middle row.
+            Ok(dest.rasterband(1)?.read_band_as::<u8>()?.data[10 * 20..11 * 20].to_vec())
+        };
+
+        let sharp = warp(0.0)?;
+        assert_eq!(sharp[4], 0);
+        assert!(sharp[5..15].iter().all(|&v| v == 100), "{sharp:?}");
+        assert_eq!(sharp[15], 0);
+
+        // The source is blended into the destination on both sides of the edges.
+        let blended = warp(4.0)?;
+        assert_eq!(blended[0], 0);
+        assert!(blended[4] > 0 && blended[4] < 100, "{blended:?}");
+        assert!(blended[5] > blended[4] && blended[5] < 100, "{blended:?}");
+        assert_eq!(blended[10], 100);
+        assert!(blended[15] > 0 && blended[15] < 100, "{blended:?}");
+        assert_eq!(blended[19], 0);
+
+        let mut warp_options = GdalWarpOptions::new();
+        assert_eq!(warp_options.cutline_blend_dist(), 0.0);
+        warp_options.with_cutline_blend_dist(4.0);
+        assert_eq!(warp_options.clone().cutline_blend_dist(), 4.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_complex_nodata() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let create = || -> Result<Dataset> {
+            let c_dataset = unsafe {
+                gdal_sys::GDALCreate(
+                    driver.c_driver(),
+                    b"\0".as_ptr().cast(),
+                    4,
+                    1,
+                    1,
+                    gdal_sys::GDALDataType::GDT_CFloat32,
+                    null_mut(),
+                )
+            };
+            assert!(!c_dataset.is_null());
+            let mut ds = unsafe { Dataset::from_c_dataset(c_dataset) };
+            ds.set_geo_transform(&[0.0, 1.0, 0.0, 1.0, 0.0, -1.0])?;
+            ds.set_spatial_ref(&SpatialRef::from_epsg(32631)?)?;
+            Ok(ds)
+        };
+        let complex_io = |ds: &Dataset, rw_flag, values: &mut [f32; 8]| unsafe {
+            let band = ds.rasterband(1).unwrap();
+            let rv = gdal_sys::GDALRasterIO(
+                band.c_rasterband(),
+                rw_flag,
+                0,
+                0,
+                4,
+                1,
+                values.as_mut_ptr().cast(),
+                4,
+                1,
+                gdal_sys::GDALDataType::GDT_CFloat32,
+                0,
+                0,
+            );
+            assert_eq!(rv, CPLErr::CE_None);
+        };
+
+        // Only the pixel matching both components is no-data.
+        let source = create()?;
+        let mut values = [5.0, 7.0, 5.0, 0.0, 0.0, 7.0, 1.0, 1.0];
+        complex_io(&source, gdal_sys::GDALRWFlag::GF_Write, &mut values);
+        let dest = create()?;
+        let mut opts = ReprojectIntoOptions::new();
+        let warp_options = opts.warp_options_mut();
+        warp_options.init_default_band_mapping(1);
+        warp_options.apply_src_nodata_complex(1, 5.0, 7.0)?;
+        warp_options.with_warp_option("INIT_DEST", "0")?;
+        reproject_into(&source, &dest, &opts)?;
+
+        let mut values = [-1.0; 8];
+        complex_io(&dest, gdal_sys::GDALRWFlag::GF_Read, &mut values);
+        assert_eq!(values, [0.0, 0.0, 5.0, 0.0, 0.0, 7.0, 1.0, 1.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_init_dest() -> Result<()> {
+        use crate::raster::InitDest;
+
+        let source = Dataset::open(fixture("tinymarble.tif"))?;
+        let (cols, rows) = source.raster_size();
+        // Twice as large as the source, on the same grid.
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut dest =
+            driver.create_with_band_type::<u8, _>("", 2 * cols as isize, 2 * rows as isize, 3)?;
+        dest.set_spatial_ref(&source.spatial_ref()?)?;
+        dest.set_geo_transform(&source.geo_transform()?)?;
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_init_dest(InitDest::Values(vec![7.0, 8.0, 9.0]))?;
+        assert_eq!(
+            opts.warp_options().warp_option("INIT_DEST").as_deref(),
+            Some("7,8,9")
+        );
+        assert_eq!(
+            opts.warp_options().init_dest(),
+            Some(InitDest::Values(vec![7.0, 8.0, 9.0]))
+        );
+        reproject_into(&source, &dest, &opts)?;
+
+        for (i, init) in [(1, 7u8), (2, 8), (3, 9)] {
+            let warped = dest.rasterband(i)?.read_band_as::<u8>()?;
+            let expected = source.rasterband(i)?.read_band_as::<u8>()?;
+            assert_eq!(warped.data[0], expected.data[0]);
+            assert_eq!(*warped.data.last().unwrap(), init);
+            assert_eq!(warped.data[2 * cols - 1], init);
+        }
+
+        let mut warp_options = GdalWarpOptions::new();
+        warp_options.with_init_dest(InitDest::NoData)?;
+        assert_eq!(warp_options.init_dest(), Some(InitDest::NoData));
+        assert!(warp_options
+            .with_init_dest(InitDest::Values(Vec::new()))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_band_offset() -> Result<()> {
+        let source = Dataset::open(fixture("tinymarble.tif"))?;
+        let (cols, rows) = source.raster_size();
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut dest =
+            driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 6)?;
+        dest.set_spatial_ref(&source.spatial_ref()?)?;
+        dest.set_geo_transform(&source.geo_transform()?)?;
+        let sevens = Buffer::new((cols, rows), vec![7u8; cols * rows]);
+        for i in 1..=3 {
+            dest.rasterband(i)?.write((0, 0), (cols, rows), &sevens)?;
+        }
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.with_dst_band_offset(3);
+        reproject_into(&source, &dest, &opts)?;
+
+        for i in 1..=3 {
+            let untouched = dest.rasterband(i)?.read_band_as::<u8>()?;
+            assert!(untouched.data.iter().all(|&v| v == 7));
+
+            let expected = source.rasterband(i)?.read_band_as::<u8>()?;
+            let actual = dest.rasterband(i + 3)?.read_band_as::<u8>()?;
+            assert_eq!(actual.data, expected.data);
+        }
+
+        // Not enough destination bands.
+        opts.with_dst_band_offset(4);
+        assert!(reproject_into(&source, &dest, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_premultiply_alpha() -> Result<()> {
+        let rgba = |size: isize| -> Result<Dataset> {
+            let driver = DriverManager::get_driver_by_name("MEM")?;
+            let mut ds = driver.create_with_band_type::<u8, _>("", size, size, 4)?;
+            ds.set_geo_transform(&[0.0, 8.0 / size as f64, 0.0, 8.0, 0.0, -8.0 / size as f64])?;
+            ds.set_spatial_ref(&SpatialRef::from_epsg(3857)?)?;
+            ds.rasterband(4)?
+                .set_color_interpretation(ColorInterpretation::AlphaBand)?;
+            Ok(ds)
+        };
+        // Opaque white on the 5 left columns, transparent black elsewhere.
+        let source = rgba(8)?;
+        let row = [255u8, 255, 255, 255, 255, 0, 0, 0];
+        let pixels = Buffer::new((8, 8), row.repeat(8));
+        for i in 1..=4 {
+            source.rasterband(i)?.write((0, 0), (8, 8), &pixels)?;
+        }
+        // Half the resolution, so that the third column straddles the edge.
+        let dest = rgba(4)?;
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_resampling_alg(WarpResampleAlg::Bilinear)
+            .with_premultiply_alpha(true);
+        reproject_into(&source, &dest, &opts)?;
+
+        let alpha = dest.rasterband(4)?.read_band_as::<u8>()?;
+        assert!(alpha.data.iter().any(|&a| a > 0 && a < 255));
+        for i in 1..=3 {
+            let color = dest.rasterband(i)?.read_band_as::<u8>()?;
+            for (&c, &a) in color.data.iter().zip(&alpha.data) {
+                // Premultiplied, and white once composited over white: no dark fringe.
+                assert!(c <= a, "color {c} above alpha {a}");
+                let over_white = c as f64 + 255.0 * (1.0 - a as f64 / 255.0);
+                assert!(over_white >= 254.0, "color {c}, alpha {a}");
+            }
+        }
+
+        // No alpha band to premultiply with.
+        let plain = DriverManager::get_driver_by_name("MEM")?
+            .create_with_band_type::<u8, _>("", 4, 4, 3)?;
+        assert!(reproject_into(&source, &plain, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_nan_nodata() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let create = |x_origin: f64| -> Result<Dataset> {
+            let mut ds = driver.create_with_band_type::<f32, _>("", 4, 4, 1)?;
+            ds.set_geo_transform(&[x_origin, 1.0, 0.0, 4.0, 0.0, -1.0])?;
+            ds.set_spatial_ref(&SpatialRef::from_epsg(32631)?)?;
+            Ok(ds)
+        };
+        // NaN on the two left columns, 1 elsewhere.
+        let source = create(0.0)?;
+        let row = [f32::NAN, f32::NAN, 1.0, 1.0];
+        source
+            .rasterband(1)?
+            .write((0, 0), (4, 4), &Buffer::new((4, 4), row.repeat(4)))?;
+        // Shifted by half a pixel, so that every output pixel blends two source columns.
+        let dest = create(0.5)?;
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.with_src_nodata(f64::NAN)
+            .with_dst_nodata(f64::NAN)
+            .warp_options_mut()
+            .with_resampling_alg(WarpResampleAlg::Bilinear);
+        reproject_into(&source, &dest, &opts)?;
+
+        let warped = dest.rasterband(1)?.read_band_as::<f32>()?;
+        for row in warped.data.chunks(4) {
+            assert!(row[0].is_nan(), "{row:?}");
+            // Next to the NaN columns, which aren't blended in.
+            assert_eq!(row[1..3], [1.0, 1.0], "{row:?}");
+        }
+
+        let bytes = driver.create_with_band_type::<u8, _>("", 4, 4, 1)?;
+        assert!(reproject_into(&bytes, &dest, &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_custom_transformer() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let (cols, rows) = source.raster_size();
+        let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+        let expected = source
+            .create_copy(&mem_driver, "", &[])?
+            .rasterband(1)?
+            .read_band_as::<u8>()?;
+        let create =
+            || mem_driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 1);
+
+        // No georeferencing is needed with a custom transformer.
+        let identity = create()?;
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_custom_transformer(|x, _, _| vec![true; x.len()]);
+        assert!(opts.warp_options().has_custom_transformer());
+        reproject_into(&source, &identity, &opts)?;
+        assert_eq!(
+            identity.rasterband(1)?.read_band_as::<u8>()?.data,
+            expected.data
+        );
+
+        // Reading one column to the right.
+        let shifted = create()?;
+        opts.warp_options_mut().with_custom_transformer(|x, _, _| {
+            x.iter_mut().for_each(|x| *x += 1.0);
+            vec![true; x.len()]
+        });
+        reproject_into(&source, &shifted, &opts)?;
+        let shifted = shifted.rasterband(1)?.read_band_as::<u8>()?.data;
+        for (row, expected) in shifted.chunks(cols).zip(expected.data.chunks(cols)) {
+            assert_eq!(row[..cols - 1], expected[1..]);
+        }
+
+        // Set up like any other warp, e.g. with the transformer cache setting applied.
+        opts.warp_options_mut()
+            .with_transformer_cache(false)
+            .with_custom_transformer(|x, _, _| {
+                let setting = get_thread_local_config_option(TRANSFORMER_CACHE_OPTION, "");
+                vec![setting.map_or(false, |setting| setting == "NO"); x.len()]
+            });
+        let uncached = create()?;
+        reproject_into(&source, &uncached, &opts)?;
+        assert_eq!(
+            uncached.rasterband(1)?.read_band_as::<u8>()?.data,
+            expected.data
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_warp_chunk_size() -> Result<()> {
+        use crate::test_utils::warp_chunks;
+
+        let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+        let source = mem_driver.create_with_band_type::<u8, _>("", 512, 512, 1)?;
+        let dest = mem_driver.create_with_band_type::<u8, _>("", 512, 512, 1)?;
+        let chunks = |chunk_size: usize| {
+            let mut opts = ReprojectIntoOptions::new();
+            opts.warp_options_mut()
+                .with_warp_chunk_size(chunk_size, chunk_size)?;
+            assert_eq!(
+                opts.warp_options().warp_chunk_size(),
+                Some((chunk_size, chunk_size))
+            );
+            warp_chunks(&source, &dest, &mut opts)
+        };
+        let small_chunks = chunks(64)?;
+        assert!(small_chunks.len() >= 64, "{small_chunks:?}");
+        for &(_, _, x_size, y_size) in &small_chunks {
+            assert!(x_size <= 64 && y_size <= 64, "{small_chunks:?}");
+        }
+        let large_chunks = chunks(512)?;
+        assert!(
+            large_chunks.len() < small_chunks.len(),
+            "{large_chunks:?} vs {small_chunks:?}"
+        );
+
+        // The chunk size replaces the memory limit, so both can't be set.
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_memory_limit(1 << 20)
+            .with_warp_chunk_size(64, 64)?;
+        assert!(reproject_into(&source, &dest, &opts).is_err());
+
+        assert!(GdalWarpOptions::new().with_warp_chunk_size(0, 64).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_drops_stale_rpc() -> Result<()> {
+        use crate::vector::Geometry;
+        use crate::GeoTransformEx;
+
+        // 100x100 image over 4.5°..5.5°E, 44.5°..45.5°N, north up, georeferenced by RPCs only.
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 100, 100, 1)?;
+        source.rasterband(1)?.write(
+            (0, 0),
+            (100, 100),
+            &Buffer::new((100, 100), vec![1; 10_000]),
+        )?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        let coefficients = |i: usize, value: i32| {
+            let mut coefficients = vec!["0"; 20];
+            let value = value.to_string();
+            coefficients[i] = &value;
+            coefficients.join(" ")
+        };
+        for (key, value) in [
+            ("LINE_OFF", "50".to_owned()),
+            ("SAMP_OFF", "50".to_owned()),
+            ("LAT_OFF", "45".to_owned()),
+            ("LONG_OFF", "5".to_owned()),
+            ("HEIGHT_OFF", "0".to_owned()),
+            ("LINE_SCALE", "50".to_owned()),
+            ("SAMP_SCALE", "50".to_owned()),
+            ("LAT_SCALE", "0.5".to_owned()),
+            ("LONG_SCALE", "0.5".to_owned()),
+            ("HEIGHT_SCALE", "1".to_owned()),
+            // Coefficients of (1, L, P, H, ...), with L the longitude and P the latitude.
+            ("LINE_NUM_COEFF", coefficients(2, -1)),
+            ("LINE_DEN_COEFF", coefficients(0, 1)),
+            ("SAMP_NUM_COEFF", coefficients(1, 1)),
+            ("SAMP_DEN_COEFF", coefficients(0, 1)),
+        ] {
+            source.set_metadata_item(key, &value, "RPC")?;
+        }
+        let dst_srs = SpatialRef::from_epsg(32631)?;
+
+        let dest = TempFixture::empty("rpc_proj.tif");
+        let report = create_and_reproject(
+            &source,
+            dest.path(),
+            &dst_srs,
+            &CreateReprojectOptions::new(),
+        )?;
+        let out = Dataset::open(dest.path())?;
+        assert_eq!(out.geo_transform()?, report.geo_transform);
+        assert!(out.metadata_domain("RPC").is_none());
+        // Centered on 5°E, 45°N.
+        let (x, y) = report
+            .geo_transform
+            .apply(report.size.0 as f64 / 2.0, report.size.1 as f64 / 2.0);
+        let mut center = Geometry::from_wkt(&format!("POINT ({x} {y})"))?;
+        center.set_spatial_ref(dst_srs.clone());
+        let mut wgs84 = SpatialRef::from_epsg(4326)?;
+        wgs84.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+        center.transform_to_inplace(&wgs84)?;
+        let (lon, lat, _) = center.get_point(0);
+        assert!(
+            (lon - 5.0).abs() < 0.01 && (lat - 45.0).abs() < 0.01,
+            "{lon} {lat}"
+        );
+
+        // A destination carrying the RPCs of the source, e.g. created from a copy of it.
+        let dst = {
+            let (cols, rows) = (report.size.0 as isize, report.size.1 as isize);
+            let mut dst = driver.create_with_band_type::<u8, _>("", cols, rows, 1)?;
+            for (key, value) in source
+                .metadata_domain("RPC")
+                .into_iter()
+                .flatten()
+                .filter_map(|kv| {
+                    kv.split_once('=')
+                        .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                })
+            {
+                dst.set_metadata_item(&key, &value, "RPC")?;
+            }
+            dst.set_geo_transform(&report.geo_transform)?;
+            dst.set_spatial_ref(&dst_srs)?;
+            dst
+        };
+        assert!(dst.metadata_domain("RPC").is_some());
+        reproject_into(&source, &dst, &ReprojectIntoOptions::new())?;
+        assert!(dst.metadata_domain("RPC").is_none());
+        assert_eq!(
+            dst.rasterband(1)?.read_band_as::<u8>()?.data,
+            out.rasterband(1)?.read_band_as::<u8>()?.data
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_into_mosaic() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let srs = SpatialRef::from_epsg(32631)?;
+        let create = |x: f64, cols: usize, value: f32| -> Result<Dataset> {
+            let mut ds = driver.create_with_band_type::<f32, _>("", cols as isize, 10, 1)?;
+            ds.set_geo_transform(&[x, 10.0, 0.0, 4500000.0, 0.0, -10.0])?;
+            ds.set_spatial_ref(&srs)?;
+            ds.rasterband(1)?.write(
+                (0, 0),
+                (cols, 10),
+                &Buffer::new((cols, 10), vec![value; cols * 10]),
+            )?;
+            Ok(ds)
+        };
+        // An empty 20x10 mosaic, and two 12x10 tiles overlapping over its 4 middle columns.
+        let mut mosaic = create(500000.0, 20, -1.0)?;
+        mosaic.rasterband(1)?.set_no_data_value(Some(-1.0))?;
+        let left = create(500000.0, 12, 0.0)?;
+        let right = create(500080.0, 12, 100.0)?;
+
+        merge_into_mosaic(&left, &mut mosaic, 4.0)?;
+        merge_into_mosaic(&right, &mut mosaic, 4.0)?;
+        let row = mosaic
+            .rasterband(1)?
+            .read_as::<f32>((0, 5), (20, 1), (20, 1), None)?
+            .data;
+        assert!(row[..8].iter().all(|&v| v == 0.0), "{row:?}");
+        assert!(
+            row[12..].iter().all(|&v| (v - 100.0).abs() < 1e-3),
+            "{row:?}"
+        );
+        // The right tile fades in over the overlap.
+        let overlap = &row[8..12];
+        assert!(overlap.iter().all(|&v| v > 0.0 && v < 100.0), "{row:?}");
+        assert!(overlap.windows(2).all(|pair| pair[0] < pair[1]), "{row:?}");
+
+        // Without feathering, the right tile overwrites the overlap.
+        merge_into_mosaic(&right, &mut mosaic, 0.0)?;
+        let row = mosaic
+            .rasterband(1)?
+            .read_as::<f32>((0, 5), (20, 1), (20, 1), None)?
+            .data;
+        assert!(row[8..].iter().all(|&v| v == 100.0), "{row:?}");
+
+        assert!(merge_into_mosaic(&right, &mut mosaic, 12.0).is_err());
+        assert!(merge_into_mosaic(&right, &mut mosaic, -1.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_into_coordinate_operation() -> Result<()> {
+        // 50x50 pixels of about a meter in Kansas, in NAD27.
+        let gt = [-98.0, 1e-5, 0.0, 39.0, 0.0, -1e-5];
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 50, 50, 1)?;
+        source.set_geo_transform(&gt)?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4267)?)?;
+        let values = (0..2500).map(|i| (i % 251) as u8).collect();
+        source
+            .rasterband(1)?
+            .write((0, 0), (50, 50), &Buffer::new((50, 50), values))?;
+
+        let warp = |opts: &ReprojectIntoOptions| -> Result<Vec<u8>> {
+            let mut dst = driver.create_with_band_type::<u8, _>("", 50, 50, 1)?;
+            dst.set_geo_transform(&gt)?;
+            dst.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+            reproject_into(&source, &dst, opts)?;
+            Ok(dst.rasterband(1)?.read_band_as::<u8>()?.data)
+        };
+        let default = warp(&ReprojectIntoOptions::new())?;
+
+        // NAD27 to WGS 84 (3), for Canada, which isn't the default here.
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_coordinate_operation("EPSG:1172");
+        assert_eq!(
+            opts.warp_options().coordinate_operation(),
+            Some("urn:ogc:def:coordinateOperation:EPSG::1172")
+        );
+        let forced = warp(&opts)?;
+        assert_ne!(forced, default);
+
+        // Same as the parameters of the operation, set explicitly.
+        let mut opts = ReprojectIntoOptions::new();
+        opts.with_src_srs(&SpatialRef::from_proj4(
+            "+proj=longlat +ellps=clrk66 +towgs84=-10,158,187,0,0,0,0 +no_defs",
+        )?);
+        assert_eq!(warp(&opts)?, forced);
+
+        // Same as the operation, as a PROJ pipeline: a Helmert transformation between the
+        // ellipsoids, on latitudes and longitudes.
+        let pipeline = "+proj=pipeline \
+            +step +proj=axisswap +order=2,1 \
+            +step +proj=unitconvert +xy_in=deg +xy_out=rad \
+            +step +proj=push +v_3 \
+            +step +proj=cart +ellps=clrk66 \
+            +step +proj=helmert +x=-10 +y=158 +z=187 \
+            +step +inv +proj=cart +ellps=WGS84 \
+            +step +proj=pop +v_3 \
+            +step +proj=unitconvert +xy_in=rad +xy_out=deg \
+            +step +proj=axisswap +order=2,1";
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut().with_coordinate_operation(pipeline);
+        assert_eq!(opts.warp_options().coordinate_operation(), Some(pipeline));
+        assert_eq!(warp(&opts)?, forced);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(not(all(major_ge_3, minor_ge_6)), ignore)]
+    fn test_reproject_into_vertical_shift() -> Result<()> {
+        // A flat DEM at 100 m, on a 10x10 grid of 100 m pixels.
+        let gt = [500000.0, 100.0, 0.0, 4500000.0, 0.0, -100.0];
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<f32, _>("", 10, 10, 1)?;
+        source.set_geo_transform(&gt)?;
+        // UTM 31N with EGM96 heights.
+        source.set_spatial_ref(&SpatialRef::from_definition("EPSG:32631+5773")?)?;
+        source
+            .rasterband(1)?
+            .write((0, 0), (10, 10), &Buffer::new((10, 10), vec![100f32; 100]))?;
+
+        let warp = |opts: &ReprojectIntoOptions| -> Result<Vec<f32>> {
+            let mut dst = driver.create_with_band_type::<f32, _>("", 10, 10, 1)?;
+            dst.set_geo_transform(&gt)?;
+            // UTM 31N with EGM2008 heights.
+            dst.set_spatial_ref(&SpatialRef::from_definition("EPSG:32631+3855")?)?;
+            reproject_into(&source, &dst, opts)?;
+            Ok(dst.rasterband(1)?.read_band_as::<f32>()?.data)
+        };
+        // Between the two datums, a constant offset of 12.5 m stands in for the geoid grids,
+        // which aren't available here.
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut()
+            .with_coordinate_operation("+proj=pipeline +step +proj=affine +zoff=12.5")
+            .with_vertical_shift(true)?;
+        assert!(opts.warp_options().vertical_shift());
+        assert_eq!(
+            opts.warp_options().transformer_option("STRIP_VERT_CS"),
+            Some("NO".into())
+        );
+        assert_eq!(warp(&opts)?, vec![112.5f32; 100]);
+
+        opts.warp_options_mut().with_vertical_shift(false)?;
+        assert!(!opts.warp_options().vertical_shift());
+        assert_eq!(warp(&opts)?, vec![100f32; 100]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_num_threads() -> Result<()> {
+        use crate::raster::NumThreads;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut options = CreateReprojectOptions::new();
+        options
+            .warp_options_mut()
+            .with_resampling_alg(WarpResampleAlg::Bilinear);
+        let single = TempFixture::empty("labels_single.tif");
+        create_and_reproject(&source, single.path(), &dst_srs, &options)?;
+        let single = Dataset::open(single.path())?;
+
+        options
+            .warp_options_mut()
+            .with_num_threads(NumThreads::AllCpus)?;
+        assert_eq!(
+            options.warp_options().num_threads(),
+            Some(NumThreads::AllCpus)
+        );
+        let multi = TempFixture::empty("labels_multi.tif");
+        create_and_reproject(&source, multi.path(), &dst_srs, &options)?;
+        assert!(Dataset::open(multi.path())?.raster_equals(&single, 0.0)?);
+
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let (cols, rows) = single.raster_size();
+        let mut into =
+            driver.create_with_band_type::<u8, _>("", cols as isize, rows as isize, 1)?;
+        into.set_geo_transform(&single.geo_transform()?)?;
+        into.set_spatial_ref(&dst_srs)?;
+        let mut into_options = ReprojectIntoOptions::new();
+        into_options.with_warp_options(options.warp_options().clone());
+        into_options
+            .warp_options_mut()
+            .with_num_threads(NumThreads::Count(3))?;
+        assert_eq!(
+            into_options.warp_options().num_threads(),
+            Some(NumThreads::Count(3))
+        );
+        reproject_into(&source, &into, &into_options)?;
+        assert!(into.raster_equals(&single, 0.0)?);
+
+        assert!(GdalWarpOptions::new()
+            .with_num_threads(NumThreads::Count(0))
+            .is_err());
+        Ok(())
+    }
+}
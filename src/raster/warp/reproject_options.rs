@@ -0,0 +1,1415 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::errors::{GdalError, Result};
+use crate::raster::{AdjustedValue, ColorInterpretation, GdalDataType, GdalType, GdalWarpOptions};
+use crate::spatial_ref::SpatialRef;
+use crate::{Dataset, GeoTransformEx, Metadata};
+
+/// If the first `num_bands` bands of `ds` have differing data types, returns the smallest
+/// type able to represent all of them.
+fn common_band_type(ds: &Dataset, num_bands: usize) -> Result<Option<GdalDataType>> {
+    let mut types = Vec::with_capacity(num_bands);
+    for i in 1..=num_bands {
+        types.push(ds.rasterband(i)?.band_type());
+    }
+    let common = match types.split_first() {
+        Some((first, rest)) if rest.iter().any(|t| t != first) => {
+            Some(rest.iter().fold(*first, |acc, t| acc.union(*t)))
+        }
+        _ => None,
+    };
+    Ok(common)
+}
+
+/// Ensure the first `num_bands` bands of `ds` can hold NaN, for use as no-data value.
+fn check_floating_bands(ds: &Dataset, num_bands: usize) -> Result<()> {
+    for i in 1..=num_bands {
+        let band_type = ds.rasterband(i)?.band_type();
+        if band_type.is_integer() {
+            return Err(GdalError::BadArgument(format!(
+                "NaN no-data requires floating point bands, band {i} is {band_type}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The value closest to (but outside of) `min..=max` which `data_type` can hold exactly,
+/// preferably below `min`.
+fn value_outside(data_type: GdalDataType, min: f64, max: f64) -> Option<f64> {
+    [min.floor() - 1.0, max.ceil() + 1.0]
+        .into_iter()
+        .filter_map(|candidate| match data_type.adjust_value(candidate) {
+            AdjustedValue::Unchanged(value) if data_type == GdalDataType::Float32 => {
+                Some(value as f32 as f64)
+            }
+            AdjustedValue::Unchanged(value) => Some(value),
+            _ => None,
+        })
+        .find(|&value| value < min || value > max)
+}
+
+/// The window `(x_off, y_off, x_size, y_size)` of the pixels of `ds` intersecting `extent`,
+/// `[min_x, min_y, max_x, max_y]` in its georeferenced coordinates.
+fn extent_window(ds: &Dataset, extent: [f64; 4]) -> Result<(usize, usize, usize, usize)> {
+    let [min_x, min_y, max_x, max_y] = extent;
+    if !(min_x < max_x && min_y < max_y) {
+        return Err(GdalError::BadArgument(format!(
+            "invalid source extent: {extent:?}"
+        )));
+    }
+    let inv_gt = ds.geo_transform()?.invert()?;
+    let corners = [
+        (min_x, min_y),
+        (min_x, max_y),
+        (max_x, min_y),
+        (max_x, max_y),
+    ]
+    .map(|(x, y)| inv_gt.apply(x, y));
+    let (cols, rows) = ds.raster_size();
+    let clamp = |v: f64, max: usize| v.max(0.0).min(max as f64) as usize;
+    let bounds = |coords: [f64; 4], max: usize| {
+        let low = coords.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = coords.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        // Extents on the edges of pixels don't take in their neighbours for rounding errors.
+        (
+            clamp((low + 1e-8).floor(), max),
+            clamp((high - 1e-8).ceil(), max),
+        )
+    };
+    let (first_col, end_col) = bounds(corners.map(|(col, _)| col), cols);
+    let (first_row, end_row) = bounds(corners.map(|(_, row)| row), rows);
+    if first_col >= end_col || first_row >= end_row {
+        return Err(GdalError::BadArgument(format!(
+            "source extent {extent:?} doesn't intersect the source"
+        )));
+    }
+    Ok((
+        first_col,
+        first_row,
+        end_col - first_col,
+        end_row - first_row,
+    ))
+}
+
+/// Whether the first four bands of `ds` are cyan, magenta, yellow and black.
+fn is_cmyk(ds: &Dataset) -> Result<bool> {
+    use ColorInterpretation::*;
+    if ds.raster_count() < 4 {
+        return Ok(false);
+    }
+    for (i, expected) in [CyanBand, MagentaBand, YellowBand, BlackBand]
+        .into_iter()
+        .enumerate()
+    {
+        if ds.rasterband(i + 1)?.color_interpretation() != expected {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// GeoTIFF creation options for the block size of the first band of `ds`: tiles if it is
+/// tiled, strips of as many rows otherwise.
+fn block_size_creation_options(ds: &Dataset) -> Result<Vec<(&'static str, String)>> {
+    let (block_x, block_y) = ds.rasterband(1)?.block_size();
+    let mut options = Vec::new();
+    if block_x < ds.raster_size().0 {
+        options.push(("TILED", "YES".to_owned()));
+        options.push(("BLOCKXSIZE", block_x.to_string()));
+    }
+    options.push(("BLOCKYSIZE", block_y.to_string()));
+    Ok(options)
+}
+
+/// Setters shared by [`CreateReprojectOptions`] and [`ReprojectIntoOptions`].
+macro_rules! common_reproject_options {
+    () => {
+        /// Fetch the underlying warp options.
+        pub fn warp_options(&self) -> &GdalWarpOptions {
+            &self.warp_options
+        }
+
+        /// Fetch the underlying warp options, for modification.
+        pub fn warp_options_mut(&mut self) -> &mut GdalWarpOptions {
+            &mut self.warp_options
+        }
+
+        /// Replace the underlying warp options.
+        pub fn with_warp_options(&mut self, warp_options: GdalWarpOptions) -> &mut Self {
+            self.warp_options = warp_options;
+            self
+        }
+
+        /// Maximum error measured in input pixels that is allowed in approximating the
+        /// transformation. `0.0` (the default) selects exact calculations.
+        pub fn with_max_error(&mut self, max_error: f64) -> &mut Self {
+            self.max_error = Some(max_error);
+            self
+        }
+
+        /// Get the maximum allowed approximation error, if set.
+        pub fn max_error(&self) -> Option<f64> {
+            self.max_error
+        }
+
+        /// Value in the source raster to be treated as no-data, applied to all bands.
+        ///
+        /// May be `f64::NAN` for floating point sources, in which case the NaN pixels are
+        /// masked out (e.g. not blended in by resampling).
+        pub fn with_src_nodata(&mut self, nodata: f64) -> &mut Self {
+            self.src_nodata = Some(nodata);
+            self.src_nodata_per_band = None;
+            self
+        }
+
+        /// Get the source no-data value, if set.
+        pub fn src_nodata(&self) -> Option<f64> {
+            self.src_nodata
+        }
+
+        /// Values in the source raster to be treated as no-data, one for each warped band,
+        /// in order, replacing the one set with [`with_src_nodata`](Self::with_src_nodata).
+        ///
+        /// Warping fails if the number of values doesn't match the number of warped bands.
+        pub fn with_src_nodata_per_band(&mut self, nodata: &[f64]) -> &mut Self {
+            self.src_nodata = None;
+            self.src_nodata_per_band = Some(nodata.to_vec());
+            self
+        }
+
+        /// Get the source no-data values of each band, if set.
+        pub fn src_nodata_per_band(&self) -> Option<&[f64]> {
+            self.src_nodata_per_band.as_deref()
+        }
+
+        /// Value written to the destination raster where no source data is available,
+        /// applied to all bands.
+        pub fn with_dst_nodata(&mut self, nodata: f64) -> &mut Self {
+            self.dst_nodata = Some(nodata);
+            self.dst_nodata_per_band = None;
+            self.dst_nodata_type = None;
+            self
+        }
+
+        /// Get the destination no-data value, if set.
+        pub fn dst_nodata(&self) -> Option<f64> {
+            self.dst_nodata
+        }
+
+        /// Values written to the destination raster where no source data is available, one
+        /// for each warped band, in order, replacing the one set with
+        /// [`with_dst_nodata`](Self::with_dst_nodata).
+        ///
+        /// Warping fails if the number of values doesn't match the number of warped bands.
+        pub fn with_dst_nodata_per_band(&mut self, nodata: &[f64]) -> &mut Self {
+            self.dst_nodata = None;
+            self.dst_nodata_per_band = Some(nodata.to_vec());
+            self.dst_nodata_type = None;
+            self
+        }
+
+        /// Get the destination no-data values of each band, if set.
+        pub fn dst_nodata_per_band(&self) -> Option<&[f64]> {
+            self.dst_nodata_per_band.as_deref()
+        }
+
+        /// Same as [`with_dst_nodata_per_band`](Self::with_dst_nodata_per_band), for `Byte`
+        /// bands, e.g. `&[0, 0, 0]` to fill the uncovered areas of an RGB output with black.
+        ///
+        /// Warping fails if the output bands aren't of the `Byte` data type.
+        pub fn with_dst_nodata_u8(&mut self, nodata: &[u8]) -> &mut Self {
+            self.with_dst_nodata_typed(nodata)
+        }
+
+        /// Same as [`with_dst_nodata_u8`](Self::with_dst_nodata_u8), for `UInt16` bands.
+        pub fn with_dst_nodata_u16(&mut self, nodata: &[u16]) -> &mut Self {
+            self.with_dst_nodata_typed(nodata)
+        }
+
+        /// Same as [`with_dst_nodata_u8`](Self::with_dst_nodata_u8), for `Int16` bands.
+        pub fn with_dst_nodata_i16(&mut self, nodata: &[i16]) -> &mut Self {
+            self.with_dst_nodata_typed(nodata)
+        }
+
+        /// Same as [`with_dst_nodata_u8`](Self::with_dst_nodata_u8), for `UInt32` bands.
+        pub fn with_dst_nodata_u32(&mut self, nodata: &[u32]) -> &mut Self {
+            self.with_dst_nodata_typed(nodata)
+        }
+
+        /// Same as [`with_dst_nodata_u8`](Self::with_dst_nodata_u8), for `Int32` bands.
+        pub fn with_dst_nodata_i32(&mut self, nodata: &[i32]) -> &mut Self {
+            self.with_dst_nodata_typed(nodata)
+        }
+
+        fn with_dst_nodata_typed<T: GdalType + Copy + Into<f64>>(
+            &mut self,
+            nodata: &[T],
+        ) -> &mut Self {
+            let values = nodata.iter().map(|&v| v.into()).collect::<Vec<f64>>();
+            self.with_dst_nodata_per_band(&values);
+            self.dst_nodata_type = Some(T::datatype());
+            self
+        }
+
+        /// Whether a destination no-data value is set, for all bands or for each of them.
+        pub(crate) fn has_dst_nodata(&self) -> bool {
+            self.dst_nodata.is_some() || self.dst_nodata_per_band.is_some()
+        }
+
+        /// Clone the warp options, applying the no-data values configured here, and the
+        /// cutline (converted to the pixel coordinates of `src`, in its spatial reference
+        /// unless overridden).
+        ///
+        /// `num_bands` is the number of bands of `src` (starting from the first one) being warped.
+        /// It is used to set up the default band mapping when one is needed and none has been
+        /// configured yet.
+        ///
+        /// If those bands have differing data types and no working data type has been set,
+        /// the smallest type able to represent all of them is selected, so that no band
+        /// has its values truncated.
+        pub(crate) fn clone_and_init_warp_options(
+            &self,
+            src: &Dataset,
+            num_bands: usize,
+        ) -> Result<GdalWarpOptions> {
+            let mut warp_options = self.warp_options.clone();
+            warp_options.apply_cutline(src, self.src_srs_override())?;
+            if warp_options.working_data_type() == GdalDataType::Unknown {
+                if let Some(common) = common_band_type(src, num_bands)? {
+                    warp_options.with_working_data_type(common);
+                }
+            }
+            warp_options.apply_warp_chunk_size(src, num_bands)?;
+            if self.src_nodata.is_some()
+                || self.src_nodata_per_band.is_some()
+                || self.has_dst_nodata()
+            {
+                warp_options.init_default_band_mapping(num_bands);
+            }
+            if let Some(nodata) = self.src_nodata {
+                if nodata.is_nan() {
+                    check_floating_bands(src, num_bands)?;
+                }
+                warp_options.apply_src_nodata(nodata)?;
+            }
+            if let Some(nodata) = &self.src_nodata_per_band {
+                if nodata.iter().any(|v| v.is_nan()) {
+                    check_floating_bands(src, num_bands)?;
+                }
+                warp_options.apply_src_nodata_per_band(nodata)?;
+            }
+            if let Some(nodata) = self.dst_nodata {
+                warp_options.apply_dst_nodata(nodata)?;
+            }
+            if let Some(nodata) = &self.dst_nodata_per_band {
+                warp_options.apply_dst_nodata_per_band(nodata)?;
+            }
+            if let Some(nodata_type) = self.dst_nodata_type {
+                for i in 1..=num_bands {
+                    let band_type = self.output_band_type(src, i)?;
+                    if band_type != nodata_type {
+                        return Err(GdalError::BadArgument(format!(
+                            "{nodata_type} no-data values given for band {i}, which is {band_type}"
+                        )));
+                    }
+                }
+            }
+            Ok(warp_options)
+        }
+    };
+}
+
+/// Owned counterparts of the setters of `common_reproject_options`, on `$builder`.
+macro_rules! common_owned_setters {
+    ($builder:ident => $options:ident) => {
+        owned_setters! {
+            $builder => $options {
+                fn warp_options(warp_options: GdalWarpOptions) => with_warp_options;
+                fn max_error(max_error: f64) => with_max_error;
+                fn src_nodata(nodata: f64) => with_src_nodata;
+                fn src_nodata_per_band(nodata: &[f64]) => with_src_nodata_per_band;
+                fn dst_nodata(nodata: f64) => with_dst_nodata;
+                fn dst_nodata_per_band(nodata: &[f64]) => with_dst_nodata_per_band;
+                fn dst_nodata_u8(nodata: &[u8]) => with_dst_nodata_u8;
+                fn dst_nodata_u16(nodata: &[u16]) => with_dst_nodata_u16;
+                fn dst_nodata_i16(nodata: &[i16]) => with_dst_nodata_i16;
+                fn dst_nodata_u32(nodata: &[u32]) => with_dst_nodata_u32;
+                fn dst_nodata_i32(nodata: &[i32]) => with_dst_nodata_i32;
+            }
+        }
+    };
+}
+
+/// Predictor applied before compressing, as understood by the GeoTIFF driver.
+///
+/// See the `PREDICTOR` creation option of the [GeoTIFF driver](https://gdal.org/drivers/raster/gtiff.html#creation-options).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Predictor {
+    /// No prediction
+    None,
+    /// Horizontal differencing, for integer data
+    Horizontal,
+    /// Floating point prediction, for floating point data
+    FloatingPoint,
+}
+
+/// Compression of the created output, rendered to the creation options of the GeoTIFF driver.
+///
+/// See the `COMPRESS` creation option of the [GeoTIFF driver](https://gdal.org/drivers/raster/gtiff.html#creation-options).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    /// No compression
+    None,
+    /// LZW compression
+    Lzw,
+    /// DEFLATE compression, with a `level` from 1 (fastest) to 9 (smallest)
+    Deflate { level: u8, predictor: Predictor },
+    /// ZSTD compression, with a `level` from 1 (fastest) to 22 (smallest)
+    Zstd { level: u8 },
+}
+
+impl Compression {
+    /// The creation options selecting this compression.
+    pub fn to_creation_options(&self) -> Vec<(&'static str, String)> {
+        match *self {
+            Self::None => vec![("COMPRESS", "NONE".to_owned())],
+            Self::Lzw => vec![("COMPRESS", "LZW".to_owned())],
+            Self::Deflate { level, predictor } => {
+                let predictor = match predictor {
+                    Predictor::None => 1,
+                    Predictor::Horizontal => 2,
+                    Predictor::FloatingPoint => 3,
+                };
+                vec![
+                    ("COMPRESS", "DEFLATE".to_owned()),
+                    ("ZLEVEL", level.to_string()),
+                    ("PREDICTOR", predictor.to_string()),
+                ]
+            }
+            Self::Zstd { level } => vec![
+                ("COMPRESS", "ZSTD".to_owned()),
+                ("ZSTD_LEVEL", level.to_string()),
+            ],
+        }
+    }
+}
+
+/// Layout of the bands of the created output.
+///
+/// See the `INTERLEAVE` creation option of the [GeoTIFF driver](https://gdal.org/drivers/raster/gtiff.html#creation-options).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interleave {
+    /// The values of all the bands of a pixel are stored together
+    Pixel,
+    /// Each band is stored separately
+    Band,
+}
+
+impl Interleave {
+    /// The value of the `INTERLEAVE` creation option selecting this layout.
+    pub fn to_gdal_option(&self) -> &'static str {
+        match self {
+            Self::Pixel => "PIXEL",
+            Self::Band => "BAND",
+        }
+    }
+}
+
+/// Color model of the created output, as understood by the GeoTIFF driver.
+///
+/// See the `PHOTOMETRIC` creation option of the [GeoTIFF driver](https://gdal.org/drivers/raster/gtiff.html#creation-options).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Photometric {
+    /// Grayscale, 0 being black
+    MinIsBlack,
+    /// Grayscale, 0 being white
+    MinIsWhite,
+    /// Red, green and blue bands
+    Rgb,
+    /// Cyan, magenta, yellow and black bands, for printing
+    Cmyk,
+    /// Luminance and chrominance, only with JPEG compression
+    YCbCr,
+    /// CIE L*a*b*
+    CieLab,
+    /// ICC L*a*b*
+    IccLab,
+    /// ITU L*a*b*
+    ItuLab,
+}
+
+impl Photometric {
+    /// The value of the `PHOTOMETRIC` creation option selecting this color model.
+    pub fn to_gdal_option(&self) -> &'static str {
+        match self {
+            Self::MinIsBlack => "MINISBLACK",
+            Self::MinIsWhite => "MINISWHITE",
+            Self::Rgb => "RGB",
+            Self::Cmyk => "CMYK",
+            Self::YCbCr => "YCBCR",
+            Self::CieLab => "CIELAB",
+            Self::IccLab => "ICCLAB",
+            Self::ItuLab => "ITULAB",
+        }
+    }
+}
+
+/// Whether the created output is a BigTIFF, rather than a classic TIFF limited to 4 GB.
+///
+/// See the `BIGTIFF` creation option of the [GeoTIFF driver](https://gdal.org/drivers/raster/gtiff.html#creation-options).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BigTiffMode {
+    /// Always a BigTIFF
+    Yes,
+    /// Always a classic TIFF
+    No,
+    /// A BigTIFF if the output is uncompressed and likely to exceed 4 GB
+    IfNeeded,
+    /// A BigTIFF if the output may exceed 4 GB, i.e. if its uncompressed size exceeds 2 GB
+    IfSafer,
+}
+
+impl BigTiffMode {
+    /// The value of the `BIGTIFF` creation option selecting this mode.
+    pub fn to_gdal_option(&self) -> &'static str {
+        match self {
+            Self::Yes => "YES",
+            Self::No => "NO",
+            Self::IfNeeded => "IF_NEEDED",
+            Self::IfSafer => "IF_SAFER",
+        }
+    }
+}
+
+type PostBandFnInner = dyn Fn(usize, &mut [f64]) + Send + Sync;
+
+/// Function applied to the warped values, set with
+/// [`CreateReprojectOptions::with_post_band_fn`].
+#[derive(Clone)]
+pub(crate) struct PostBandFn(Arc<PostBandFnInner>);
+
+impl PostBandFn {
+    pub(crate) fn call(&self, band_index: usize, values: &mut [f64]) {
+        (self.0)(band_index, values)
+    }
+}
+
+impl Debug for PostBandFn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PostBandFn")
+    }
+}
+
+/// Options for [`create_and_reproject`](crate::raster::create_and_reproject).
+#[derive(Debug, Clone, Default)]
+pub struct CreateReprojectOptions {
+    warp_options: GdalWarpOptions,
+    max_error: Option<f64>,
+    src_nodata: Option<f64>,
+    src_nodata_per_band: Option<Vec<f64>>,
+    dst_nodata: Option<f64>,
+    dst_nodata_per_band: Option<Vec<f64>>,
+    dst_nodata_type: Option<GdalDataType>,
+    output_format: Option<String>,
+    target_resolution: Option<(f64, f64)>,
+    target_extent: Option<[f64; 4]>,
+    target_aligned_pixels: bool,
+    source_window: Option<(usize, usize, usize, usize)>,
+    src_extent: Option<[f64; 4]>,
+    skip_nodata_reopen: bool,
+    match_source_tiling: bool,
+    prototype_creation_options: Vec<(String, String)>,
+    compression: Option<Compression>,
+    interleave: Option<Interleave>,
+    photometric: Option<Photometric>,
+    bigtiff: Option<BigTiffMode>,
+    output_datatype: Option<GdalDataType>,
+    output_scaling: Option<(f64, f64)>,
+    sparse: bool,
+    auto_dst_nodata: bool,
+    extra_creation_options: Vec<(String, String)>,
+    add_alpha: bool,
+    pam_stats: bool,
+    post_band_fn: Option<PostBandFn>,
+}
+
+impl CreateReprojectOptions {
+    /// Create a set of options with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a builder of options, with owned setters, starting from the defaults.
+    pub fn builder() -> CreateReprojectOptionsBuilder {
+        CreateReprojectOptionsBuilder::default()
+    }
+
+    common_reproject_options!();
+
+    /// Short name of the driver used to create the output, e.g. `"GTiff"` (the default).
+    pub fn with_output_format(&mut self, format: &str) -> &mut Self {
+        self.output_format = Some(format.to_owned());
+        self
+    }
+
+    /// Get the output driver name, if set.
+    pub fn output_format(&self) -> Option<&str> {
+        self.output_format.as_deref()
+    }
+
+    /// Set the output pixel size, in the linear units of the destination spatial reference.
+    ///
+    /// E.g. for a State Plane CRS in US survey feet, `(1.0, 1.0)` means one foot per pixel,
+    /// not one metre. Defaults to the resolution computed by GDAL to preserve the source one.
+    ///
+    /// Like `gdalwarp -tr`: with a target resolution or extent, the output is created with
+    /// [`GDALWarp`], unless other options (such as a post-warp function or a cutline) require
+    /// warping onto the computed grid directly.
+    ///
+    /// [`GDALWarp`]: https://gdal.org/api/gdal_utils.html#_CPPv48GDALWarpPKc12GDALDatasetHiP12GDALDatasetHPK20GDALWarpAppOptionsPi
+    pub fn with_target_resolution(&mut self, x_res: f64, y_res: f64) -> &mut Self {
+        self.target_resolution = Some((x_res, y_res));
+        self
+    }
+
+    /// Get the output pixel size, if set.
+    pub fn target_resolution(&self) -> Option<(f64, f64)> {
+        self.target_resolution
+    }
+
+    /// Set the output extent, like `gdalwarp -te`, in the coordinates of the destination
+    /// spatial reference, in its traditional GIS axis order. The output covers this extent
+    /// exactly, at the target resolution, or at the one computed by GDAL.
+    ///
+    /// The extent isn't snapped to multiples of the resolution, unless
+    /// [`with_target_aligned_pixels`](Self::with_target_aligned_pixels) is set as well.
+    ///
+    /// If the extent doesn't intersect the one of the reprojected source, no output is created
+    /// and [`GdalError::EmptyOutput`](crate::errors::GdalError::EmptyOutput) is returned.
+    pub fn with_target_extent(
+        &mut self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+    ) -> &mut Self {
+        self.target_extent = Some([min_x, min_y, max_x, max_y]);
+        self
+    }
+
+    /// Get the output extent, as `[min_x, min_y, max_x, max_y]`, if set.
+    pub fn target_extent(&self) -> Option<[f64; 4]> {
+        self.target_extent
+    }
+
+    /// Align the output extent on multiples of the target resolution, like `gdalwarp -tap`,
+    /// growing it to still cover the target extent, or the computed one.
+    ///
+    /// Requires [`with_target_resolution`](Self::with_target_resolution). Defaults to `false`.
+    pub fn with_target_aligned_pixels(&mut self, aligned: bool) -> &mut Self {
+        self.target_aligned_pixels = aligned;
+        self
+    }
+
+    /// Whether the output extent is aligned on multiples of the target resolution.
+    pub fn target_aligned_pixels(&self) -> bool {
+        self.target_aligned_pixels
+    }
+
+    /// Only warp the window of `x_size` by `y_size` source pixels starting at pixel `x_off`
+    /// and line `y_off`, like `gdal_translate -srcwin`, so that the rest of the source isn't
+    /// read. The output covers the extent of that window.
+    ///
+    /// The window must lie within the source, and is the window of each source of
+    /// [`reproject_stack_like`](crate::raster::reproject_stack_like). Rejected by
+    /// [`reproject_to_cog`](crate::raster::reproject_to_cog). Replaces the source extent set
+    /// with [`with_src_extent`](Self::with_src_extent).
+    pub fn with_source_window(
+        &mut self,
+        x_off: usize,
+        y_off: usize,
+        x_size: usize,
+        y_size: usize,
+    ) -> &mut Self {
+        self.source_window = Some((x_off, y_off, x_size, y_size));
+        self.src_extent = None;
+        self
+    }
+
+    /// Get the source window, as `(x_off, y_off, x_size, y_size)`, if set.
+    pub fn source_window(&self) -> Option<(usize, usize, usize, usize)> {
+        self.source_window
+    }
+
+    /// Only warp the part of the source within `[min_x, min_y, max_x, max_y]`, in the
+    /// georeferenced coordinates of the source, e.g. to reproject the relevant part of a large
+    /// mosaic. The output covers the extent of that part.
+    ///
+    /// This is the source window (see [`with_source_window`](Self::with_source_window), which
+    /// it replaces) of the source pixels intersecting the extent, so the output may extend
+    /// past it by up to a source pixel. The extent must intersect the source, and has the same
+    /// support as the source window.
+    pub fn with_src_extent(&mut self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> &mut Self {
+        self.src_extent = Some([min_x, min_y, max_x, max_y]);
+        self.source_window = None;
+        self
+    }
+
+    /// Get the source extent, as `[min_x, min_y, max_x, max_y]`, if set.
+    pub fn src_extent(&self) -> Option<[f64; 4]> {
+        self.src_extent
+    }
+
+    /// A copy of these options, without the source window or extent.
+    pub(crate) fn without_source_window(&self) -> Self {
+        Self {
+            source_window: None,
+            src_extent: None,
+            ..self.clone()
+        }
+    }
+
+    /// The source window, if any, or the one of the source extent, checked to lie within `src`.
+    pub(crate) fn checked_source_window(
+        &self,
+        src: &Dataset,
+    ) -> Result<Option<(usize, usize, usize, usize)>> {
+        if let Some(extent) = self.src_extent {
+            return extent_window(src, extent).map(Some);
+        }
+        let (x_off, y_off, x_size, y_size) = match self.source_window {
+            Some(window) => window,
+            None => return Ok(None),
+        };
+        let (cols, rows) = src.raster_size();
+        let within = |off: usize, size: usize, max: usize| {
+            size > 0 && off.checked_add(size).map_or(false, |end| end <= max)
+        };
+        if !within(x_off, x_size, cols) || !within(y_off, y_size, rows) {
+            return Err(GdalError::BadArgument(format!(
+                "source window of {x_size}x{y_size} pixels at ({x_off}, {y_off}) is not within the {cols}x{rows} source"
+            )));
+        }
+        Ok(Some((x_off, y_off, x_size, y_size)))
+    }
+
+    /// `GDALCreateAndReprojectImage` doesn't record the destination no-data value on the
+    /// output, so by default the output is reopened after warping to set it on every band.
+    ///
+    /// Set to `true` to skip that step, e.g. when the no-data value doesn't need to be
+    /// recorded, or with a GDAL build that records it already.
+    pub fn with_skip_nodata_reopen(&mut self, skip: bool) -> &mut Self {
+        self.skip_nodata_reopen = skip;
+        self
+    }
+
+    /// Whether reopening the output to record the destination no-data value is skipped.
+    pub fn skip_nodata_reopen(&self) -> bool {
+        self.skip_nodata_reopen
+    }
+
+    /// Create the output with the same block size as the first band of the source, so that
+    /// it can be read as efficiently. A tiled source yields a tiled output (`TILED=YES`),
+    /// a stripped one an output with strips of as many rows.
+    ///
+    /// The block size is passed as `BLOCKXSIZE`/`BLOCKYSIZE` creation options, as
+    /// understood by the GeoTIFF driver.
+    pub fn with_match_source_tiling(&mut self, match_tiling: bool) -> &mut Self {
+        self.match_source_tiling = match_tiling;
+        self
+    }
+
+    /// Whether the output is created with the block size of the source.
+    pub fn match_source_tiling(&self) -> bool {
+        self.match_source_tiling
+    }
+
+    /// Create the output like `prototype`: with its driver, and with the compression,
+    /// predictor, interleaving and block size reported by its first band and its
+    /// `IMAGE_STRUCTURE` metadata, e.g. to deliver files with the exact structure a downstream
+    /// system expects.
+    ///
+    /// The creation options are named as understood by the GeoTIFF driver. Those set with
+    /// [`with_compression`](Self::with_compression), [`with_interleave`](Self::with_interleave),
+    /// [`with_creation_option`](Self::with_creation_option) or derived from the source take
+    /// precedence.
+    pub fn with_prototype(&mut self, prototype: &Dataset) -> Result<&mut Self> {
+        let structure = |key: &str| prototype.metadata_item(key, "IMAGE_STRUCTURE");
+        let mut options = Vec::new();
+        if let Some(compression) = structure("COMPRESSION") {
+            // GeoTIFF reports JPEG compression of YCbCr data as `YCbCr JPEG`.
+            if compression.eq_ignore_ascii_case("YCbCr JPEG") {
+                options.push(("COMPRESS", "JPEG".to_owned()));
+                options.push(("PHOTOMETRIC", "YCBCR".to_owned()));
+            } else {
+                options.push(("COMPRESS", compression));
+            }
+        }
+        if let Some(predictor) = structure("PREDICTOR") {
+            options.push(("PREDICTOR", predictor));
+        }
+        if let Some(interleave) = structure("INTERLEAVE") {
+            options.push(("INTERLEAVE", interleave));
+        }
+        if prototype.raster_count() > 0 {
+            options.extend(block_size_creation_options(prototype)?);
+        }
+        self.output_format = Some(prototype.driver().short_name());
+        self.prototype_creation_options = options
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        Ok(self)
+    }
+
+    /// Compress the output. Defaults to the driver's default, i.e. uncompressed for GeoTIFF.
+    pub fn with_compression(&mut self, compression: Compression) -> &mut Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Get the output compression, if set.
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    /// Lay out the bands of the output as `interleave`, for the drivers supporting the
+    /// `INTERLEAVE` creation option, such as GeoTIFF. Defaults to the driver's default, i.e.
+    /// pixel interleaving for GeoTIFF.
+    pub fn with_interleave(&mut self, interleave: Interleave) -> &mut Self {
+        self.interleave = Some(interleave);
+        self
+    }
+
+    /// Get the output interleaving, if set.
+    pub fn interleave(&self) -> Option<Interleave> {
+        self.interleave
+    }
+
+    /// Create the output with the `photometric` color model, for the GeoTIFF driver, e.g.
+    /// [`Photometric::YCbCr`] along with the `COMPRESS=JPEG` creation option.
+    ///
+    /// Defaults to [`Photometric::Cmyk`] for a source whose first four bands are cyan, magenta,
+    /// yellow and black, so that CMYK rasters stay CMYK, and to the driver's default otherwise.
+    pub fn with_photometric(&mut self, photometric: Photometric) -> &mut Self {
+        self.photometric = Some(photometric);
+        self
+    }
+
+    /// Get the output color model, if set.
+    pub fn photometric(&self) -> Option<Photometric> {
+        self.photometric
+    }
+
+    /// Select whether the output is a BigTIFF, for the GeoTIFF and COG drivers.
+    ///
+    /// Defaults to [`BigTiffMode::IfSafer`] with those drivers, so that outputs which may
+    /// exceed the 4 GB limit of classic TIFF files are created as BigTIFF.
+    pub fn with_bigtiff(&mut self, mode: BigTiffMode) -> &mut Self {
+        self.bigtiff = Some(mode);
+        self
+    }
+
+    /// Get the BigTIFF mode of the output: the one set, or the default one for the GeoTIFF
+    /// and COG drivers.
+    pub fn bigtiff(&self) -> Option<BigTiffMode> {
+        let tiff_output = self.output_format.as_deref().map_or(true, |format| {
+            format.eq_ignore_ascii_case("GTiff") || format.eq_ignore_ascii_case("COG")
+        });
+        self.bigtiff
+            .or_else(|| Some(BigTiffMode::IfSafer).filter(|_| tiff_output))
+    }
+
+    /// Create the output with bands of `data_type`, instead of the type of the source bands.
+    ///
+    /// Warped values are converted to that type, i.e. rounded and clamped for integer types.
+    pub fn with_output_datatype(&mut self, data_type: GdalDataType) -> &mut Self {
+        self.output_datatype = Some(data_type);
+        self.output_scaling = None;
+        self
+    }
+
+    /// Create the output with bands of the integer `data_type`, linearly mapping the warped
+    /// values from `src_min..=src_max` to the whole range of `data_type`.
+    ///
+    /// E.g. with [`GdalDataType::UInt8`], `src_min` maps to `0` and `src_max` to `255`, and
+    /// values outside of the source range are clamped. The destination no-data value, if any,
+    /// is left as is.
+    pub fn with_output_datatype_scaled(
+        &mut self,
+        data_type: GdalDataType,
+        src_min: f64,
+        src_max: f64,
+    ) -> &mut Self {
+        self.output_datatype = Some(data_type);
+        self.output_scaling = Some((src_min, src_max));
+        self
+    }
+
+    /// Get the output band data type, if set.
+    pub fn output_datatype(&self) -> Option<GdalDataType> {
+        self.output_datatype
+    }
+
+    /// Get the source range mapped to the range of the output data type, if set.
+    pub fn output_scaling(&self) -> Option<(f64, f64)> {
+        self.output_scaling
+    }
+
+    /// Allow the output to be sparse (`SPARSE_OK=TRUE`), so that blocks holding only no-data
+    /// (or zero) values aren't written at all, for drivers supporting it, such as GeoTIFF.
+    ///
+    /// This saves space when reprojecting sparse coverages.
+    pub fn with_sparse(&mut self, sparse: bool) -> &mut Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Whether the output is allowed to be sparse.
+    pub fn sparse(&self) -> bool {
+        self.sparse
+    }
+
+    /// Pick the destination no-data value automatically, unless one is set explicitly with
+    /// [`with_dst_nodata`](Self::with_dst_nodata).
+    ///
+    /// The value is the closest one just outside the range of the warped source values
+    /// (below their minimum if possible, above their maximum otherwise) that the output data
+    /// type can hold. Warping fails if there is none, e.g. when a byte source uses every value
+    /// from 0 to 255, or when the output is scaled to the full range of its data type. Computing
+    /// the range reads the whole source.
+    pub fn with_auto_dst_nodata(&mut self, auto: bool) -> &mut Self {
+        self.auto_dst_nodata = auto;
+        self
+    }
+
+    /// Whether the destination no-data value is picked automatically.
+    pub fn auto_dst_nodata(&self) -> bool {
+        self.auto_dst_nodata
+    }
+
+    /// The data type of the output band warped from the (1-based) band `band` of `src`.
+    fn output_band_type(&self, src: &Dataset, band: usize) -> Result<GdalDataType> {
+        match self.output_datatype {
+            Some(band_type) => Ok(band_type),
+            None => Ok(src.rasterband(band)?.band_type()),
+        }
+    }
+
+    /// The source spatial reference override: there is none, the one of the source is used.
+    fn src_srs_override(&self) -> Option<&SpatialRef> {
+        None
+    }
+
+    /// The destination no-data value of the (1-based) warped band `band`, if any.
+    pub(crate) fn dst_nodata_of_band(&self, band: usize) -> Option<f64> {
+        match &self.dst_nodata_per_band {
+            Some(nodata) => nodata.get(band - 1).copied(),
+            None => self.dst_nodata,
+        }
+    }
+
+    /// Create the output with an extra band, after the ones warped from the source bands,
+    /// holding the coverage of each pixel as alpha (see
+    /// [`GdalWarpOptions::with_dst_alpha_band`]), e.g. to turn an RGB source into an RGBA output.
+    ///
+    /// The band has the [`AlphaBand`](crate::raster::ColorInterpretation::AlphaBand) color
+    /// interpretation. Not supported together with
+    /// [`with_output_datatype_scaled`](Self::with_output_datatype_scaled).
+    pub fn with_add_alpha(&mut self, add_alpha: bool) -> &mut Self {
+        self.add_alpha = add_alpha;
+        self
+    }
+
+    /// Whether the output is created with an extra alpha band.
+    pub fn add_alpha(&self) -> bool {
+        self.add_alpha
+    }
+
+    /// Once the output is written, compute the statistics and the default histogram of each of
+    /// its bands, and save them in a `.aux.xml` sidecar file (GDAL's [PAM]), so that tools such
+    /// as QGIS or ArcGIS don't have to scan the output again.
+    ///
+    /// The output is reopened read-only for this, so that formats which could store them
+    /// internally, like GeoTIFF, use the sidecar as well. This reads the whole output. The
+    /// sidecar isn't passed on by [`reproject_to_writer`](crate::raster::reproject_to_writer),
+    /// which only streams the output file itself.
+    ///
+    /// [PAM]: https://gdal.org/drivers/raster/pam.html
+    pub fn with_pam_stats(&mut self, pam_stats: bool) -> &mut Self {
+        self.pam_stats = pam_stats;
+        self
+    }
+
+    /// Whether statistics and histograms are saved in a sidecar file.
+    pub fn pam_stats(&self) -> bool {
+        self.pam_stats
+    }
+
+    /// Call `f` on the values of each output band right after they are warped (so before they
+    /// are scaled, with [`with_output_datatype_scaled`](Self::with_output_datatype_scaled)),
+    /// e.g. to apply a gamma correction or a lookup table.
+    ///
+    /// `f` receives the index of the band (_1-based_) and the values of one of its blocks at a
+    /// time, no-data pixels included, which it updates in place. It isn't called on an alpha
+    /// band added with [`with_add_alpha`](Self::with_add_alpha). The output is then created on
+    /// its grid before being warped into, as with
+    /// [`with_target_resolution`](Self::with_target_resolution). Not supported by
+    /// [`reproject_to_cog`](crate::raster::reproject_to_cog).
+    pub fn with_post_band_fn<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(usize, &mut [f64]) + Send + Sync + 'static,
+    {
+        self.post_band_fn = Some(PostBandFn(Arc::new(f)));
+        self
+    }
+
+    /// Whether a function is applied to the warped values.
+    pub fn has_post_band_fn(&self) -> bool {
+        self.post_band_fn.is_some()
+    }
+
+    pub(crate) fn post_band_fn(&self) -> Option<&PostBandFn> {
+        self.post_band_fn.as_ref()
+    }
+
+    /// Whether the output is created with an extra alpha band, either explicitly or because
+    /// the warp options write one.
+    pub(crate) fn output_alpha(&self) -> bool {
+        self.add_alpha || self.warp_options.dst_alpha_band()
+    }
+
+    /// Pass the `key=value` creation option to the output driver, e.g. `OVERVIEWS=NONE` for the
+    /// [COG] driver.
+    ///
+    /// It takes precedence over the options derived from the other settings (such as
+    /// [`with_compression`](Self::with_compression)) for the same key. Options are passed the
+    /// same way whether the driver creates the output directly, or, like COG, can only write
+    /// a copy of a dataset: the source is then warped through a warped VRT as it is copied,
+    /// or in memory first, with a post-warp function or scaling.
+    ///
+    /// [COG]: https://gdal.org/drivers/raster/cog.html
+    pub fn with_creation_option(&mut self, key: &str, value: &str) -> &mut Self {
+        self.extra_creation_options
+            .retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+        self.extra_creation_options
+            .push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Pass several `key=value` creation options to the output driver, as with
+    /// [`with_creation_option`](Self::with_creation_option), e.g.
+    /// `[("COMPRESS", "DEFLATE"), ("BLOCKSIZE", "512")]`.
+    pub fn with_creation_options<I, K, V>(&mut self, options: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (key, value) in options {
+            self.with_creation_option(key.as_ref(), value.as_ref());
+        }
+        self
+    }
+
+    /// Get the creation options set with [`with_creation_option`](Self::with_creation_option).
+    pub fn extra_creation_options(&self) -> &[(String, String)] {
+        &self.extra_creation_options
+    }
+
+    /// A value outside the range of the first `num_bands` bands of `src`, which `data_type`
+    /// can hold, to be used as destination no-data value.
+    pub(crate) fn unused_dst_value(
+        &self,
+        src: &Dataset,
+        num_bands: usize,
+        data_type: GdalDataType,
+    ) -> Result<f64> {
+        if self.output_scaling.is_some() {
+            return Err(GdalError::BadArgument(format!(
+                "no unused {data_type} value is left for no-data by scaling to its full range"
+            )));
+        }
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        for i in 1..=num_bands {
+            let band_range = src.rasterband(i)?.compute_raster_min_max(false)?;
+            min = min.min(band_range.min);
+            max = max.max(band_range.max);
+        }
+        value_outside(data_type, min, max).ok_or_else(|| {
+            GdalError::BadArgument(format!(
+                "no {data_type} value is left outside the source range {min}..={max} for no-data"
+            ))
+        })
+    }
+
+    /// These options, with the destination no-data value picked for warping the first
+    /// `band_count` bands of `src` if [`with_auto_dst_nodata`](Self::with_auto_dst_nodata) is
+    /// set and no value is set explicitly, for the output data type (by default the one of the
+    /// first band of `src`).
+    pub(crate) fn resolved_for(&self, src: &Dataset, band_count: usize) -> Result<Cow<'_, Self>> {
+        if !self.needs_auto_dst_nodata() || src.raster_count() == 0 {
+            return Ok(Cow::Borrowed(self));
+        }
+        let data_type = self.output_band_type(src, 1)?;
+        self.resolved_for_type(src, band_count, data_type)
+    }
+
+    /// Same as [`resolved_for`](Self::resolved_for), for an output of `data_type`.
+    pub(crate) fn resolved_for_type(
+        &self,
+        src: &Dataset,
+        band_count: usize,
+        data_type: GdalDataType,
+    ) -> Result<Cow<'_, Self>> {
+        if !self.needs_auto_dst_nodata() {
+            return Ok(Cow::Borrowed(self));
+        }
+        let mut options = self.clone();
+        options.with_dst_nodata(self.unused_dst_value(src, band_count, data_type)?);
+        Ok(Cow::Owned(options))
+    }
+
+    fn needs_auto_dst_nodata(&self) -> bool {
+        self.auto_dst_nodata && !self.has_dst_nodata()
+    }
+
+    /// Creation options for the output of warping `src`.
+    pub(crate) fn creation_options(&self, src: &Dataset) -> Result<Vec<(String, String)>> {
+        let mut options = Vec::new();
+        if self.match_source_tiling && src.raster_count() > 0 {
+            options.extend(block_size_creation_options(src)?);
+        }
+        if let Some(compression) = self.compression {
+            options.extend(compression.to_creation_options());
+        }
+        if let Some(interleave) = self.interleave {
+            options.push(("INTERLEAVE", interleave.to_gdal_option().to_owned()));
+        }
+        let gtiff_output = self
+            .output_format
+            .as_deref()
+            .map_or(true, |format| format.eq_ignore_ascii_case("GTiff"));
+        let photometric = match self.photometric {
+            Some(photometric) => Some(photometric),
+            None if gtiff_output && is_cmyk(src)? => Some(Photometric::Cmyk),
+            None => None,
+        };
+        if let Some(photometric) = photometric {
+            options.push(("PHOTOMETRIC", photometric.to_gdal_option().to_owned()));
+        }
+        if self.sparse {
+            options.push(("SPARSE_OK", "TRUE".to_owned()));
+        }
+        if let Some(bigtiff) = self.bigtiff() {
+            options.push(("BIGTIFF", bigtiff.to_gdal_option().to_owned()));
+        }
+        let mut options: Vec<_> = options
+            .into_iter()
+            .filter(|(key, _)| {
+                !self
+                    .extra_creation_options
+                    .iter()
+                    .any(|(k, _)| k.eq_ignore_ascii_case(key))
+            })
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        let prototype_options = self
+            .prototype_creation_options
+            .iter()
+            .filter(|(key, _)| {
+                !options
+                    .iter()
+                    .chain(&self.extra_creation_options)
+                    .any(|(k, _)| k.eq_ignore_ascii_case(key))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        options.extend(prototype_options);
+        options.extend(self.extra_creation_options.iter().cloned());
+        Ok(options)
+    }
+}
+
+/// Owned builder of [`CreateReprojectOptions`], as returned by
+/// [`CreateReprojectOptions::builder`], so that a fluent chain of setters can be bound with
+/// `let`.
+///
+/// Each setter is the owned counterpart of the `with_*` setter of [`CreateReprojectOptions`]
+/// of the same name, and [`build`](Self::build) returns the options.
+///
+/// # Example
+///
+/// ```rust
+/// use gdal::raster::{CreateReprojectOptions, GdalWarpOptions, WarpResampleAlg};
+/// let options = CreateReprojectOptions::builder()
+///     .dst_nodata(0.0)
+///     .warp_options(
+///         GdalWarpOptions::builder()
+///             .resampling_alg(WarpResampleAlg::Bilinear)
+///             .build(),
+///     )
+///     .build();
+/// assert_eq!(options.dst_nodata(), Some(0.0));
+/// assert_eq!(
+///     options.warp_options().resampling_alg(),
+///     WarpResampleAlg::Bilinear
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct CreateReprojectOptionsBuilder(CreateReprojectOptions);
+
+impl CreateReprojectOptionsBuilder {
+    /// The options set.
+    pub fn build(self) -> CreateReprojectOptions {
+        self.0
+    }
+}
+
+common_owned_setters!(CreateReprojectOptionsBuilder => CreateReprojectOptions);
+
+owned_setters! {
+    CreateReprojectOptionsBuilder => CreateReprojectOptions {
+        fn output_format(format: &str) => with_output_format;
+        fn target_resolution(x_res: f64, y_res: f64) => with_target_resolution;
+        fn target_extent(min_x: f64, min_y: f64, max_x: f64, max_y: f64) => with_target_extent;
+        fn target_aligned_pixels(aligned: bool) => with_target_aligned_pixels;
+        fn source_window(
+            x_off: usize,
+            y_off: usize,
+            x_size: usize,
+            y_size: usize
+        ) => with_source_window;
+        fn src_extent(min_x: f64, min_y: f64, max_x: f64, max_y: f64) => with_src_extent;
+        fn skip_nodata_reopen(skip: bool) => with_skip_nodata_reopen;
+        fn match_source_tiling(match_tiling: bool) => with_match_source_tiling;
+        fn compression(compression: Compression) => with_compression;
+        fn interleave(interleave: Interleave) => with_interleave;
+        fn photometric(photometric: Photometric) => with_photometric;
+        fn bigtiff(mode: BigTiffMode) => with_bigtiff;
+        fn output_datatype(data_type: GdalDataType) => with_output_datatype;
+        fn output_datatype_scaled(
+            data_type: GdalDataType,
+            src_min: f64,
+            src_max: f64
+        ) => with_output_datatype_scaled;
+        fn sparse(sparse: bool) => with_sparse;
+        fn auto_dst_nodata(auto: bool) => with_auto_dst_nodata;
+        fn add_alpha(add_alpha: bool) => with_add_alpha;
+        fn pam_stats(pam_stats: bool) => with_pam_stats;
+        fn post_band_fn(f: impl Fn(usize, &mut [f64]) + Send + Sync + 'static) => with_post_band_fn;
+        fn creation_option(key: &str, value: &str) => with_creation_option;
+        fn creation_options(
+            options: impl IntoIterator<Item = (impl AsRef<str>, impl AsRef<str>)>
+        ) => with_creation_options;
+    }
+    fallible {
+        fn prototype(prototype: &Dataset) => with_prototype;
+    }
+}
+
+/// Options for [`reproject_into`](crate::raster::reproject_into).
+#[derive(Debug, Clone, Default)]
+pub struct ReprojectIntoOptions {
+    warp_options: GdalWarpOptions,
+    max_error: Option<f64>,
+    src_nodata: Option<f64>,
+    src_nodata_per_band: Option<Vec<f64>>,
+    dst_nodata: Option<f64>,
+    dst_nodata_per_band: Option<Vec<f64>>,
+    dst_nodata_type: Option<GdalDataType>,
+    src_srs: Option<SpatialRef>,
+    dst_srs: Option<SpatialRef>,
+    dst_band_offset: Option<usize>,
+}
+
+impl ReprojectIntoOptions {
+    /// Create a set of options with defaults.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a builder of options, with owned setters, starting from the defaults.
+    pub fn builder() -> ReprojectIntoOptionsBuilder {
+        ReprojectIntoOptionsBuilder::default()
+    }
+
+    common_reproject_options!();
+
+    /// Override the source spatial reference. Defaults to the one of the source dataset.
+    pub fn with_src_srs(&mut self, srs: &SpatialRef) -> &mut Self {
+        self.src_srs = Some(srs.clone());
+        self
+    }
+
+    /// Get the source spatial reference override, if set.
+    pub fn src_srs(&self) -> Option<&SpatialRef> {
+        self.src_srs.as_ref()
+    }
+
+    /// Override the destination spatial reference. Defaults to the one of the destination dataset.
+    pub fn with_dst_srs(&mut self, srs: &SpatialRef) -> &mut Self {
+        self.dst_srs = Some(srs.clone());
+        self
+    }
+
+    /// Get the destination spatial reference override, if set.
+    pub fn dst_srs(&self) -> Option<&SpatialRef> {
+        self.dst_srs.as_ref()
+    }
+
+    /// Write the source bands into the destination starting after band `offset`, so that
+    /// source band `i` is warped into destination band `offset + i`.
+    ///
+    /// The other destination bands are left untouched. E.g. an offset of `3` warps a
+    /// 3-band source into bands 4–6 of the destination.
+    pub fn with_dst_band_offset(&mut self, offset: usize) -> &mut Self {
+        self.dst_band_offset = Some(offset);
+        self
+    }
+
+    /// Get the destination band offset, if set.
+    pub fn dst_band_offset(&self) -> Option<usize> {
+        self.dst_band_offset
+    }
+
+    /// The data type of the (1-based) band `band` of `src`, which is expected to match the
+    /// one of the destination band it is warped into.
+    fn output_band_type(&self, src: &Dataset, band: usize) -> Result<GdalDataType> {
+        Ok(src.rasterband(band)?.band_type())
+    }
+
+    /// The source spatial reference override, set with [`with_src_srs`](Self::with_src_srs).
+    fn src_srs_override(&self) -> Option<&SpatialRef> {
+        self.src_srs.as_ref()
+    }
+}
+
+/// Owned builder of [`ReprojectIntoOptions`], as returned by [`ReprojectIntoOptions::builder`],
+/// like [`CreateReprojectOptionsBuilder`].
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct ReprojectIntoOptionsBuilder(ReprojectIntoOptions);
+
+impl ReprojectIntoOptionsBuilder {
+    /// The options set.
+    pub fn build(self) -> ReprojectIntoOptions {
+        self.0
+    }
+}
+
+common_owned_setters!(ReprojectIntoOptionsBuilder => ReprojectIntoOptions);
+
+owned_setters! {
+    ReprojectIntoOptionsBuilder => ReprojectIntoOptions {
+        fn src_srs(srs: &SpatialRef) => with_src_srs;
+        fn dst_srs(srs: &SpatialRef) => with_dst_srs;
+        fn dst_band_offset(offset: usize) => with_dst_band_offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DriverManager;
+    use gdal_sys::{CPLErr, GDALDataType};
+    use std::ptr::null_mut;
+
+    /// In-memory dataset with a UInt8 and a Float32 band.
+    fn mixed_type_dataset() -> Result<Dataset> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let ds = driver.create_with_band_type::<u8, _>("", 10, 10, 1)?;
+        let rv =
+            unsafe { gdal_sys::GDALAddBand(ds.c_dataset(), GDALDataType::GDT_Float32, null_mut()) };
+        assert_eq!(rv, CPLErr::CE_None);
+        Ok(ds)
+    }
+
+    #[test]
+    fn test_value_outside() {
+        use GdalDataType::*;
+        assert_eq!(value_outside(UInt8, 10.0, 200.0), Some(9.0));
+        assert_eq!(value_outside(UInt8, 0.0, 200.0), Some(201.0));
+        assert_eq!(value_outside(UInt8, 0.0, 255.0), None);
+        assert_eq!(value_outside(Int16, -3.5, 7.0), Some(-5.0));
+        assert_eq!(value_outside(Float32, 0.25, 1.0), Some(-1.0));
+        let huge = f32::MAX as f64;
+        assert_eq!(value_outside(Float32, -huge, huge), None);
+    }
+
+    #[test]
+    fn test_compression_creation_options() {
+        let deflate = Compression::Deflate {
+            level: 6,
+            predictor: Predictor::Horizontal,
+        };
+        assert_eq!(
+            deflate.to_creation_options(),
+            [
+                ("COMPRESS", "DEFLATE".to_owned()),
+                ("ZLEVEL", "6".to_owned()),
+                ("PREDICTOR", "2".to_owned())
+            ]
+        );
+        assert_eq!(
+            Compression::Zstd { level: 9 }.to_creation_options(),
+            [
+                ("COMPRESS", "ZSTD".to_owned()),
+                ("ZSTD_LEVEL", "9".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mixed_band_types() -> Result<()> {
+        let ds = mixed_type_dataset()?;
+        assert_eq!(ds.rasterband(2)?.band_type(), GdalDataType::Float32);
+
+        let opts = ReprojectIntoOptions::new();
+        let warp_options = opts.clone_and_init_warp_options(&ds, 2)?;
+        assert_eq!(warp_options.working_data_type(), GdalDataType::Float32);
+
+        // Only the first band is warped.
+        let warp_options = opts.clone_and_init_warp_options(&ds, 1)?;
+        assert_eq!(warp_options.working_data_type(), GdalDataType::Unknown);
+
+        // An explicit choice is left alone.
+        let mut opts = CreateReprojectOptions::new();
+        opts.warp_options_mut()
+            .with_working_data_type(GdalDataType::Float64);
+        let warp_options = opts.clone_and_init_warp_options(&ds, 2)?;
+        assert_eq!(warp_options.working_data_type(), GdalDataType::Float64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproject_mixed_band_types() -> Result<()> {
+        use crate::raster::{reproject_into, Buffer};
+
+        let mut src = mixed_type_dataset()?;
+        let gt = [0.0, 1.0, 0.0, 10.0, 0.0, -1.0];
+        src.set_geo_transform(&gt)?;
+        src.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        src.rasterband(1)?
+            .write((0, 0), (10, 10), &Buffer::new((10, 10), vec![200u8; 100]))?;
+        src.rasterband(2)?
+            .write((0, 0), (10, 10), &Buffer::new((10, 10), vec![0.25f32; 100]))?;
+
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut dst = driver.create_with_band_type::<f32, _>("", 10, 10, 2)?;
+        dst.set_geo_transform(&gt)?;
+        dst.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+        reproject_into(&src, &dst, &ReprojectIntoOptions::new())?;
+
+        let band1 = dst.rasterband(1)?.read_band_as::<f32>()?;
+        let band2 = dst.rasterband(2)?.read_band_as::<f32>()?;
+        assert!(band1.data.iter().all(|&v| v == 200.0));
+        assert!(band2.data.iter().all(|&v| v == 0.25));
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use gdal_sys::GDALResampleAlg;
+
+use crate::errors::{GdalError, Result};
+use crate::version::VersionInfo;
+
+/// Resampling algorithms available to the GDAL warp kernel.
+///
+/// This is a separate set of algorithms from [`ResampleAlg`](crate::raster::ResampleAlg),
+/// which is only applicable to raster I/O operations.
+///
+/// See [`GDALResampleAlg`](https://gdal.org/api/gdalwarp_cpp.html#_CPPv415GDALResampleAlg).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WarpResampleAlg {
+    /// Nearest neighbour (select on one input pixel)
+    NearestNeighbour,
+    /// Bilinear (2x2 kernel)
+    Bilinear,
+    /// Cubic Convolution Approximation (4x4 kernel)
+    Cubic,
+    /// Cubic B-Spline Approximation (4x4 kernel)
+    CubicSpline,
+    /// Lanczos windowed sinc interpolation (6x6 kernel)
+    Lanczos,
+    /// Average (computes the weighted average of all non-NODATA contributing pixels)
+    Average,
+    /// Mode (selects the value which appears most often of all the sampled points)
+    Mode,
+    /// Max (selects maximum of all non-NODATA contributing pixels)
+    Max,
+    /// Min (selects minimum of all non-NODATA contributing pixels)
+    Min,
+    /// Med (selects median of all non-NODATA contributing pixels)
+    Median,
+    /// Q1 (selects first quartile of all non-NODATA contributing pixels)
+    FirstQuartile,
+    /// Q3 (selects third quartile of all non-NODATA contributing pixels)
+    ThirdQuartile,
+    #[cfg(all(major_ge_3, minor_ge_1))]
+    /// Sum (weighted sum of all non-NODATA contributing pixels, GDAL >= 3.1)
+    Sum,
+    #[cfg(all(major_ge_3, minor_ge_3))]
+    /// RMS (weighted root mean square of all non-NODATA contributing pixels, GDAL >= 3.3)
+    ///
+    /// Unlike [`Average`](Self::Average), it preserves the power of amplitude data, such as
+    /// SAR backscatter, when downsampling.
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # fn main() -> gdal::errors::Result<()> {
+    /// use gdal::raster::{CreateReprojectOptions, WarpResampleAlg};
+    /// // Parsed rather than named, as the variant is compiled out with GDAL < 3.3.
+    /// let alg = "rms".parse::<WarpResampleAlg>()?;
+    /// alg.check_available()?;
+    /// let mut options = CreateReprojectOptions::new();
+    /// options.warp_options_mut().with_resampling_alg(alg);
+    /// # Ok(())
+    /// # }
+    /// ```
+    RootMeanSquare,
+}
+
+impl WarpResampleAlg {
+    /// Convert Rust enum discriminant to value expected by [`gdal_sys::GDALWarpOptions`].
+    pub fn to_gdal(&self) -> GDALResampleAlg::Type {
+        match self {
+            Self::NearestNeighbour => GDALResampleAlg::GRA_NearestNeighbour,
+            Self::Bilinear => GDALResampleAlg::GRA_Bilinear,
+            Self::Cubic => GDALResampleAlg::GRA_Cubic,
+            Self::CubicSpline => GDALResampleAlg::GRA_CubicSpline,
+            Self::Lanczos => GDALResampleAlg::GRA_Lanczos,
+            Self::Average => GDALResampleAlg::GRA_Average,
+            Self::Mode => GDALResampleAlg::GRA_Mode,
+            Self::Max => GDALResampleAlg::GRA_Max,
+            Self::Min => GDALResampleAlg::GRA_Min,
+            Self::Median => GDALResampleAlg::GRA_Med,
+            Self::FirstQuartile => GDALResampleAlg::GRA_Q1,
+            Self::ThirdQuartile => GDALResampleAlg::GRA_Q3,
+            #[cfg(all(major_ge_3, minor_ge_1))]
+            Self::Sum => GDALResampleAlg::GRA_Sum,
+            #[cfg(all(major_ge_3, minor_ge_3))]
+            Self::RootMeanSquare => GDALResampleAlg::GRA_RMS,
+        }
+    }
+
+    /// Create from the value stored in [`gdal_sys::GDALWarpOptions`].
+    ///
+    /// Returns `None` for algorithms not (yet) represented by this enumeration.
+    pub fn from_gdal(alg: GDALResampleAlg::Type) -> Option<Self> {
+        Self::iter().find(|a| a.to_gdal() == alg)
+    }
+
+    /// The name used for the algorithm by the `-r` flag of the `gdalwarp` program.
+    pub fn to_gdal_option(&self) -> &'static str {
+        match self {
+            Self::NearestNeighbour => "near",
+            Self::Bilinear => "bilinear",
+            Self::Cubic => "cubic",
+            Self::CubicSpline => "cubicspline",
+            Self::Lanczos => "lanczos",
+            Self::Average => "average",
+            Self::Mode => "mode",
+            Self::Max => "max",
+            Self::Min => "min",
+            Self::Median => "med",
+            Self::FirstQuartile => "q1",
+            Self::ThirdQuartile => "q3",
+            #[cfg(all(major_ge_3, minor_ge_1))]
+            Self::Sum => "sum",
+            #[cfg(all(major_ge_3, minor_ge_3))]
+            Self::RootMeanSquare => "rms",
+        }
+    }
+
+    /// Get an iterator over all the valid enumeration values.
+    pub fn iter() -> impl Iterator<Item = WarpResampleAlg> {
+        use WarpResampleAlg::*;
+        [
+            NearestNeighbour,
+            Bilinear,
+            Cubic,
+            CubicSpline,
+            Lanczos,
+            Average,
+            Mode,
+            Max,
+            Min,
+            Median,
+            FirstQuartile,
+            ThirdQuartile,
+            #[cfg(all(major_ge_3, minor_ge_1))]
+            Sum,
+            #[cfg(all(major_ge_3, minor_ge_3))]
+            RootMeanSquare,
+        ]
+        .into_iter()
+    }
+
+    /// The algorithms supported by the linked GDAL library, e.g. to offer them as choices.
+    ///
+    /// Older GDAL versions don't support the more recent algorithms, such as
+    /// [`RootMeanSquare`](Self::RootMeanSquare) (GDAL 3.3), which are then left out here,
+    /// and also compiled out when building against them.
+    pub fn available() -> Vec<WarpResampleAlg> {
+        let version = VersionInfo::version_num().parse::<i32>().unwrap_or(0);
+        Self::iter()
+            .filter(|alg| alg.min_version_num() <= version)
+            .collect()
+    }
+
+    /// Check that the linked GDAL library supports the algorithm, which may not be the case
+    /// for the more recent ones, such as [`Sum`](Self::Sum) (GDAL 3.1), if it is older than
+    /// the version the crate was built against.
+    pub fn check_available(&self) -> Result<()> {
+        let version = VersionInfo::version_num().parse::<i32>().unwrap_or(0);
+        if self.min_version_num() > version {
+            return Err(GdalError::BadArgument(format!(
+                "the {self} resampling algorithm isn't supported by GDAL {}",
+                VersionInfo::release_name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// The first GDAL version supporting the algorithm, formatted as `GDAL_VERSION_NUM`.
+    fn min_version_num(&self) -> i32 {
+        match self {
+            #[cfg(all(major_ge_3, minor_ge_1))]
+            Self::Sum => 3_01_00_00,
+            #[cfg(all(major_ge_3, minor_ge_3))]
+            Self::RootMeanSquare => 3_03_00_00,
+            _ => 0,
+        }
+    }
+}
+
+impl Default for WarpResampleAlg {
+    fn default() -> Self {
+        Self::NearestNeighbour
+    }
+}
+
+impl Display for WarpResampleAlg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Display format is the same as debug format.
+        Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for WarpResampleAlg {
+    type Err = GdalError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let lc = s.to_lowercase();
+        Self::iter()
+            .find(|a| a.to_string().to_lowercase() == lc || a.to_gdal_option() == lc)
+            .ok_or_else(|| {
+                GdalError::BadArgument(format!(
+                    "'{}' does not match one of {:?}",
+                    s,
+                    Self::iter().map(|e| e.to_string()).collect::<Vec<_>>()
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::Result;
+
+    #[test]
+    fn test_warp_resample_str() -> Result<()> {
+        for alg in WarpResampleAlg::iter() {
+            assert_eq!(alg.to_string().parse::<WarpResampleAlg>()?, alg);
+            assert_eq!(alg.to_gdal_option().parse::<WarpResampleAlg>()?, alg);
+            assert_eq!(WarpResampleAlg::from_gdal(alg.to_gdal()), Some(alg));
+        }
+        assert!("foobar".parse::<WarpResampleAlg>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_warp_resample_available() {
+        let available = WarpResampleAlg::available();
+        assert!(available.contains(&WarpResampleAlg::NearestNeighbour));
+        assert!(available.contains(&WarpResampleAlg::Bilinear));
+        assert!(available
+            .iter()
+            .all(|alg| WarpResampleAlg::iter().any(|a| a == *alg)));
+        #[cfg(all(major_ge_3, minor_ge_3))]
+        assert!(available.contains(&WarpResampleAlg::RootMeanSquare));
+        assert!(available.iter().all(|alg| alg.check_available().is_ok()));
+    }
+}
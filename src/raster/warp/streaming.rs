@@ -0,0 +1,261 @@
+use super::create::{check_no_premultiply_alpha, source_window_vrt};
+use super::grid::suggested_grid;
+use super::operation::{update_as_f64, WarpOperation};
+use crate::dataset::Dataset;
+use crate::errors::*;
+use crate::raster::{Buffer, CreateReprojectOptions, GdalType};
+use crate::spatial_ref::SpatialRef;
+use crate::GeoTransform;
+
+/// Reproject the first band of `src` into a buffer of `size` (columns, rows) pixels, in the
+/// `dst_srs` spatial reference, without creating any file.
+///
+/// The output covers the whole extent of the source, as computed by GDAL, stretched to `size`.
+/// Returns the warped pixels along with the geotransform of the output grid. They are warped
+/// block by block, as with [`reproject_streaming`].
+///
+/// The output format set in `options` is ignored. If a destination no-data value is set,
+/// it is used for the output pixels not covered by the source.
+pub fn create_and_reproject_to_buffer<T: GdalType + Copy>(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    size: (usize, usize),
+    options: &CreateReprojectOptions,
+) -> Result<(Buffer<T>, GeoTransform)> {
+    let (cols, rows) = size;
+    let mut data = Vec::with_capacity(cols * rows);
+    // The blocks of the current row of blocks, copied line by line once it is complete.
+    let mut blocks: Vec<Buffer<T>> = Vec::new();
+    let gt = reproject_streaming(src, dst_srs, size, options, |_, _, block: &Buffer<T>| {
+        blocks.push(Buffer::new(block.size, block.data.clone()));
+        if blocks.iter().map(|block| block.size.0).sum::<usize>() < cols {
+            return;
+        }
+        for line in 0..block.size.1 {
+            for block in &blocks {
+                let width = block.size.0;
+                data.extend_from_slice(&block.data[line * width..(line + 1) * width]);
+            }
+        }
+        blocks.clear();
+    })?;
+    Ok((Buffer::new(size, data), gt))
+}
+
+/// Same as [`create_and_reproject_to_buffer`], but warps the output block by block, passing
+/// each one to `sink` as soon as it is warped, so that the whole output is never held in
+/// memory.
+///
+/// The blocks are [`STREAMING_BLOCK_SIZE`] pixels wide and high, except on the right and bottom
+/// edges of the output, where they are cropped. `sink` receives the column and row of each
+/// block, counted in blocks, row by row. Returns the geotransform of the output grid.
+///
+/// The warp is set up once for the whole output, and each block is warped into the same
+/// buffer, reading only the window of the source it covers.
+pub fn reproject_streaming<T, F>(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    out_size: (usize, usize),
+    options: &CreateReprojectOptions,
+    mut sink: F,
+) -> Result<GeoTransform>
+where
+    T: GdalType + Copy,
+    F: FnMut(usize, usize, &Buffer<T>),
+{
+    let (cols, rows) = out_size;
+    if cols == 0 || rows == 0 {
+        return Err(GdalError::BadArgument(format!(
+            "output size must be non-zero, got {out_size:?}"
+        )));
+    }
+    check_no_premultiply_alpha(options)?;
+    if let Some(window) = options.checked_source_window(src)? {
+        let window = source_window_vrt(src, window)?;
+        let options = options.without_source_window();
+        return reproject_streaming(&window, dst_srs, out_size, &options, sink);
+    }
+    let options = &*options.resolved_for_type(src, 1, T::datatype())?;
+    let gt = stretched_grid(src, dst_srs, out_size, options)?;
+    let mut warp_options = options.clone_and_init_warp_options(src, 1)?;
+    warp_options.init_default_band_mapping(1);
+    let mut operation = WarpOperation::new(
+        src,
+        dst_srs,
+        gt,
+        None,
+        &warp_options,
+        options.max_error().unwrap_or(0.0),
+        T::datatype(),
+    )?;
+    let mut block = Buffer::new((0, 0), Vec::new());
+    for block_y in 0..(rows + STREAMING_BLOCK_SIZE - 1) / STREAMING_BLOCK_SIZE {
+        for block_x in 0..(cols + STREAMING_BLOCK_SIZE - 1) / STREAMING_BLOCK_SIZE {
+            let offset = (
+                block_x * STREAMING_BLOCK_SIZE,
+                block_y * STREAMING_BLOCK_SIZE,
+            );
+            block.size = (
+                STREAMING_BLOCK_SIZE.min(cols - offset.0),
+                STREAMING_BLOCK_SIZE.min(rows - offset.1),
+            );
+            operation.warp_region(offset, block.size, &mut block.data)?;
+            if let Some(post_band_fn) = options.post_band_fn() {
+                update_as_f64(&mut block.data, |values| post_band_fn.call(1, values));
+            }
+            sink(block_x, block_y, &block);
+        }
+    }
+    Ok(gt)
+}
+
+/// Width and height, in pixels, of the blocks warped by [`reproject_streaming`].
+pub const STREAMING_BLOCK_SIZE: usize = 256;
+
+/// The grid covering the whole extent of `src` in `dst_srs`, as computed by GDAL, stretched
+/// to `size` (columns, rows) pixels.
+fn stretched_grid(
+    src: &Dataset,
+    dst_srs: &SpatialRef,
+    size: (usize, usize),
+    options: &CreateReprojectOptions,
+) -> Result<GeoTransform> {
+    let (cols, rows) = size;
+    let (suggested_gt, (suggested_cols, suggested_rows)) =
+        suggested_grid(src, dst_srs, options.warp_options())?;
+    Ok([
+        suggested_gt[0],
+        suggested_gt[1] * suggested_cols as f64 / cols as f64,
+        0.0,
+        suggested_gt[3],
+        0.0,
+        suggested_gt[5] * suggested_rows as f64 / rows as f64,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::reproject_into;
+    use crate::raster::ReprojectIntoOptions;
+    use crate::test_utils::fixture;
+    use crate::DriverManager;
+
+    #[test]
+    fn test_reproject_streaming() -> Result<()> {
+        use crate::raster::InitDest;
+
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(4326)?;
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(255.0);
+        let size = (300, 280);
+        let (expected, expected_gt) =
+            create_and_reproject_to_buffer::<u8>(&source, &dst_srs, size, &opts)?;
+
+        let mut streamed = vec![0u8; size.0 * size.1];
+        let mut blocks = Vec::new();
+        let gt = reproject_streaming(
+            &source,
+            &dst_srs,
+            size,
+            &opts,
+            |block_x, block_y, block: &Buffer<u8>| {
+                blocks.push((block_x, block_y, block.size));
+                let (x_off, y_off) = (
+                    block_x * STREAMING_BLOCK_SIZE,
+                    block_y * STREAMING_BLOCK_SIZE,
+                );
+                for (row, values) in block.data.chunks(block.size.0).enumerate() {
+                    let start = (y_off + row) * size.0 + x_off;
+                    streamed[start..start + values.len()].copy_from_slice(values);
+                }
+            },
+        )?;
+        assert_eq!(gt, expected_gt);
+        assert_eq!(
+            blocks,
+            vec![
+                (0, 0, (256, 256)),
+                (1, 0, (44, 256)),
+                (0, 1, (256, 24)),
+                (1, 1, (44, 24))
+            ]
+        );
+        assert_eq!(streamed, expected.data);
+
+        // Same as warping the whole output at once.
+        let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut whole =
+            mem_driver.create_with_band_type::<u8, _>("", size.0 as isize, size.1 as isize, 1)?;
+        whole.set_geo_transform(&expected_gt)?;
+        whole.set_spatial_ref(&dst_srs)?;
+        let mut into_opts = ReprojectIntoOptions::new();
+        into_opts
+            .with_dst_nodata(255.0)
+            .warp_options_mut()
+            .with_init_dest(InitDest::NoData)?;
+        reproject_into(&source, &whole, &into_opts)?;
+        assert_eq!(streamed, whole.rasterband(1)?.read_band_as::<u8>()?.data);
+
+        assert!(
+            reproject_streaming::<u8, _>(&source, &dst_srs, (0, 10), &opts, |_, _, _| {}).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_to_buffer() -> Result<()> {
+        let source = Dataset::open(fixture("dem-hills.tiff"))?;
+        let (cols, rows) = source.raster_size();
+        let src_gt = source.geo_transform()?;
+
+        // Warping onto its own grid is a plain copy.
+        let mut opts = CreateReprojectOptions::new();
+        opts.with_dst_nodata(-9999.0);
+        let (buffer, gt) = create_and_reproject_to_buffer::<f32>(
+            &source,
+            &source.spatial_ref()?,
+            (cols, rows),
+            &opts,
+        )?;
+        assert_eq!(buffer.size, (cols, rows));
+        for (a, e) in gt.iter().zip(src_gt) {
+            assert!((a - e).abs() < 1e-9, "{gt:?} != {src_gt:?}");
+        }
+        let expected = source.rasterband(1)?.read_band_as::<f32>()?;
+        let center = (rows / 2) * cols + cols / 2;
+        assert_eq!(buffer.data[center], expected.data[center]);
+
+        // Half the resolution covers the same extent.
+        let (buffer, gt) = create_and_reproject_to_buffer::<f32>(
+            &source,
+            &source.spatial_ref()?,
+            (cols / 2, rows / 2),
+            &opts,
+        )?;
+        assert_eq!(buffer.data.len(), (cols / 2) * (rows / 2));
+        assert!((gt[1] * (cols / 2) as f64 - src_gt[1] * cols as f64).abs() < 1e-9);
+
+        assert!(create_and_reproject_to_buffer::<f32>(
+            &source,
+            &source.spatial_ref()?,
+            (0, rows),
+            &opts
+        )
+        .is_err());
+
+        // Only the top left quarter of the source, on its own grid.
+        let window = (cols / 2, rows / 2);
+        opts.with_source_window(0, 0, window.0, window.1);
+        let (buffer, gt) =
+            create_and_reproject_to_buffer::<f32>(&source, &source.spatial_ref()?, window, &opts)?;
+        assert!((gt[1] - src_gt[1]).abs() < 1e-9, "{gt:?} != {src_gt:?}");
+        let expected = source
+            .rasterband(1)?
+            .read_as::<f32>((0, 0), window, window, None)?;
+        let center = (window.1 / 2) * window.0 + window.0 / 2;
+        assert_eq!(buffer.data[center], expected.data[center]);
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+use std::path::{Path, PathBuf};
+
+use gdal_sys::{self, OSRAxisMappingStrategy};
+
+use super::create::{
+    check_no_premultiply_alpha, create_on_grid, finish_with_pam_stats, output_driver,
+    source_window_vrt,
+};
+use super::grid::{output_grid_from, suggested_grid, suggested_grid_with, transformer_to};
+use super::operation::Transformer;
+use super::reproject::with_transformer_cache_setting;
+use crate::dataset::Dataset;
+use crate::errors::*;
+use crate::raster::CreateReprojectOptions;
+use crate::spatial_ref::SpatialRef;
+use crate::{GdalOpenMode, Metadata};
+
+/// Reproject `ds` into a new file at `dst_file`, on the grid of the `tile_matrix_set` tile
+/// matrix set at zoom level `zoom`, as used by web maps.
+///
+/// The output is in the spatial reference of the tile matrix set, at its resolution for `zoom`,
+/// and covers the extent of the source with pixels aligned on the ones of the tile matrix
+/// (so snapping the extent outwards as needed). The supported [tile matrix sets] are:
+///  - `WebMercatorQuad` (EPSG:3857), the usual "slippy map" tiles,
+///  - `WorldCRS84Quad` (longitude/latitude WGS 84).
+///
+/// The target resolution set in `options` is ignored. Returns the output dataset.
+///
+/// [tile matrix sets]: https://docs.ogc.org/is/17-083r4/17-083r4.html
+pub fn create_and_reproject_to_tile_matrix<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_file: P,
+    tile_matrix_set: &str,
+    zoom: u8,
+    options: &CreateReprojectOptions,
+) -> Result<Dataset> {
+    const MAX_ZOOM: u8 = 24;
+    if zoom > MAX_ZOOM {
+        return Err(GdalError::BadArgument(format!(
+            "zoom level must be at most {MAX_ZOOM}, got {zoom}"
+        )));
+    }
+    let (dst_srs, (origin_x, origin_y), base_resolution) = tile_matrix_origin(tile_matrix_set)?;
+    check_no_premultiply_alpha(options)?;
+    if let Some(window) = options.checked_source_window(ds)? {
+        let window = source_window_vrt(ds, window)?;
+        let options = options.without_source_window();
+        return create_and_reproject_to_tile_matrix(
+            &window,
+            dst_file,
+            tile_matrix_set,
+            zoom,
+            &options,
+        );
+    }
+    let options = &*options.resolved_for(ds, ds.raster_count())?;
+
+    let resolution = base_resolution / f64::from(1u32 << zoom);
+    let (gt, (cols, rows)) = suggested_grid(ds, &dst_srs, options.warp_options())?;
+    let first_col = ((gt[0] - origin_x) / resolution).floor();
+    let last_col = ((gt[0] + gt[1] * cols as f64 - origin_x) / resolution).ceil();
+    let first_row = ((origin_y - gt[3]) / resolution).floor();
+    let last_row = ((origin_y - gt[3] - gt[5] * rows as f64) / resolution).ceil();
+    let grid = (
+        [
+            origin_x + first_col * resolution,
+            resolution,
+            0.0,
+            origin_y - first_row * resolution,
+            0.0,
+            -resolution,
+        ],
+        (last_col - first_col).max(1.0) as usize,
+        (last_row - first_row).max(1.0) as usize,
+    );
+    let dst_file = dst_file.as_ref();
+    let out = create_on_grid(ds, dst_file, &dst_srs, grid, options, None)?;
+    finish_with_pam_stats(out, dst_file, options, || {
+        Dataset::open_with_mode(dst_file, GdalOpenMode::Update)
+    })
+}
+
+/// Spatial reference, top left corner and resolution at zoom level 0 of the tile matrix set
+/// called `name`.
+fn tile_matrix_origin(name: &str) -> Result<(SpatialRef, (f64, f64), f64)> {
+    const TILE_SIZE: f64 = 256.0;
+    match name {
+        "WebMercatorQuad" => {
+            let half_extent = 20037508.342789244;
+            let srs = SpatialRef::from_epsg(3857)?;
+            Ok((
+                srs,
+                (-half_extent, half_extent),
+                2.0 * half_extent / TILE_SIZE,
+            ))
+        }
+        "WorldCRS84Quad" => {
+            let mut srs = SpatialRef::from_epsg(4326)?;
+            srs.set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+            // Two tiles side by side at zoom level 0.
+            Ok((srs, (-180.0, 90.0), 180.0 / TILE_SIZE))
+        }
+        _ => Err(GdalError::BadArgument(format!(
+            "unknown tile matrix set: {name}"
+        ))),
+    }
+}
+
+/// Reproject `ds` into one new file in `dst_dir` per resolution of `resolutions`, in the
+/// `dst_srs` spatial reference, e.g. one file per zoom level for a tile pipeline, rather than
+/// internal overviews.
+///
+/// Each output is warped from the source at its resolution (in units of `dst_srs`, the same for
+/// both axes), with target-aligned pixels (see
+/// [`CreateReprojectOptions::with_target_aligned_pixels`]): when the resolutions are multiples
+/// of each other, the pixels of the coarser outputs are made of whole pixels of the finer ones.
+/// The transformer, and the coordinate transformation it resolves, is set up once for all the
+/// outputs, and moved onto the grid of each in turn.
+///
+/// Each output is named after the file of the source and its resolution (with the extension of
+/// the output format), e.g. `labels_0.001.tif`, or after its resolution only for a source
+/// without a file. The target resolution set in `options` is ignored. Returns the paths of the
+/// outputs, in the order of `resolutions`.
+pub fn create_and_reproject_pyramid<P: AsRef<Path>>(
+    ds: &Dataset,
+    dst_dir: P,
+    dst_srs: &SpatialRef,
+    resolutions: &[f64],
+    options: &CreateReprojectOptions,
+) -> Result<Vec<PathBuf>> {
+    check_no_premultiply_alpha(options)?;
+    let description = ds.description()?;
+    let stem = Path::new(&description)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned());
+    let extension = output_driver(options)?
+        .metadata_item("DMD_EXTENSION", "")
+        .filter(|extension| !extension.is_empty());
+
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(resolutions.len());
+    for &resolution in resolutions {
+        if !(resolution.is_finite() && resolution > 0.0) {
+            return Err(GdalError::BadArgument(format!(
+                "resolutions must be positive, got {resolution}"
+            )));
+        }
+        // Not with `set_extension`, which would replace the decimals of the resolution.
+        let mut name = match &stem {
+            Some(stem) => format!("{stem}_{resolution}"),
+            None => resolution.to_string(),
+        };
+        if let Some(extension) = &extension {
+            name = format!("{name}.{extension}");
+        }
+        let path = dst_dir.as_ref().join(name);
+        if paths.contains(&path) {
+            return Err(GdalError::BadArgument(format!(
+                "resolution {resolution} is given several times"
+            )));
+        }
+        paths.push(path);
+    }
+
+    // Read the source window once for all the levels.
+    let window = match options.checked_source_window(ds)? {
+        Some(window) => Some(source_window_vrt(ds, window)?),
+        None => None,
+    };
+    let ds = window.as_ref().unwrap_or(ds);
+    let mut level_options = options.without_source_window();
+    level_options.with_target_aligned_pixels(true);
+    // Picking the no-data value may read the whole source, so it's done once as well.
+    let mut level_options = level_options
+        .resolved_for(ds, ds.raster_count())?
+        .into_owned();
+
+    // The extent of the source in `dst_srs`, and the transformer it is computed with, are the
+    // same for all the levels.
+    let warp_options = level_options.warp_options();
+    let (suggested, mut transformer) = with_transformer_cache_setting(warp_options, || {
+        let transformer = transformer_to(ds, dst_srs, warp_options)?;
+        let suggested = match unsafe { suggested_grid_with(ds, dst_srs, warp_options, transformer) }
+        {
+            Ok(suggested) => suggested,
+            Err(err) => {
+                unsafe { gdal_sys::GDALDestroyGenImgProjTransformer(transformer) };
+                return Err(err);
+            }
+        };
+        let transformer = unsafe {
+            Transformer::from_gen_img_proj(
+                transformer,
+                suggested.0,
+                warp_options,
+                level_options.max_error().unwrap_or(0.0),
+            )?
+        };
+        Ok((suggested, transformer))
+    })??;
+    for (&resolution, path) in resolutions.iter().zip(&paths) {
+        level_options.with_target_resolution(resolution, resolution);
+        let grid = output_grid_from(suggested, &level_options)?;
+        let out = create_on_grid(
+            ds,
+            path,
+            dst_srs,
+            grid,
+            &level_options,
+            Some(&mut transformer),
+        )?;
+        finish_with_pam_stats(Some(out), path, &level_options, || Ok(None))?;
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::grid::TRANSFORMERS_CREATED;
+    use super::*;
+    use crate::test_utils::{fixture, TempFixture};
+    use crate::DriverManager;
+
+    #[test]
+    fn test_create_and_reproject_to_tile_matrix() -> Result<()> {
+        let driver = DriverManager::get_driver_by_name("MEM")?;
+        let mut source = driver.create_with_band_type::<u8, _>("", 100, 100, 1)?;
+        source.set_geo_transform(&[2.0, 0.01, 0.0, 49.0, 0.0, -0.01])?;
+        source.set_spatial_ref(&SpatialRef::from_epsg(4326)?)?;
+
+        let dest = TempFixture::empty("tiled.tif");
+        let opts = CreateReprojectOptions::new();
+        let out = create_and_reproject_to_tile_matrix(
+            &source,
+            dest.path(),
+            "WebMercatorQuad",
+            10,
+            &opts,
+        )?;
+        assert_eq!(out.spatial_ref()?.auth_code()?, 3857);
+        // Zoom level 10 resolution of WebMercatorQuad.
+        let resolution = 152.8740565703525;
+        let gt = out.geo_transform()?;
+        assert!((gt[1] - resolution).abs() < 1e-9, "{gt:?}");
+        assert!((gt[5] + resolution).abs() < 1e-9, "{gt:?}");
+        // Aligned on the pixels of the tile matrix.
+        let col = (gt[0] + 20037508.342789244) / resolution;
+        let row = (20037508.342789244 - gt[3]) / resolution;
+        assert!((col - col.round()).abs() < 1e-6, "{col}");
+        assert!((row - row.round()).abs() < 1e-6, "{row}");
+        // Covering the whole source: a degree of longitude spans about 111 km in Web Mercator,
+        // and a degree of latitude more, away from the equator.
+        let (cols, rows) = out.raster_size();
+        assert!(cols as f64 * resolution >= 111_000.0, "{cols}");
+        assert!(rows > cols);
+
+        assert!(
+            create_and_reproject_to_tile_matrix(&source, dest.path(), "Unknown", 10, &opts)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_reproject_pyramid() -> Result<()> {
+        let source = Dataset::open(fixture("labels.tif"))?;
+        let dst_srs = SpatialRef::from_epsg(3857)?;
+        let out_dir = tempfile::tempdir().unwrap();
+        let resolutions = [25.0, 50.0, 100.0];
+        TRANSFORMERS_CREATED.with(|count| count.set(0));
+        let paths = create_and_reproject_pyramid(
+            &source,
+            out_dir.path(),
+            &dst_srs,
+            &resolutions,
+            &CreateReprojectOptions::new(),
+        )?;
+        // One transformer for all the levels.
+        assert_eq!(TRANSFORMERS_CREATED.with(|count| count.get()), 1);
+        assert_eq!(
+            paths,
+            [
+                out_dir.path().join("labels_25.tif"),
+                out_dir.path().join("labels_50.tif"),
+                out_dir.path().join("labels_100.tif"),
+            ]
+        );
+
+        let grids = paths
+            .iter()
+            .map(|path| {
+                let out = Dataset::open(path)?;
+                assert_eq!(out.spatial_ref()?, dst_srs);
+                Ok((out.geo_transform()?, out.raster_size()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for ((gt, _), resolution) in grids.iter().zip(resolutions) {
+            assert_eq!((gt[1], gt[5]), (resolution, -resolution));
+        }
+        for pair in grids.windows(2) {
+            let ((fine_gt, (fine_cols, fine_rows)), (coarse_gt, (coarse_cols, coarse_rows))) =
+                (pair[0], pair[1]);
+            assert_eq!(coarse_gt[1] / fine_gt[1], 2.0);
+            // The corners of the coarser grid fall on the corners of pixels of the finer one.
+            let col_offset = (fine_gt[0] - coarse_gt[0]) / fine_gt[1];
+            let row_offset = (coarse_gt[3] - fine_gt[3]) / fine_gt[1];
+            assert_eq!(col_offset, col_offset.round());
+            assert_eq!(row_offset, row_offset.round());
+            assert!((0.0..2.0).contains(&col_offset));
+            assert!((0.0..2.0).contains(&row_offset));
+            assert!(coarse_cols * 2 >= fine_cols && coarse_cols * 2 <= fine_cols + 2);
+            assert!(coarse_rows * 2 >= fine_rows && coarse_rows * 2 <= fine_rows + 2);
+        }
+
+        let out_dir = tempfile::tempdir().unwrap();
+        for resolutions in [&[25.0, 25.0][..], &[25.0, 0.0]] {
+            assert!(create_and_reproject_pyramid(
+                &source,
+                out_dir.path(),
+                &dst_srs,
+                resolutions,
+                &CreateReprojectOptions::new(),
+            )
+            .is_err());
+        }
+        assert_eq!(std::fs::read_dir(out_dir.path()).unwrap().count(), 0);
+        Ok(())
+    }
+}
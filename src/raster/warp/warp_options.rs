@@ -0,0 +1,1450 @@
+use std::ffi::CString;
+use std::fmt::{Debug, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr::{null_mut, NonNull};
+use std::sync::Arc;
+
+use gdal_sys::{CSLFetchNameValue, CSLSetNameValue, OGRwkbGeometryType, OSRAxisMappingStrategy};
+use libc::c_int;
+
+use crate::cpl::CslStringList;
+use crate::errors::{GdalError, Result};
+use crate::raster::{ColorInterpretation, GdalDataType, WarpResampleAlg};
+use crate::spatial_ref::{coordinate_operation_definition, SpatialRef};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err, _string};
+use crate::vector::{Geometry, Layer, LayerAccess};
+use crate::{Dataset, GeoTransformEx};
+
+/// A function transforming destination pixel/line coordinates into source ones, in place,
+/// see [`GdalWarpOptions::with_custom_transformer`].
+pub(crate) type CustomTransformer =
+    dyn Fn(&mut [f64], &mut [f64], &mut [f64]) -> Vec<bool> + Send + Sync;
+
+/// Number of threads the warp kernel runs on, as set with [`GdalWarpOptions::with_num_threads`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NumThreads {
+    /// As many threads as there are CPUs
+    AllCpus,
+    /// A given number of threads, at least one
+    Count(usize),
+}
+
+/// Initial value of the destination pixels, before warping, as set with
+/// [`GdalWarpOptions::with_init_dest`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitDest {
+    /// The destination no-data value of each band
+    NoData,
+    /// A value for each band, or a single one for all of them
+    Values(Vec<f64>),
+}
+
+/// Wrapper around a [`GDALWarpOptions`][GDALWarpOptions] object.
+///
+/// These are the low-level settings handed to the GDAL warp machinery. Most users
+/// will interact with them through [`CreateReprojectOptions`](crate::raster::CreateReprojectOptions)
+/// or [`ReprojectIntoOptions`](crate::raster::ReprojectIntoOptions).
+///
+/// [GDALWarpOptions]: https://gdal.org/api/gdalwarp_cpp.html#_CPPv415GDALWarpOptions
+pub struct GdalWarpOptions {
+    c_options: NonNull<gdal_sys::GDALWarpOptions>,
+    transformer_cache: bool,
+    explicit_memory_limit: bool,
+    cutline: Option<Geometry>,
+    cutline_srs: Option<SpatialRef>,
+    premultiply_alpha: bool,
+    dst_alpha: bool,
+    custom_transformer: Option<Arc<CustomTransformer>>,
+    coordinate_operation: Option<String>,
+    transformer_options: CslStringList,
+    overview_resampling: Option<WarpResampleAlg>,
+    warp_chunk_size: Option<(usize, usize)>,
+}
+
+impl GdalWarpOptions {
+    /// Smallest memory limit set by [`with_memory_limit_percent`](Self::with_memory_limit_percent),
+    /// in bytes: GDAL's default of 64 MiB.
+    pub const MIN_MEMORY_LIMIT: usize = 64 << 20;
+
+    /// Create a default set of warp options.
+    ///
+    /// See [`GDALCreateWarpOptions`](https://gdal.org/api/gdalwarp_cpp.html#_CPPv421GDALCreateWarpOptionsv).
+    pub fn new() -> Self {
+        let c_options = unsafe { gdal_sys::GDALCreateWarpOptions() };
+        Self {
+            c_options: NonNull::new(c_options).expect("GDALCreateWarpOptions"),
+            transformer_cache: true,
+            explicit_memory_limit: false,
+            cutline: None,
+            cutline_srs: None,
+            premultiply_alpha: false,
+            dst_alpha: false,
+            custom_transformer: None,
+            overview_resampling: None,
+            coordinate_operation: None,
+            transformer_options: CslStringList::new(),
+            warp_chunk_size: None,
+        }
+    }
+
+    /// Create a builder of warp options, with owned setters, starting from the defaults.
+    pub fn builder() -> GdalWarpOptionsBuilder {
+        GdalWarpOptionsBuilder::default()
+    }
+
+    /// Returns the wrapped C pointer
+    ///
+    /// # Safety
+    /// This method returns a raw C pointer
+    pub unsafe fn c_options(&self) -> *mut gdal_sys::GDALWarpOptions {
+        self.c_options.as_ptr()
+    }
+
+    fn c_ref(&self) -> &gdal_sys::GDALWarpOptions {
+        unsafe { self.c_options.as_ref() }
+    }
+
+    fn c_mut(&mut self) -> &mut gdal_sys::GDALWarpOptions {
+        unsafe { self.c_options.as_mut() }
+    }
+
+    /// Set the resampling algorithm used by the warp kernel.
+    ///
+    /// Defaults to [`WarpResampleAlg::NearestNeighbour`].
+    pub fn with_resampling_alg(&mut self, alg: WarpResampleAlg) -> &mut Self {
+        self.c_mut().eResampleAlg = alg.to_gdal();
+        self
+    }
+
+    /// Get the resampling algorithm used by the warp kernel.
+    pub fn resampling_alg(&self) -> WarpResampleAlg {
+        WarpResampleAlg::from_gdal(self.c_ref().eResampleAlg).unwrap_or_default()
+    }
+
+    /// Set the resampling algorithm used to compute the overviews of the output, when
+    /// reprojecting with [`create_and_reproject_with_overviews`] or [`reproject_to_cog`],
+    /// separately from the one of the warp itself, e.g. to avoid smoothing the output twice.
+    ///
+    /// Defaults to the resampling algorithm of the warp, or, for [`reproject_to_cog`], to the
+    /// default of the COG driver. Algorithms that GDAL doesn't support for overviews fall back
+    /// to nearest neighbour.
+    ///
+    /// [`create_and_reproject_with_overviews`]: crate::raster::create_and_reproject_with_overviews
+    /// [`reproject_to_cog`]: crate::raster::reproject_to_cog
+    pub fn with_overview_resampling(&mut self, alg: WarpResampleAlg) -> &mut Self {
+        self.overview_resampling = Some(alg);
+        self
+    }
+
+    /// Get the resampling algorithm used to compute the overviews of the output.
+    pub fn overview_resampling(&self) -> WarpResampleAlg {
+        self.overview_resampling
+            .unwrap_or_else(|| self.resampling_alg())
+    }
+
+    /// The algorithm set with [`with_overview_resampling`](Self::with_overview_resampling).
+    pub(crate) fn explicit_overview_resampling(&self) -> Option<WarpResampleAlg> {
+        self.overview_resampling
+    }
+
+    /// Set the maximum amount of memory, in bytes, the warp API is allowed to use for caching.
+    ///
+    /// A value of `0` selects GDAL's internal default. This takes precedence over
+    /// [`with_memory_limit_percent`](Self::with_memory_limit_percent), whatever the order
+    /// they are called in.
+    pub fn with_memory_limit(&mut self, limit: usize) -> &mut Self {
+        self.explicit_memory_limit = true;
+        self.c_mut().dfWarpMemoryLimit = limit as f64;
+        self
+    }
+
+    /// Set the maximum amount of memory the warp API is allowed to use for caching to `pct`
+    /// percent of the usable physical RAM, like `gdalwarp -wm 50%`.
+    ///
+    /// The limit is computed right away, with [`CPLGetUsablePhysicalRAM`], and is never less
+    /// than [`MIN_MEMORY_LIMIT`](Self::MIN_MEMORY_LIMIT), e.g. when the amount of RAM can't be
+    /// determined. `pct` is clamped to `0..=100`. Ignored if an absolute limit is set with
+    /// [`with_memory_limit`](Self::with_memory_limit).
+    ///
+    /// [`CPLGetUsablePhysicalRAM`]: https://gdal.org/api/cpl.html#_CPPv423CPLGetUsablePhysicalRAMv
+    pub fn with_memory_limit_percent(&mut self, pct: f64) -> &mut Self {
+        if self.explicit_memory_limit {
+            return self;
+        }
+        // NaN counts as 0.
+        let fraction = if pct > 0.0 {
+            (pct / 100.0).min(1.0)
+        } else {
+            0.0
+        };
+        let ram = unsafe { gdal_sys::CPLGetUsablePhysicalRAM() }.max(0) as f64;
+        let limit = ((ram * fraction) as usize).max(Self::MIN_MEMORY_LIMIT);
+        self.c_mut().dfWarpMemoryLimit = limit as f64;
+        self
+    }
+
+    /// Get the maximum amount of memory, in bytes, the warp API is allowed to use for caching.
+    pub fn memory_limit(&self) -> usize {
+        self.c_ref().dfWarpMemoryLimit as usize
+    }
+
+    /// Set the data type used during the warp operation.
+    ///
+    /// [`GdalDataType::Unknown`] (the default) lets GDAL select the type.
+    pub fn with_working_data_type(&mut self, data_type: GdalDataType) -> &mut Self {
+        self.c_mut().eWorkingDataType = data_type.gdal_ordinal();
+        self
+    }
+
+    /// Get the data type used during the warp operation.
+    pub fn working_data_type(&self) -> GdalDataType {
+        GdalDataType::try_from(self.c_ref().eWorkingDataType).unwrap_or(GdalDataType::Unknown)
+    }
+
+    /// Set whether the coordinate transformations set up by PROJ are cached, and reused by
+    /// later warps between the same spatial references.
+    ///
+    /// Within a single warp, the transformer is created once and shared by all the chunks.
+    /// Across warps, e.g. when warping many small windows one after the other, building the
+    /// PROJ pipeline again each time can take longer than the warp itself, which this cache
+    /// avoids. It is enabled by default.
+    ///
+    /// This is only a toggle: disabling it sets the `OSR_CT_USE_CACHE` configuration option to
+    /// `NO` on the current thread for the duration of the warps made with these options, and
+    /// whether a transformation is actually reused is left to GDAL.
+    pub fn with_transformer_cache(&mut self, enabled: bool) -> &mut Self {
+        self.transformer_cache = enabled;
+        self
+    }
+
+    /// Whether the coordinate transformations set up by PROJ are cached.
+    pub fn transformer_cache(&self) -> bool {
+        self.transformer_cache
+    }
+
+    /// Set whether the colors of the output are premultiplied by its alpha band.
+    ///
+    /// The source and destination alpha bands are the ones with the
+    /// [`AlphaBand`](crate::raster::ColorInterpretation::AlphaBand) color interpretation, and are
+    /// both required. Colors are resampled weighted by the source alpha, so that transparent
+    /// pixels don't bleed into their neighbours, then multiplied by the destination alpha over
+    /// `DST_ALPHA_MAX` (a warp option, `255` by default). Compositing the output as premultiplied
+    /// colors then avoids dark fringes along semi-transparent edges.
+    ///
+    /// Only supported by [`reproject_into`](crate::raster::reproject_into), which premultiplies
+    /// the whole destination.
+    pub fn with_premultiply_alpha(&mut self, premultiply: bool) -> &mut Self {
+        self.premultiply_alpha = premultiply;
+        self
+    }
+
+    /// Whether the colors of the output are premultiplied by its alpha band.
+    pub fn premultiply_alpha(&self) -> bool {
+        self.premultiply_alpha
+    }
+
+    /// Set whether the coverage of the output pixels is written to the last band of the
+    /// destination, as alpha: `0` where no source data was warped, up to `DST_ALPHA_MAX` (a warp
+    /// option, `255` by default) where fully covered.
+    ///
+    /// The other destination bands receive the warped source bands, so the destination needs one
+    /// more band than those, e.g. 4 bands (RGBA) for a 3-band (RGB) source. When creating the
+    /// output, e.g. with [`create_and_reproject`](crate::raster::create_and_reproject), that
+    /// band is added, as with
+    /// [`CreateReprojectOptions::with_add_alpha`](crate::raster::CreateReprojectOptions::with_add_alpha).
+    pub fn with_dst_alpha_band(&mut self, dst_alpha: bool) -> &mut Self {
+        self.dst_alpha = dst_alpha;
+        self
+    }
+
+    /// Whether the coverage of the output pixels is written to the last destination band.
+    pub fn dst_alpha_band(&self) -> bool {
+        self.dst_alpha
+    }
+
+    /// Warp with `transformer` instead of the transformation derived from the spatial
+    /// references and geotransforms of the datasets, e.g. to apply a custom sensor model.
+    ///
+    /// `transformer` receives the `x` (column), `y` (line) and `z` coordinates of destination
+    /// pixels, to be replaced by the matching source pixel coordinates, and returns whether
+    /// each point could be transformed (points it returns no flag for are failures). It may be
+    /// called concurrently when warping with several threads. The inverse transformation,
+    /// from source to destination, isn't supported, and always fails.
+    ///
+    /// The output grid of [`create_and_reproject`](crate::raster::create_and_reproject) and
+    /// its variants is still computed from the spatial references.
+    pub fn with_custom_transformer<F>(&mut self, transformer: F) -> &mut Self
+    where
+        F: Fn(&mut [f64], &mut [f64], &mut [f64]) -> Vec<bool> + Send + Sync + 'static,
+    {
+        self.custom_transformer = Some(Arc::new(transformer));
+        self
+    }
+
+    /// Whether a custom transformer is set.
+    pub fn has_custom_transformer(&self) -> bool {
+        self.custom_transformer.is_some()
+    }
+
+    /// The custom transformer, if set, to be called through [`call_custom_transformer`].
+    pub(crate) fn custom_transformer(&self) -> Option<&Arc<CustomTransformer>> {
+        self.custom_transformer.as_ref()
+    }
+
+    /// Force the coordinate operation between the source and destination spatial references,
+    /// given by its EPSG code or as a PROJ pipeline, as with
+    /// [`CoordTransformOptions::with_coordinate_operation`](crate::spatial_ref::CoordTransformOptions::with_coordinate_operation).
+    ///
+    /// Forcing the operation makes the results reproducible across machines with different
+    /// PROJ grids available, where PROJ may otherwise pick different operations. A PROJ
+    /// pipeline works on the coordinates in the axis order of the spatial references, e.g.
+    /// latitude first for EPSG geographic ones.
+    ///
+    /// Applies to the output grid computed by
+    /// [`create_and_reproject`](crate::raster::create_and_reproject) and its variants as well.
+    pub fn with_coordinate_operation(&mut self, epsg_or_pipeline: &str) -> &mut Self {
+        self.coordinate_operation = Some(coordinate_operation_definition(epsg_or_pipeline));
+        self
+    }
+
+    /// Get the definition of the forced coordinate operation, if set, with EPSG codes
+    /// turned into URNs.
+    pub fn coordinate_operation(&self) -> Option<&str> {
+        self.coordinate_operation.as_deref()
+    }
+
+    /// Set a `key=value` option of the transformer from the source pixels to the destination
+    /// pixels, as with `gdalwarp -to`, e.g. `RPC_HEIGHT` or `SRC_METHOD`.
+    ///
+    /// See [`GDALCreateGenImgProjTransformer2`](https://gdal.org/api/gdal_alg.html#_CPPv432GDALCreateGenImgProjTransformer212GDALDatasetH12GDALDatasetHPPc)
+    /// for the available options. Like the coordinate operation, they apply to the output grid
+    /// computed by [`create_and_reproject`](crate::raster::create_and_reproject) and its
+    /// variants as well.
+    pub fn with_transformer_option(&mut self, key: &str, value: &str) -> Result<&mut Self> {
+        self.transformer_options.set_name_value(key, value)?;
+        Ok(self)
+    }
+
+    /// Get the value of the transformer option `key`, if set.
+    pub fn transformer_option(&self, key: &str) -> Option<String> {
+        self.transformer_options.fetch_name_value(key)
+    }
+
+    /// Get the transformer options set with [`with_transformer_option`](Self::with_transformer_option).
+    pub fn transformer_options(&self) -> &CslStringList {
+        &self.transformer_options
+    }
+
+    /// Georeference the source with its rational polynomial coefficients (RPC), as with
+    /// `gdalwarp -rpc`, rather than with its geotransform or GCPs.
+    pub fn with_rpc(&mut self) -> Result<&mut Self> {
+        self.with_transformer_option("METHOD", "RPC")
+    }
+
+    /// Same as [`with_rpc`](Self::with_rpc), taking the heights from the digital elevation
+    /// model at `path`, through the `RPC_DEM` transformer option.
+    pub fn with_rpc_dem<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self> {
+        self.with_rpc()?
+            .with_transformer_option("RPC_DEM", &path.as_ref().to_string_lossy())
+    }
+
+    /// Set whether the vertical shift between the source and destination spatial references is
+    /// applied to the pixel values, e.g. to convert the elevations of a DEM from ellipsoidal
+    /// heights to the vertical datum of a compound `dst_srs`, as with `gdalwarp -vshift` and
+    /// `-novshift`.
+    ///
+    /// When enabled, the transformer does 3D transforms, and the height it finds for each
+    /// destination pixel is removed from the source value (the `APPLY_VERTICAL_SHIFT` warp
+    /// option). When disabled, the vertical components of compound spatial references are
+    /// ignored altogether (the `STRIP_VERT_CS` transformer option). Left unset, GDAL leaves the
+    /// values alone, but may still use the vertical components to transform the coordinates.
+    ///
+    /// Requires GDAL 3.6 or later, and PROJ must find the grids of the vertical datums, e.g.
+    /// geoid models, through its data directory or network access.
+    pub fn with_vertical_shift(&mut self, enabled: bool) -> Result<&mut Self> {
+        let value = if enabled { "YES" } else { "NO" };
+        self.with_warp_option("APPLY_VERTICAL_SHIFT", value)?
+            .with_transformer_option("STRIP_VERT_CS", if enabled { "NO" } else { "YES" })
+    }
+
+    /// Whether the vertical shift is applied to the pixel values, as set by
+    /// [`with_vertical_shift`](Self::with_vertical_shift).
+    pub fn vertical_shift(&self) -> bool {
+        self.warp_option("APPLY_VERTICAL_SHIFT")
+            .map_or(false, |value| value.eq_ignore_ascii_case("YES"))
+    }
+
+    /// Warp `src` into the whole of `dst` with the custom transformer, which must be set.
+    pub(crate) fn warp_with_custom_transformer(&self, src: &Dataset, dst: &Dataset) -> Result<()> {
+        let transformer = self
+            .custom_transformer()
+            .cloned()
+            .ok_or_else(|| GdalError::BadArgument("no custom transformer is set".into()))?;
+        // Kept alive until the warp operation is destroyed.
+        let transformer_arg = Box::new(transformer);
+        unsafe {
+            self.warp_with_transformer(
+                src,
+                dst,
+                Some(call_custom_transformer),
+                &*transformer_arg as *const Arc<CustomTransformer> as *mut _,
+            )
+        }
+    }
+
+    /// Warp `src` into the whole of `dst` with the `transformer` function, called with
+    /// `transformer_arg`.
+    ///
+    /// `GDALReprojectImage` always sets up its own transformer, so this drives a
+    /// `GDALWarpOperation` directly instead.
+    ///
+    /// # Safety
+    /// `transformer_arg` must be valid for `transformer` until this returns.
+    pub(crate) unsafe fn warp_with_transformer(
+        &self,
+        src: &Dataset,
+        dst: &Dataset,
+        transformer: gdal_sys::GDALTransformerFunc,
+        transformer_arg: *mut libc::c_void,
+    ) -> Result<()> {
+        let mut options = self.clone();
+        options.init_default_band_mapping(src.raster_count());
+        let c_options = options.c_mut();
+        c_options.hSrcDS = src.c_dataset();
+        c_options.hDstDS = dst.c_dataset();
+        c_options.pfnTransformer = transformer;
+        c_options.pTransformerArg = transformer_arg;
+
+        let operation = gdal_sys::GDALCreateWarpOperation(options.c_options());
+        let c_options = options.c_mut();
+        c_options.hSrcDS = null_mut();
+        c_options.hDstDS = null_mut();
+        c_options.pTransformerArg = null_mut();
+        if operation.is_null() {
+            return Err(_last_null_pointer_err("GDALCreateWarpOperation"));
+        }
+        let (cols, rows) = dst.raster_size();
+        let rv = gdal_sys::GDALChunkAndWarpImage(operation, 0, 0, cols as c_int, rows as c_int);
+        gdal_sys::GDALDestroyWarpOperation(operation);
+        if rv != gdal_sys::CPLErr::CE_None {
+            return Err(_last_cpl_err(rv));
+        }
+        Ok(())
+    }
+
+    /// Restrict the warp to the area covered by `cutline`, a polygon or multipolygon.
+    ///
+    /// The cutline coordinates are in the pixel/line space of the source dataset, unless a
+    /// spatial reference is set with [`with_cutline_srs`](Self::with_cutline_srs), or `cutline`
+    /// has one: they are then georeferenced, and reprojected to the source spatial reference as
+    /// needed. Destination pixels outside of it are left untouched (see the `INIT_DEST` warp
+    /// option to have them set to the no-data value).
+    pub fn with_cutline(&mut self, cutline: &Geometry) -> Result<&mut Self> {
+        let mut polygons = Geometry::empty(OGRwkbGeometryType::wkbMultiPolygon)?;
+        add_polygons(&mut polygons, cutline)?;
+        if let Some(srs) = cutline.spatial_ref() {
+            polygons.set_spatial_ref(srs);
+        }
+        self.cutline = Some(polygons);
+        Ok(self)
+    }
+
+    /// Use the (multi)polygons of the features of `layer` as cutline, combined into a single
+    /// multipolygon in the spatial reference of the layer, or in source pixel/line coordinates
+    /// if the layer has none.
+    ///
+    /// If `where_clause` is given, only the features matching it (as an OGR SQL `WHERE`
+    /// clause) are used. The attribute filter of `layer` is cleared afterwards.
+    ///
+    /// This is the equivalent of the `-cutline` and `-cwhere` flags of `gdalwarp`.
+    /// See [`with_cutline`](Self::with_cutline).
+    pub fn with_cutline_from_layer(
+        &mut self,
+        layer: &mut Layer,
+        where_clause: Option<&str>,
+    ) -> Result<&mut Self> {
+        if let Some(where_clause) = where_clause {
+            layer.set_attribute_filter(where_clause)?;
+        }
+        let mut polygons = Geometry::empty(OGRwkbGeometryType::wkbMultiPolygon)?;
+        let added = layer
+            .features()
+            .filter_map(|feature| feature.geometry().cloned())
+            .try_for_each(|geometry| add_polygons(&mut polygons, &geometry));
+        if where_clause.is_some() {
+            layer.clear_attribute_filter();
+        }
+        added?;
+        if polygons.is_empty() {
+            return Err(GdalError::BadArgument(
+                "no polygon found in the cutline layer".into(),
+            ));
+        }
+        if let Some(srs) = layer.spatial_ref() {
+            polygons.set_spatial_ref(srs);
+        }
+        self.cutline = Some(polygons);
+        Ok(self)
+    }
+
+    /// Get the cutline, as a multipolygon, if set.
+    pub fn cutline(&self) -> Option<&Geometry> {
+        self.cutline.as_ref()
+    }
+
+    /// Interpret the coordinates of the cutline in `srs`, whatever the spatial reference of the
+    /// cutline geometry or layer, like the `-cutline_srs` flag of `gdalwarp`, instead of in
+    /// source pixel/line coordinates.
+    pub fn with_cutline_srs(&mut self, srs: &SpatialRef) -> &mut Self {
+        self.cutline_srs = Some(srs.clone());
+        self
+    }
+
+    /// Get the spatial reference set with [`with_cutline_srs`](Self::with_cutline_srs), if any.
+    pub fn cutline_srs(&self) -> Option<&SpatialRef> {
+        self.cutline_srs.as_ref()
+    }
+
+    /// Feather the edges of the cutline over `pixels` source pixels, blending the warped
+    /// values into the destination, like the `-cblend` flag of `gdalwarp`. Defaults to `0`,
+    /// for a sharp edge.
+    ///
+    /// See `dfCutlineBlendDist` in [`GDALWarpOptions`](https://gdal.org/api/gdalwarp_cpp.html#_CPPv415GDALWarpOptions).
+    pub fn with_cutline_blend_dist(&mut self, pixels: f64) -> &mut Self {
+        self.c_mut().dfCutlineBlendDist = pixels;
+        self
+    }
+
+    /// Get the distance, in source pixels, over which the edges of the cutline are feathered.
+    pub fn cutline_blend_dist(&self) -> f64 {
+        self.c_ref().dfCutlineBlendDist
+    }
+
+    /// Set a `KEY=VALUE` entry in the warp options string list (`papszWarpOptions`).
+    ///
+    /// See [`GDALWarpOptions::papszWarpOptions`](https://gdal.org/api/gdalwarp_cpp.html#_CPPv4N15GDALWarpOptions16papszWarpOptionsE)
+    /// for the list of recognized entries.
+    pub fn with_warp_option(&mut self, key: &str, value: &str) -> Result<&mut Self> {
+        let c_key = CString::new(key)?;
+        let c_value = CString::new(value)?;
+        let c_options = self.c_mut();
+        c_options.papszWarpOptions = unsafe {
+            CSLSetNameValue(c_options.papszWarpOptions, c_key.as_ptr(), c_value.as_ptr())
+        };
+        Ok(self)
+    }
+
+    /// Fetch a value from the warp options string list (`papszWarpOptions`).
+    pub fn warp_option(&self, key: &str) -> Option<String> {
+        let c_key = CString::new(key).ok()?;
+        let c_value = unsafe { CSLFetchNameValue(self.c_ref().papszWarpOptions, c_key.as_ptr()) };
+        if c_value.is_null() {
+            None
+        } else {
+            Some(_string(c_value))
+        }
+    }
+
+    /// Whether these options may change the values of a source warped onto its own grid: they
+    /// set anything besides the resampling algorithm, the transformer cache and the
+    /// performance settings (memory limit, chunking and number of threads).
+    pub(crate) fn may_change_values(&self) -> bool {
+        // Warp options only tuning how the output is split and warped.
+        const PERFORMANCE_OPTIONS: [&str; 2] = ["NUM_THREADS", "OPTIMIZE_SIZE"];
+        let c_options = self.c_ref();
+        self.cutline.is_some()
+            || self.premultiply_alpha
+            || self.dst_alpha
+            || self.custom_transformer.is_some()
+            || self.working_data_type() != GdalDataType::Unknown
+            || c_options.nBandCount != 0
+            || c_options.nSrcAlphaBand != 0
+            || c_options.nDstAlphaBand != 0
+            || self.warp_option_entries().iter().any(|entry| {
+                let key = entry.split('=').next().unwrap_or_default();
+                !PERFORMANCE_OPTIONS
+                    .iter()
+                    .any(|option| key.eq_ignore_ascii_case(option))
+            })
+    }
+
+    /// The `KEY=VALUE` entries of the warp options string list (`papszWarpOptions`).
+    pub(crate) fn warp_option_entries(&self) -> Vec<String> {
+        let mut entries = Vec::new();
+        let mut entry = self.c_ref().papszWarpOptions;
+        if entry.is_null() {
+            return entries;
+        }
+        unsafe {
+            while !(*entry).is_null() {
+                entries.push(_string(*entry));
+                entry = entry.add(1);
+            }
+        }
+        entries
+    }
+
+    /// Set the number of points sampled along each edge of the source (`SAMPLE_STEPS`, 21 by
+    /// default), when working out which source pixels an output chunk needs and, when creating
+    /// the output, its extent.
+    ///
+    /// Strongly curved transformations, e.g. towards polar projections, need more samples for
+    /// the output extent to cover the source accurately. `steps` must be at least 2.
+    pub fn with_sample_steps(&mut self, steps: usize) -> Result<&mut Self> {
+        if steps < 2 {
+            return Err(GdalError::BadArgument(format!(
+                "at least 2 sample steps are needed, got {steps}"
+            )));
+        }
+        self.with_warp_option("SAMPLE_STEPS", &steps.to_string())
+    }
+
+    /// Get the number of points sampled along each edge of the source, if set.
+    pub fn sample_steps(&self) -> Option<usize> {
+        self.warp_option("SAMPLE_STEPS")?.parse().ok()
+    }
+
+    /// Set the number of threads the warp kernel runs on (`NUM_THREADS`), like the `-wo
+    /// NUM_THREADS` flag of `gdalwarp`. Defaults to a single thread.
+    ///
+    /// This applies to every reprojection function, and doesn't change the warped values.
+    pub fn with_num_threads(&mut self, threads: NumThreads) -> Result<&mut Self> {
+        let value = match threads {
+            NumThreads::AllCpus => "ALL_CPUS".to_owned(),
+            NumThreads::Count(0) => {
+                return Err(GdalError::BadArgument(
+                    "at least one warp thread is needed".into(),
+                ))
+            }
+            NumThreads::Count(count) => count.to_string(),
+        };
+        self.with_warp_option("NUM_THREADS", &value)
+    }
+
+    /// Get the number of threads the warp kernel runs on, if set.
+    pub fn num_threads(&self) -> Option<NumThreads> {
+        let value = self.warp_option("NUM_THREADS")?;
+        if value.eq_ignore_ascii_case("ALL_CPUS") {
+            Some(NumThreads::AllCpus)
+        } else {
+            value.parse().ok().map(NumThreads::Count)
+        }
+    }
+
+    /// Set the value the destination pixels are initialized to before warping (`INIT_DEST`),
+    /// like the `-init` flag of `gdalwarp`, so that the pixels no source data is warped into
+    /// keep it. By default, the existing destination values are left as they are.
+    pub fn with_init_dest(&mut self, init_dest: InitDest) -> Result<&mut Self> {
+        let value = match init_dest {
+            InitDest::NoData => "NO_DATA".to_owned(),
+            InitDest::Values(values) if values.is_empty() => {
+                return Err(GdalError::BadArgument(
+                    "at least one initial destination value is needed".into(),
+                ))
+            }
+            InitDest::Values(values) => values
+                .iter()
+                .map(f64::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+        };
+        self.with_warp_option("INIT_DEST", &value)
+    }
+
+    /// Get the value the destination pixels are initialized to before warping, if set.
+    pub fn init_dest(&self) -> Option<InitDest> {
+        let value = self.warp_option("INIT_DEST")?;
+        if value.eq_ignore_ascii_case("NO_DATA") {
+            return Some(InitDest::NoData);
+        }
+        value
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<Vec<_>>>()
+            .map(InitDest::Values)
+    }
+
+    /// Set whether the sample points cover the whole source as a grid (`SAMPLE_GRID`), rather
+    /// than only its edges, e.g. when the output extent isn't reached on the source edges.
+    pub fn with_sample_grid(&mut self, grid: bool) -> Result<&mut Self> {
+        self.with_warp_option("SAMPLE_GRID", if grid { "YES" } else { "NO" })
+    }
+
+    /// Whether the sample points cover the whole source as a grid.
+    pub fn sample_grid(&self) -> bool {
+        self.warp_option("SAMPLE_GRID")
+            .map_or(false, |grid| grid.eq_ignore_ascii_case("YES"))
+    }
+
+    /// Warp the output in chunks of the size of the blocks of (the first band of) `source`, as
+    /// with [`with_warp_chunk_size`](Self::with_warp_chunk_size).
+    ///
+    /// When warping onto a grid close to the one of `source`, each chunk then reads about one
+    /// block of the source, rather than (repeatedly decoded) parts of several. As with
+    /// [`with_warp_chunk_size`](Self::with_warp_chunk_size), warping fails if a memory limit is
+    /// also set.
+    pub fn with_auto_tile_from_source(&mut self, source: &Dataset) -> Result<&mut Self> {
+        if source.raster_count() == 0 {
+            return Err(GdalError::BadArgument(
+                "source dataset has no raster bands".into(),
+            ));
+        }
+        let (block_x, block_y) = source.rasterband(1)?.block_size();
+        self.with_warp_chunk_size(block_x, block_y)
+    }
+
+    /// Warp the output in chunks of about `x_size` × `y_size` pixels, or fewer, rather than
+    /// in the ones following from the memory limit.
+    ///
+    /// GDAL splits the output until each chunk fits in the memory limit, so this replaces the
+    /// limit by the memory needed to warp such a chunk of all the bands, computed when warping.
+    /// Larger chunks use more memory, but need fewer source windows to be computed and read.
+    /// Warping fails if a memory limit is also set with
+    /// [`with_memory_limit`](Self::with_memory_limit).
+    pub fn with_warp_chunk_size(&mut self, x_size: usize, y_size: usize) -> Result<&mut Self> {
+        if x_size == 0 || y_size == 0 {
+            return Err(GdalError::BadArgument(format!(
+                "invalid warp chunk size: {x_size}x{y_size}"
+            )));
+        }
+        self.warp_chunk_size = Some((x_size, y_size));
+        Ok(self)
+    }
+
+    /// Get the size of the chunks the output is warped in, if set.
+    pub fn warp_chunk_size(&self) -> Option<(usize, usize)> {
+        self.warp_chunk_size
+    }
+
+    /// Set the memory limit for the warp chunk size, if any, when warping `num_bands` bands
+    /// of `src`.
+    pub(crate) fn apply_warp_chunk_size(&mut self, src: &Dataset, num_bands: usize) -> Result<()> {
+        let (x_size, y_size) = match self.warp_chunk_size {
+            Some(size) => size,
+            None => return Ok(()),
+        };
+        if self.explicit_memory_limit {
+            return Err(GdalError::BadArgument(
+                "a warp chunk size and a memory limit can't both be set".into(),
+            ));
+        }
+        let data_type = match self.working_data_type() {
+            GdalDataType::Unknown if src.raster_count() > 0 => src.rasterband(1)?.band_type(),
+            data_type => data_type,
+        };
+        let pixel_bytes = (data_type.bytes() as usize).max(1);
+        let limit = Self::chunk_memory(x_size, y_size, num_bands.max(1), pixel_bytes);
+        self.c_mut().dfWarpMemoryLimit = limit as f64;
+        Ok(())
+    }
+
+    /// Estimate of the working memory needed to warp a chunk of `x_size` × `y_size` pixels.
+    ///
+    /// Accounts for source and destination buffers plus their validity masks.
+    pub(crate) fn chunk_memory(
+        x_size: usize,
+        y_size: usize,
+        band_count: usize,
+        pixel_bytes: usize,
+    ) -> usize {
+        let pixels = x_size * y_size;
+        // Source + destination buffers, plus a bit per pixel each for validity masks.
+        2 * pixels * band_count * pixel_bytes + pixels / 4
+    }
+
+    /// Set the number of bands warped, assigning the default `1..=count` mapping between
+    /// source and destination bands.
+    ///
+    /// Has no effect if a band mapping has already been established.
+    pub fn init_default_band_mapping(&mut self, band_count: usize) {
+        if self.c_ref().nBandCount == 0 {
+            unsafe {
+                gdal_sys::GDALWarpInitDefaultBandMapping(self.c_options(), band_count as c_int)
+            };
+        }
+    }
+
+    /// Shift the destination side of the band mapping by `offset` bands.
+    pub(crate) fn offset_dst_bands(&mut self, offset: usize) -> Result<()> {
+        let band_count = self.checked_band_count("offset_dst_bands")?;
+        let dst_bands =
+            unsafe { std::slice::from_raw_parts_mut(self.c_ref().panDstBands, band_count) };
+        for band in dst_bands {
+            *band += offset as c_int;
+        }
+        Ok(())
+    }
+
+    /// Number of bands covered by the band mapping (`0` means "all bands").
+    pub fn band_count(&self) -> usize {
+        self.c_ref().nBandCount as usize
+    }
+
+    /// The (1-based) destination bands of the band mapping, in order.
+    pub(crate) fn dst_bands(&self) -> Vec<usize> {
+        let dst_bands =
+            unsafe { std::slice::from_raw_parts(self.c_ref().panDstBands, self.band_count()) };
+        dst_bands.iter().map(|&band| band as usize).collect()
+    }
+
+    /// Apply a source no-data value to every band in the current band mapping.
+    pub(crate) fn apply_src_nodata(&mut self, nodata: f64) -> Result<()> {
+        let band_count = self.checked_band_count("apply_src_nodata")?;
+        self.apply_src_nodata_per_band(&vec![nodata; band_count])
+    }
+
+    /// Apply a destination no-data value to every band in the current band mapping.
+    pub(crate) fn apply_dst_nodata(&mut self, nodata: f64) -> Result<()> {
+        let band_count = self.checked_band_count("apply_dst_nodata")?;
+        self.apply_dst_nodata_per_band(&vec![nodata; band_count])
+    }
+
+    /// Apply the source no-data value of each band in the current band mapping, in order.
+    pub(crate) fn apply_src_nodata_per_band(&mut self, nodata: &[f64]) -> Result<()> {
+        self.check_nodata_per_band("apply_src_nodata_per_band", nodata)?;
+        unsafe {
+            gdal_sys::GDALWarpInitSrcNoDataReal(self.c_options(), nodata[0]);
+            let values =
+                std::slice::from_raw_parts_mut(self.c_ref().padfSrcNoDataReal, nodata.len());
+            values.copy_from_slice(nodata);
+        }
+        Ok(())
+    }
+
+    /// Apply the destination no-data value of each band in the current band mapping, in order.
+    pub(crate) fn apply_dst_nodata_per_band(&mut self, nodata: &[f64]) -> Result<()> {
+        self.check_nodata_per_band("apply_dst_nodata_per_band", nodata)?;
+        unsafe {
+            gdal_sys::GDALWarpInitDstNoDataReal(self.c_options(), nodata[0]);
+            let values =
+                std::slice::from_raw_parts_mut(self.c_ref().padfDstNoDataReal, nodata.len());
+            values.copy_from_slice(nodata);
+        }
+        Ok(())
+    }
+
+    /// Apply the complex source no-data value `re + im·i` to the (1-based) band `band` of the
+    /// current band mapping, for complex data types, where GDAL compares both components.
+    ///
+    /// If no source no-data value is set yet, the other bands get the same value; otherwise,
+    /// they keep their real part and get an imaginary part of `0`, if they had none.
+    ///
+    /// The band mapping must have been set up first, e.g. with
+    /// [`init_default_band_mapping`](Self::init_default_band_mapping).
+    pub fn apply_src_nodata_complex(&mut self, band: usize, re: f64, im: f64) -> Result<()> {
+        let index = self.checked_band_index("apply_src_nodata_complex", band)?;
+        unsafe {
+            if self.c_ref().padfSrcNoDataReal.is_null() {
+                gdal_sys::GDALWarpInitSrcNoDataReal(self.c_options(), re);
+                gdal_sys::GDALWarpInitSrcNoDataImag(self.c_options(), im);
+            } else if self.c_ref().padfSrcNoDataImag.is_null() {
+                gdal_sys::GDALWarpInitSrcNoDataImag(self.c_options(), 0.0);
+            }
+            *self.c_ref().padfSrcNoDataReal.add(index) = re;
+            *self.c_ref().padfSrcNoDataImag.add(index) = im;
+        }
+        Ok(())
+    }
+
+    /// Apply the complex destination no-data value `re + im·i` to the (1-based) band `band`
+    /// of the current band mapping, like
+    /// [`apply_src_nodata_complex`](Self::apply_src_nodata_complex).
+    pub fn apply_dst_nodata_complex(&mut self, band: usize, re: f64, im: f64) -> Result<()> {
+        let index = self.checked_band_index("apply_dst_nodata_complex", band)?;
+        unsafe {
+            if self.c_ref().padfDstNoDataReal.is_null() {
+                gdal_sys::GDALWarpInitDstNoDataReal(self.c_options(), re);
+                gdal_sys::GDALWarpInitDstNoDataImag(self.c_options(), im);
+            } else if self.c_ref().padfDstNoDataImag.is_null() {
+                gdal_sys::GDALWarpInitDstNoDataImag(self.c_options(), 0.0);
+            }
+            *self.c_ref().padfDstNoDataReal.add(index) = re;
+            *self.c_ref().padfDstNoDataImag.add(index) = im;
+        }
+        Ok(())
+    }
+
+    /// Write the validity of the output pixels (`0` for none, up to `DST_ALPHA_MAX` for
+    /// full) to the destination band at (1-based) index `band`.
+    pub(crate) fn set_dst_alpha_band(&mut self, band: usize) {
+        self.c_mut().nDstAlphaBand = band as c_int;
+    }
+
+    /// Write the coverage of the output to the last of the `dst_band_count` destination bands,
+    /// after warping the first `num_bands` source bands into the other ones.
+    pub(crate) fn apply_dst_alpha(
+        &mut self,
+        num_bands: usize,
+        dst_band_count: usize,
+    ) -> Result<()> {
+        self.init_default_band_mapping(num_bands);
+        let band_count = self.checked_band_count("apply_dst_alpha")?;
+        let dst_bands = unsafe { std::slice::from_raw_parts(self.c_ref().panDstBands, band_count) };
+        let last_dst_band = dst_bands.iter().copied().max().unwrap_or(0) as usize;
+        if last_dst_band >= dst_band_count {
+            return Err(GdalError::BadArgument(format!(
+                "destination has no band left for alpha after band {last_dst_band}, out of {dst_band_count}"
+            )));
+        }
+        self.set_dst_alpha_band(dst_band_count);
+        Ok(())
+    }
+
+    /// Warp the alpha bands of `src` and `dst` as such, and the remaining (color) bands in
+    /// order, replacing any other band mapping.
+    ///
+    /// Returns the (1-based) indices of the destination alpha band and color bands.
+    pub(crate) fn map_alpha_bands(
+        &mut self,
+        src: &Dataset,
+        dst: &Dataset,
+    ) -> Result<(usize, Vec<usize>)> {
+        let (src_alpha, src_colors) = split_alpha_band(src, "source")?;
+        let (dst_alpha, dst_colors) = split_alpha_band(dst, "destination")?;
+        if src_colors.len() != dst_colors.len() {
+            return Err(GdalError::BadArgument(format!(
+                "cannot warp {} color band(s) into {}",
+                src_colors.len(),
+                dst_colors.len()
+            )));
+        }
+
+        let band_count = src_colors.len();
+        if self.band_count() < band_count {
+            unsafe {
+                gdal_sys::GDALWarpInitDefaultBandMapping(self.c_options(), band_count as c_int)
+            };
+        }
+        let c_options = self.c_mut();
+        c_options.nBandCount = band_count as c_int;
+        let (src_bands, dst_bands) = unsafe {
+            (
+                std::slice::from_raw_parts_mut(c_options.panSrcBands, band_count),
+                std::slice::from_raw_parts_mut(c_options.panDstBands, band_count),
+            )
+        };
+        for (i, (&src_band, &dst_band)) in src_colors.iter().zip(&dst_colors).enumerate() {
+            src_bands[i] = src_band as c_int;
+            dst_bands[i] = dst_band as c_int;
+        }
+        c_options.nSrcAlphaBand = src_alpha as c_int;
+        c_options.nDstAlphaBand = dst_alpha as c_int;
+        Ok((dst_alpha, dst_colors))
+    }
+
+    /// Hand the cutline, if any, to GDAL, which expects it in pixel/line coordinates of `src`.
+    ///
+    /// A georeferenced cutline is reprojected to `src_srs` if given, otherwise to the spatial
+    /// reference of `src`.
+    pub(crate) fn apply_cutline(
+        &mut self,
+        src: &Dataset,
+        src_srs: Option<&SpatialRef>,
+    ) -> Result<()> {
+        let mut cutline = match &self.cutline {
+            Some(cutline) => cutline.clone(),
+            None => return Ok(()),
+        };
+        cutline.flatten_to_2d();
+        let cutline_srs = match self.cutline_srs.clone().or_else(|| cutline.spatial_ref()) {
+            Some(cutline_srs) => cutline_srs,
+            // Already in pixel/line coordinates.
+            None => {
+                self.set_c_cutline(cutline);
+                return Ok(());
+            }
+        };
+        let src_srs = match src_srs {
+            Some(src_srs) => Ok(src_srs.clone()),
+            None => src.spatial_ref(),
+        };
+        if let Ok(mut src_srs) = src_srs {
+            if cutline_srs != src_srs {
+                let mut cutline_srs = cutline_srs;
+                cutline_srs
+                    .set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+                src_srs
+                    .set_axis_mapping_strategy(OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+                cutline.set_spatial_ref(cutline_srs);
+                cutline.transform_to_inplace(&src_srs)?;
+            }
+        }
+
+        let inverse = src.geo_transform()?.invert()?;
+        for i in 0..cutline.geometry_count() {
+            let polygon = cutline.get_geometry(i);
+            for j in 0..polygon.geometry_count() {
+                let mut ring = polygon.get_geometry(j);
+                for k in 0..ring.point_count() {
+                    let (x, y, _) = ring.get_point(k as i32);
+                    ring.set_point_2d(k, inverse.apply(x, y));
+                }
+            }
+        }
+        self.set_c_cutline(cutline);
+        Ok(())
+    }
+
+    /// Replace the cutline handed to GDAL by `cutline`, in pixel/line coordinates of the source.
+    fn set_c_cutline(&mut self, cutline: Geometry) {
+        let c_options = self.c_mut();
+        unsafe {
+            if !c_options.hCutline.is_null() {
+                gdal_sys::OGR_G_DestroyGeometry(c_options.hCutline);
+            }
+            c_options.hCutline = cutline.into_c_geometry();
+        }
+    }
+
+    /// GDAL compares pixels with a NaN no-data value with `isnan`, which only works if the
+    /// working buffers are of a floating point type.
+    fn check_nodata_working_type(&self, nodata: f64) -> Result<()> {
+        let data_type = self.working_data_type();
+        if nodata.is_nan() && data_type.is_integer() {
+            return Err(GdalError::BadArgument(format!(
+                "NaN no-data requires a floating point working data type, got {data_type}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Ensure there is one `nodata` value for each band in the current band mapping.
+    fn check_nodata_per_band(&self, method_name: &str, nodata: &[f64]) -> Result<()> {
+        let band_count = self.checked_band_count(method_name)?;
+        if nodata.len() != band_count {
+            return Err(GdalError::BadArgument(format!(
+                "got {} no-data value(s) for {band_count} warped band(s)",
+                nodata.len()
+            )));
+        }
+        for &value in nodata {
+            self.check_nodata_working_type(value)?;
+        }
+        Ok(())
+    }
+
+    /// The 0-based index of the (1-based) band `band` in the current band mapping.
+    fn checked_band_index(&self, method_name: &str, band: usize) -> Result<usize> {
+        let band_count = self.checked_band_count(method_name)?;
+        if band == 0 || band > band_count {
+            return Err(GdalError::BadArgument(format!(
+                "band {band} is out of the {band_count} warped band(s)"
+            )));
+        }
+        Ok(band - 1)
+    }
+
+    fn checked_band_count(&self, method_name: &str) -> Result<usize> {
+        match self.band_count() {
+            0 => Err(GdalError::BadArgument(format!(
+                "band mapping must be initialized before calling `{method_name}`"
+            ))),
+            n => Ok(n),
+        }
+    }
+}
+
+/// `GDALTransformerFunc` calling the custom transformer `arg` points to.
+pub(crate) unsafe extern "C" fn call_custom_transformer(
+    arg: *mut libc::c_void,
+    dst_to_src: c_int,
+    point_count: c_int,
+    x: *mut f64,
+    y: *mut f64,
+    z: *mut f64,
+    success: *mut c_int,
+) -> c_int {
+    let count = point_count.max(0) as usize;
+    if count == 0 {
+        return 1;
+    }
+    let success = std::slice::from_raw_parts_mut(success, count);
+    if dst_to_src == 0 {
+        success.fill(0);
+        return 0;
+    }
+    let transformer = &*(arg as *const Arc<CustomTransformer>);
+    let x = std::slice::from_raw_parts_mut(x, count);
+    let y = std::slice::from_raw_parts_mut(y, count);
+    let mut no_z = Vec::new();
+    let z = if z.is_null() {
+        no_z.resize(count, 0.0);
+        &mut no_z[..]
+    } else {
+        std::slice::from_raw_parts_mut(z, count)
+    };
+    // Unwinding into GDAL is undefined behavior, so a panic fails the whole batch instead.
+    match catch_unwind(AssertUnwindSafe(|| transformer(x, y, z))) {
+        Ok(transformed) => {
+            for (i, flag) in success.iter_mut().enumerate() {
+                *flag = c_int::from(transformed.get(i).copied().unwrap_or(false));
+            }
+            1
+        }
+        Err(_) => {
+            success.fill(0);
+            0
+        }
+    }
+}
+
+/// Split the bands of `ds` into its alpha band and the other ones (1-based indices).
+fn split_alpha_band(ds: &Dataset, which: &str) -> Result<(usize, Vec<usize>)> {
+    let mut alpha = None;
+    let mut colors = Vec::new();
+    for i in 1..=ds.raster_count() {
+        if ds.rasterband(i)?.color_interpretation() == ColorInterpretation::AlphaBand {
+            alpha.get_or_insert(i);
+        } else {
+            colors.push(i);
+        }
+    }
+    match alpha {
+        Some(alpha) => Ok((alpha, colors)),
+        None => Err(GdalError::BadArgument(format!(
+            "{which} dataset has no alpha band to premultiply with"
+        ))),
+    }
+}
+
+/// Add the polygons of `geometry` to the `polygons` multipolygon.
+fn add_polygons(polygons: &mut Geometry, geometry: &Geometry) -> Result<()> {
+    match unsafe { gdal_sys::OGR_GT_Flatten(geometry.geometry_type()) } {
+        OGRwkbGeometryType::wkbPolygon => polygons.add_geometry(geometry.clone()),
+        OGRwkbGeometryType::wkbMultiPolygon => (0..geometry.geometry_count())
+            .try_for_each(|i| polygons.add_geometry((*geometry.get_geometry(i)).clone())),
+        _ => Err(GdalError::BadArgument(format!(
+            "cutline must be made of polygons, got a {}",
+            geometry.geometry_name()
+        ))),
+    }
+}
+
+impl Default for GdalWarpOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for GdalWarpOptions {
+    fn clone(&self) -> Self {
+        let c_options = unsafe { gdal_sys::GDALCloneWarpOptions(self.c_options()) };
+        Self {
+            c_options: NonNull::new(c_options).expect("GDALCloneWarpOptions"),
+            transformer_cache: self.transformer_cache,
+            explicit_memory_limit: self.explicit_memory_limit,
+            cutline: self.cutline.clone(),
+            cutline_srs: self.cutline_srs.clone(),
+            premultiply_alpha: self.premultiply_alpha,
+            dst_alpha: self.dst_alpha,
+            custom_transformer: self.custom_transformer.clone(),
+            coordinate_operation: self.coordinate_operation.clone(),
+            transformer_options: self.transformer_options.clone(),
+            overview_resampling: self.overview_resampling,
+            warp_chunk_size: self.warp_chunk_size,
+        }
+    }
+}
+
+impl Drop for GdalWarpOptions {
+    fn drop(&mut self) {
+        unsafe { gdal_sys::GDALDestroyWarpOptions(self.c_options()) };
+    }
+}
+
+impl Debug for GdalWarpOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let c_options = self.c_ref();
+        f.debug_struct("GdalWarpOptions")
+            .field("resampling_alg", &self.resampling_alg())
+            .field("overview_resampling", &self.overview_resampling)
+            .field("memory_limit", &self.memory_limit())
+            .field("working_data_type", &self.working_data_type())
+            .field("transformer_cache", &self.transformer_cache)
+            .field("cutline", &self.cutline)
+            .field("cutline_srs", &self.cutline_srs)
+            .field("cutline_blend_dist", &c_options.dfCutlineBlendDist)
+            .field("premultiply_alpha", &self.premultiply_alpha)
+            .field("dst_alpha", &self.dst_alpha)
+            .field("custom_transformer", &self.custom_transformer.is_some())
+            .field("coordinate_operation", &self.coordinate_operation)
+            .field("transformer_options", &self.transformer_options)
+            .field("warp_chunk_size", &self.warp_chunk_size)
+            .field("band_count", &c_options.nBandCount)
+            .field("src_alpha_band", &c_options.nSrcAlphaBand)
+            .field("dst_alpha_band", &c_options.nDstAlphaBand)
+            .finish()
+    }
+}
+
+/// Owned builder of [`GdalWarpOptions`], as returned by [`GdalWarpOptions::builder`], so that
+/// a fluent chain of setters can be bound with `let`.
+///
+/// Each setter is the owned counterpart of the `with_*` setter of [`GdalWarpOptions`] of the
+/// same name (e.g. [`resampling_alg`](Self::resampling_alg) for
+/// [`GdalWarpOptions::with_resampling_alg`]), and [`build`](Self::build) returns the options.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() -> gdal::errors::Result<()> {
+/// use gdal::raster::{GdalWarpOptions, NumThreads, WarpResampleAlg};
+/// let options = GdalWarpOptions::builder()
+///     .resampling_alg(WarpResampleAlg::Cubic)
+///     .num_threads(NumThreads::AllCpus)?
+///     .build();
+///
+/// let mut expected = GdalWarpOptions::new();
+/// expected.with_resampling_alg(WarpResampleAlg::Cubic);
+/// expected.with_num_threads(NumThreads::AllCpus)?;
+/// assert_eq!(options.resampling_alg(), expected.resampling_alg());
+/// assert_eq!(options.num_threads(), expected.num_threads());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct GdalWarpOptionsBuilder(GdalWarpOptions);
+
+impl GdalWarpOptionsBuilder {
+    /// The options set.
+    pub fn build(self) -> GdalWarpOptions {
+        self.0
+    }
+}
+
+owned_setters! {
+    GdalWarpOptionsBuilder => GdalWarpOptions {
+        fn resampling_alg(alg: WarpResampleAlg) => with_resampling_alg;
+        fn overview_resampling(alg: WarpResampleAlg) => with_overview_resampling;
+        fn memory_limit(limit: usize) => with_memory_limit;
+        fn memory_limit_percent(pct: f64) => with_memory_limit_percent;
+        fn working_data_type(data_type: GdalDataType) => with_working_data_type;
+        fn transformer_cache(enabled: bool) => with_transformer_cache;
+        fn premultiply_alpha(premultiply: bool) => with_premultiply_alpha;
+        fn dst_alpha_band(dst_alpha: bool) => with_dst_alpha_band;
+        fn custom_transformer(
+            transformer: impl Fn(&mut [f64], &mut [f64], &mut [f64]) -> Vec<bool>
+                + Send
+                + Sync
+                + 'static
+        ) => with_custom_transformer;
+        fn coordinate_operation(epsg_or_pipeline: &str) => with_coordinate_operation;
+        fn cutline_srs(srs: &SpatialRef) => with_cutline_srs;
+        fn cutline_blend_dist(pixels: f64) => with_cutline_blend_dist;
+    }
+    fallible {
+        fn transformer_option(key: &str, value: &str) => with_transformer_option;
+        fn rpc() => with_rpc;
+        fn rpc_dem(path: impl AsRef<Path>) => with_rpc_dem;
+        fn vertical_shift(enabled: bool) => with_vertical_shift;
+        fn cutline(cutline: &Geometry) => with_cutline;
+        fn cutline_from_layer(
+            layer: &mut Layer,
+            where_clause: Option<&str>
+        ) => with_cutline_from_layer;
+        fn warp_option(key: &str, value: &str) => with_warp_option;
+        fn sample_steps(steps: usize) => with_sample_steps;
+        fn num_threads(threads: NumThreads) => with_num_threads;
+        fn init_dest(init_dest: InitDest) => with_init_dest;
+        fn sample_grid(grid: bool) => with_sample_grid;
+        fn auto_tile_from_source(source: &Dataset) => with_auto_tile_from_source;
+        fn warp_chunk_size(x_size: usize, y_size: usize) => with_warp_chunk_size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raster::{RasterCreationOption, ReprojectIntoOptions};
+    use crate::test_utils::TempFixture;
+    use crate::DriverManager;
+
+    #[test]
+    fn test_memory_limit_percent() {
+        let mut opts = GdalWarpOptions::new();
+        opts.with_memory_limit_percent(0.0);
+        assert_eq!(opts.memory_limit(), GdalWarpOptions::MIN_MEMORY_LIMIT);
+        opts.with_memory_limit_percent(f64::NAN);
+        assert_eq!(opts.memory_limit(), GdalWarpOptions::MIN_MEMORY_LIMIT);
+
+        let ram = unsafe { gdal_sys::CPLGetUsablePhysicalRAM() } as usize;
+        opts.with_memory_limit_percent(100.0);
+        assert_eq!(
+            opts.memory_limit(),
+            ram.max(GdalWarpOptions::MIN_MEMORY_LIMIT)
+        );
+        opts.with_memory_limit_percent(1e300);
+        assert_eq!(
+            opts.memory_limit(),
+            ram.max(GdalWarpOptions::MIN_MEMORY_LIMIT)
+        );
+
+        // An absolute limit wins, whatever the order.
+        opts.with_memory_limit(1 << 20)
+            .with_memory_limit_percent(50.0);
+        assert_eq!(opts.memory_limit(), 1 << 20);
+        assert_eq!(
+            opts.clone().with_memory_limit_percent(50.0).memory_limit(),
+            1 << 20
+        );
+    }
+
+    #[test]
+    fn test_build() -> Result<()> {
+        use crate::raster::{CreateReprojectOptions, ReprojectIntoOptions};
+
+        let opts = GdalWarpOptions::builder()
+            .resampling_alg(WarpResampleAlg::Cubic)
+            .num_threads(NumThreads::Count(2))?
+            .build();
+        assert_eq!(opts.resampling_alg(), WarpResampleAlg::Cubic);
+        assert_eq!(opts.num_threads(), Some(NumThreads::Count(2)));
+        assert!(GdalWarpOptions::builder().sample_steps(0).is_err());
+
+        let reproject_opts = CreateReprojectOptions::builder()
+            .dst_nodata(0.0)
+            .warp_options(opts)
+            .target_resolution(10.0, 10.0)
+            .build();
+        assert_eq!(reproject_opts.dst_nodata(), Some(0.0));
+        assert_eq!(reproject_opts.target_resolution(), Some((10.0, 10.0)));
+        assert_eq!(
+            reproject_opts.warp_options().resampling_alg(),
+            WarpResampleAlg::Cubic
+        );
+
+        let into_opts = ReprojectIntoOptions::builder()
+            .max_error(0.5)
+            .dst_band_offset(1)
+            .build();
+        assert_eq!(into_opts.max_error(), Some(0.5));
+        assert_eq!(into_opts.dst_band_offset(), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample_steps() -> Result<()> {
+        let mut opts = GdalWarpOptions::new();
+        assert_eq!(opts.sample_steps(), None);
+        assert!(!opts.sample_grid());
+        opts.with_sample_steps(100)?.with_sample_grid(true)?;
+        assert_eq!(opts.sample_steps(), Some(100));
+        assert_eq!(opts.warp_option("SAMPLE_STEPS").as_deref(), Some("100"));
+        assert!(opts.sample_grid());
+        assert!(opts.with_sample_steps(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_warp_options_accessors() -> Result<()> {
+        let mut opts = GdalWarpOptions::new();
+        assert_eq!(opts.resampling_alg(), WarpResampleAlg::NearestNeighbour);
+        opts.with_resampling_alg(WarpResampleAlg::Cubic)
+            .with_memory_limit(1 << 26)
+            .with_working_data_type(GdalDataType::Float32)
+            .with_transformer_cache(false)
+            .with_warp_option("SKIP_NOSOURCE", "YES")?;
+
+        let opts = opts.clone();
+        assert!(!opts.transformer_cache());
+        assert_eq!(opts.resampling_alg(), WarpResampleAlg::Cubic);
+        assert_eq!(opts.memory_limit(), 1 << 26);
+        assert_eq!(opts.working_data_type(), GdalDataType::Float32);
+        assert_eq!(opts.warp_option("SKIP_NOSOURCE"), Some("YES".into()));
+        assert_eq!(opts.warp_option("INIT_DEST"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_nodata_requires_bands() -> Result<()> {
+        let mut opts = GdalWarpOptions::new();
+        assert!(opts.apply_src_nodata(0.0).is_err());
+
+        opts.init_default_band_mapping(3);
+        opts.apply_src_nodata(255.0)?;
+        opts.apply_dst_nodata(0.0)?;
+        let c_options = opts.c_ref();
+        let src = unsafe { std::slice::from_raw_parts(c_options.padfSrcNoDataReal, 3) };
+        let dst = unsafe { std::slice::from_raw_parts(c_options.padfDstNoDataReal, 3) };
+        assert_eq!(src, [255.0; 3]);
+        assert_eq!(dst, [0.0; 3]);
+
+        assert!(opts.apply_dst_nodata_complex(4, 1.0, 2.0).is_err());
+        opts.apply_dst_nodata_complex(2, 1.0, 2.0)?;
+        let c_options = opts.c_ref();
+        let dst = unsafe { std::slice::from_raw_parts(c_options.padfDstNoDataReal, 3) };
+        let dst_imag = unsafe { std::slice::from_raw_parts(c_options.padfDstNoDataImag, 3) };
+        assert_eq!(dst, [0.0, 1.0, 0.0]);
+        assert_eq!(dst_imag, [0.0, 2.0, 0.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transformer_options() -> Result<()> {
+        let mut opts = GdalWarpOptions::new();
+        assert!(opts.transformer_options().is_empty());
+        opts.with_transformer_option("RPC_HEIGHT", "100")?;
+        assert!(opts.with_transformer_option("RPC=HEIGHT", "100").is_err());
+
+        let opts = opts.clone();
+        assert_eq!(opts.transformer_option("RPC_HEIGHT"), Some("100".into()));
+        assert_eq!(opts.transformer_option("METHOD"), None);
+        let entries = opts
+            .transformer_options()
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(entries, ["RPC_HEIGHT=100"]);
+
+        let mut opts = GdalWarpOptions::new();
+        opts.with_rpc_dem("/vsimem/dem.tif")?;
+        assert_eq!(opts.transformer_option("METHOD"), Some("RPC".into()));
+        assert_eq!(
+            opts.transformer_option("RPC_DEM"),
+            Some("/vsimem/dem.tif".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_tile_from_source() -> Result<()> {
+        let tmp = TempFixture::empty("tiled.tif");
+        let driver = DriverManager::get_driver_by_name("GTiff")?;
+        let ds = driver.create_with_band_type_with_options::<u8, _>(
+            tmp.path(),
+            1024,
+            1024,
+            3,
+            &[
+                RasterCreationOption {
+                    key: "TILED",
+                    value: "YES",
+                },
+                RasterCreationOption {
+                    key: "BLOCKXSIZE",
+                    value: "512",
+                },
+                RasterCreationOption {
+                    key: "BLOCKYSIZE",
+                    value: "256",
+                },
+            ],
+        )?;
+        assert_eq!(ds.rasterband(1)?.block_size(), (512, 256));
+
+        let mut opts = ReprojectIntoOptions::new();
+        opts.warp_options_mut().with_auto_tile_from_source(&ds)?;
+        assert_eq!(opts.warp_options().warp_chunk_size(), Some((512, 256)));
+        let mem_driver = DriverManager::get_driver_by_name("MEM")?;
+        let dest = mem_driver.create_with_band_type::<u8, _>("", 1024, 1024, 3)?;
+        crate::raster::reproject_into(&ds, &dest, &opts)?;
+
+        let mem = mem_driver.create("", 1, 1, 0)?;
+        assert!(GdalWarpOptions::new()
+            .with_auto_tile_from_source(&mem)
+            .is_err());
+        Ok(())
+    }
+}
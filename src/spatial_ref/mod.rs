@@ -15,4 +15,5 @@ pub type AxisOrientationType = gdal_sys::OGRAxisOrientation::Type;
 
 pub use srs::SpatialRef;
 pub use transform::CoordTransform;
+pub(crate) use transform_opts::coordinate_operation_definition;
 pub use transform_opts::CoordTransformOptions;
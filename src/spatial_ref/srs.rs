@@ -1,6 +1,7 @@
-use crate::utils::{_last_null_pointer_err, _string};
-use gdal_sys::{self, OGRErr};
+use crate::utils::{_last_cpl_err, _last_null_pointer_err, _path_to_c_string, _string};
+use gdal_sys::{self, CPLErr, OGRErr};
 use std::ffi::{CStr, CString};
+use std::path::Path;
 use std::ptr::{self};
 use std::str::FromStr;
 
@@ -33,7 +34,7 @@ impl Clone for SpatialRef {
 
 impl PartialEq for SpatialRef {
     fn eq(&self, other: &SpatialRef) -> bool {
-        unsafe { gdal_sys::OSRIsSame(self.0, other.0) == 1 }
+        self.is_same(other)
     }
 }
 
@@ -110,19 +111,37 @@ impl SpatialRef {
         }
     }
 
+    /// Create a spatial reference from a PROJ.4 string, e.g.
+    /// `+proj=utm +zone=31 +datum=WGS84 +units=m +no_defs`.
+    ///
+    /// Fails with the error message reported by GDAL (as a [`GdalError::CplError`]) if the
+    /// string can't be parsed, or doesn't describe a coordinate reference system PROJ knows
+    /// of, or with a [`GdalError::OgrError`] if GDAL reported none.
+    ///
+    /// See [`OSRImportFromProj4`](https://gdal.org/api/ogrspatialref.html#_CPPv4N19OGRSpatialReference15importFromProj4EPKc).
     pub fn from_proj4(proj4_string: &str) -> Result<SpatialRef> {
         let c_str = CString::new(proj4_string)?;
-        let null_ptr = ptr::null_mut();
-        let c_obj = unsafe { gdal_sys::OSRNewSpatialReference(null_ptr) };
+        let c_obj = unsafe { gdal_sys::OSRNewSpatialReference(ptr::null()) };
+        if c_obj.is_null() {
+            return Err(_last_null_pointer_err("OSRNewSpatialReference"));
+        }
+        // Released on error.
+        let spatial_ref = SpatialRef(c_obj);
+        // Clear any error left over by an earlier call, so that it isn't taken as the reason
+        // for a failure to import.
+        unsafe { gdal_sys::CPLErrorReset() };
         let rv = unsafe { gdal_sys::OSRImportFromProj4(c_obj, c_str.as_ptr()) };
         if rv != OGRErr::OGRERR_NONE {
-            Err(GdalError::OgrError {
+            let err_class = unsafe { gdal_sys::CPLGetLastErrorType() };
+            if err_class != CPLErr::CE_None {
+                return Err(_last_cpl_err(err_class));
+            }
+            return Err(GdalError::OgrError {
                 err: rv,
                 method_name: "OSRImportFromProj4",
-            })
-        } else {
-            Ok(SpatialRef(c_obj))
+            });
         }
+        Ok(spatial_ref)
     }
 
     pub fn from_esri(esri_wkt: &str) -> Result<SpatialRef> {
@@ -141,6 +160,53 @@ impl SpatialRef {
         }
     }
 
+    /// Create a spatial reference from WKT in the ESRI flavor, as found in
+    /// the `.prj` sidecar files written by ArcGIS.
+    ///
+    /// ESRI names for datums, projections and parameters are mapped to their
+    /// standard equivalents. See [`OSRImportFromESRI`](https://gdal.org/api/ogrspatialref.html#_CPPv4N19OGRSpatialReference14importFromESRIEPPc).
+    pub fn from_esri_wkt(esri_wkt: &str) -> Result<SpatialRef> {
+        Self::from_esri(esri_wkt)
+    }
+
+    /// Create a spatial reference from a `.prj` file, such as the sidecar of ESRI shapefiles,
+    /// or of rasters georeferenced with a world file.
+    ///
+    /// Both WKT (in either the ESRI or OGC flavor) and the older multi-line ESRI `.prj`
+    /// format are supported.
+    pub fn from_prj_file<P: AsRef<Path>>(path: P) -> Result<SpatialRef> {
+        let c_path = _path_to_c_string(path.as_ref())?;
+        let lines = unsafe { gdal_sys::CSLLoad(c_path.as_ptr()) };
+        if lines.is_null() {
+            return Err(_last_null_pointer_err("CSLLoad"));
+        }
+        let c_obj = unsafe { gdal_sys::OSRNewSpatialReference(ptr::null()) };
+        if c_obj.is_null() {
+            unsafe { gdal_sys::CSLDestroy(lines) };
+            return Err(_last_null_pointer_err("OSRNewSpatialReference"));
+        }
+        let rv = unsafe { gdal_sys::OSRImportFromESRI(c_obj, lines) };
+        unsafe { gdal_sys::CSLDestroy(lines) };
+        if rv != OGRErr::OGRERR_NONE {
+            unsafe { gdal_sys::OSRRelease(c_obj) };
+            return Err(GdalError::OgrError {
+                err: rv,
+                method_name: "OSRImportFromESRI",
+            });
+        }
+        Ok(SpatialRef(c_obj))
+    }
+
+    /// Export the spatial reference as WKT in the ESRI flavor, suitable for
+    /// writing `.prj` sidecar files read by ArcGIS.
+    ///
+    /// `self` is left unchanged; see [`SpatialRef::morph_to_esri`] for the in-place version.
+    pub fn to_esri_wkt(&self) -> Result<String> {
+        let esri = self.clone();
+        esri.morph_to_esri()?;
+        esri.to_wkt()
+    }
+
     pub fn to_wkt(&self) -> Result<String> {
         let mut c_wkt = ptr::null_mut();
         let rv = unsafe { gdal_sys::OSRExportToWkt(self.0, &mut c_wkt) };
@@ -269,6 +335,25 @@ impl SpatialRef {
         Ok(format!("{name}:{code}"))
     }
 
+    /// Check that this spatial reference is structurally complete and consistent, e.g. before
+    /// using a spatial reference imported from WKT as a warp target.
+    ///
+    /// See: [`OSRValidate`](https://gdal.org/api/ogr_srs_api.html#_CPPv411OSRValidate20OGRSpatialReferenceH)
+    pub fn validate(&self) -> Result<()> {
+        let rv = unsafe { gdal_sys::OSRValidate(self.0) };
+        if rv != OGRErr::OGRERR_NONE {
+            return Err(GdalError::OgrError {
+                err: rv,
+                method_name: "OSRValidate",
+            });
+        }
+        Ok(())
+    }
+
+    /// Set the EPSG authority code of this spatial reference (and of its parts) when it can be
+    /// identified as a well known one, e.g. after importing a WKT missing its `AUTHORITY` node.
+    ///
+    /// See: [`OSRAutoIdentifyEPSG`](https://gdal.org/api/ogr_srs_api.html#_CPPv419OSRAutoIdentifyEPSG20OGRSpatialReferenceH)
     pub fn auto_identify_epsg(&mut self) -> Result<()> {
         let rv = unsafe { gdal_sys::OSRAutoIdentifyEPSG(self.0) };
         if rv != OGRErr::OGRERR_NONE {
@@ -316,6 +401,22 @@ impl SpatialRef {
         unsafe { gdal_sys::OSRGetLinearUnits(self.0, ptr::null_mut()) }
     }
 
+    /// Whether this spatial reference describes the same coordinate reference system as
+    /// `other`, e.g. one built from an EPSG code and the other from the equivalent WKT.
+    ///
+    /// This is what `==` compares. See [`OSRIsSame`](https://gdal.org/api/ogrspatialref.html#_CPPv4NK19OGRSpatialReference6IsSameEPK19OGRSpatialReference).
+    pub fn is_same(&self, other: &SpatialRef) -> bool {
+        unsafe { gdal_sys::OSRIsSame(self.0, other.0) == 1 }
+    }
+
+    /// Whether this spatial reference and `other` have the same geographic coordinate system,
+    /// i.e. the same datum, prime meridian and angular units, whatever their projections.
+    ///
+    /// See [`OSRIsSameGeogCS`](https://gdal.org/api/ogrspatialref.html#_CPPv4NK19OGRSpatialReference12IsSameGeogCSEPK19OGRSpatialReference).
+    pub fn is_same_geogcs(&self, other: &SpatialRef) -> bool {
+        unsafe { gdal_sys::OSRIsSameGeogCS(self.0, other.0) == 1 }
+    }
+
     #[inline]
     pub fn is_geographic(&self) -> bool {
         unsafe { gdal_sys::OSRIsGeographic(self.0) == 1 }
@@ -610,6 +711,7 @@ pub struct AreaOfUse {
 mod tests {
     use super::*;
     use crate::assert_almost_eq;
+    use crate::test_utils::SuppressGDALErrorLog;
 
     #[test]
     fn from_wkt_to_proj4() {
@@ -639,6 +741,36 @@ mod tests {
         assert_eq!(spatial_ref, round_trip);
     }
 
+    #[test]
+    fn is_same() {
+        let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+        let utm = SpatialRef::from_epsg(32631).unwrap();
+        let nad27 = SpatialRef::from_epsg(4267).unwrap();
+        assert!(wgs84.is_same(&SpatialRef::from_wkt(&wgs84.to_wkt().unwrap()).unwrap()));
+        assert!(!wgs84.is_same(&utm));
+        assert!(wgs84.is_same_geogcs(&utm));
+        assert!(!wgs84.is_same_geogcs(&nad27));
+    }
+
+    #[test]
+    fn from_proj4_malformed() {
+        let _nolog = SuppressGDALErrorLog::new();
+        for proj4_string in [
+            "+proj=not_a_projection +no_defs",
+            "+proj=utm +zone=31 +ellps=nope",
+        ] {
+            // With the message reported by GDAL, if any.
+            match SpatialRef::from_proj4(proj4_string) {
+                Err(GdalError::CplError { msg, .. }) => assert!(!msg.is_empty()),
+                Err(GdalError::OgrError {
+                    method_name: "OSRImportFromProj4",
+                    ..
+                }) => {}
+                result => panic!("unexpected result: {result:?}"),
+            }
+        }
+    }
+
     #[test]
     fn from_epsg_to_wkt_proj4() {
         let spatial_ref = SpatialRef::from_epsg(4326).unwrap();
@@ -674,6 +806,46 @@ mod tests {
         assert_eq!("+proj=longlat +datum=WGS84 +no_defs", proj4string.trim());
     }
 
+    #[test]
+    fn esri_wkt_round_trip() {
+        // NAD83 / California zone 3 (ftUS)
+        let spatial_ref = SpatialRef::from_epsg(2227).unwrap();
+        let esri_wkt = spatial_ref.to_esri_wkt().unwrap();
+        assert!(
+            esri_wkt.contains("NAD_1983_StatePlane_California_III_FIPS_0403_Feet"),
+            "{esri_wkt:?} does not contain the ESRI CRS name",
+        );
+        assert!(esri_wkt.contains("D_North_American_1983"));
+        // `self` is not morphed.
+        assert!(!spatial_ref
+            .to_wkt()
+            .unwrap()
+            .contains("D_North_American_1983"));
+
+        let round_trip = SpatialRef::from_esri_wkt(&esri_wkt).unwrap();
+        assert_eq!(
+            round_trip.to_proj4().unwrap().trim(),
+            spatial_ref.to_proj4().unwrap().trim()
+        );
+        assert_eq!(round_trip.to_esri_wkt().unwrap(), esri_wkt);
+    }
+
+    #[test]
+    fn from_prj_file() {
+        let prj = crate::test_utils::TempFixture::empty("labels.prj");
+        let spatial_ref = SpatialRef::from_epsg(2227).unwrap();
+        std::fs::write(prj.path(), spatial_ref.to_esri_wkt().unwrap()).unwrap();
+
+        let from_prj = SpatialRef::from_prj_file(prj.path()).unwrap();
+        assert_eq!(
+            from_prj.to_proj4().unwrap().trim(),
+            spatial_ref.to_proj4().unwrap().trim()
+        );
+
+        let _nolog = crate::test_utils::SuppressGDALErrorLog::new();
+        assert!(SpatialRef::from_prj_file("/does/not/exist.prj").is_err());
+    }
+
     #[test]
     fn comparison() {
         let spatial_ref1 = SpatialRef::from_wkt("GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563,AUTHORITY[\"EPSG\",7030]],TOWGS84[0,0,0,0,0,0,0],AUTHORITY[\"EPSG\",6326]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",8901]],UNIT[\"DMSH\",0.0174532925199433,AUTHORITY[\"EPSG\",9108]],AXIS[\"Lat\",NORTH],AXIS[\"Long\",EAST],AUTHORITY[\"EPSG\",4326]]").unwrap();
@@ -743,6 +915,14 @@ mod tests {
         assert!(spatial_ref.auth_code().is_err());
         spatial_ref.auto_identify_epsg().unwrap();
         assert_eq!(spatial_ref.auth_code().unwrap(), 32632);
+        spatial_ref.validate().unwrap();
+    }
+
+    #[test]
+    fn validate() {
+        SpatialRef::from_epsg(4326).unwrap().validate().unwrap();
+        let _nolog = SuppressGDALErrorLog::new();
+        assert!(SpatialRef::new().unwrap().validate().is_err());
     }
 
     #[cfg(major_ge_3)]
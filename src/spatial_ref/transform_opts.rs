@@ -153,6 +153,32 @@ impl CoordTransformOptions {
         }
         Ok(())
     }
+
+    /// Forces the coordinate operation, given either by its EPSG code (e.g. `"EPSG:1173"` or
+    /// `"1173"`), or as any definition accepted by
+    /// [`set_coordinate_operation`](Self::set_coordinate_operation), such as a PROJ pipeline.
+    ///
+    /// This makes datum transformations reproducible, as PROJ otherwise picks the operation
+    /// it deems best among the available ones, which depends on the installed grids.
+    pub fn with_coordinate_operation(&mut self, epsg_or_pipeline: &str) -> Result<&mut Self> {
+        self.set_coordinate_operation(&coordinate_operation_definition(epsg_or_pipeline), false)?;
+        Ok(self)
+    }
+}
+
+/// The definition of the coordinate operation `epsg_or_pipeline`, turning EPSG codes
+/// (`EPSG:XXXX` or `XXXX`) into URNs, as expected by GDAL, and leaving anything else as is.
+pub(crate) fn coordinate_operation_definition(epsg_or_pipeline: &str) -> String {
+    let operation = epsg_or_pipeline.trim();
+    let code = match operation.get(..5) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("EPSG:") => &operation[5..],
+        _ => operation,
+    };
+    if !code.is_empty() && code.bytes().all(|b| b.is_ascii_digit()) {
+        format!("urn:ogc:def:coordinateOperation:EPSG::{code}")
+    } else {
+        operation.to_owned()
+    }
 }
 
 #[cfg(test)]
@@ -190,4 +216,36 @@ mod tests {
         let trafo = CoordTransform::new_with_options(&nad27, &wgs84, &options);
         assert!(trafo.is_ok());
     }
+
+    #[test]
+    fn with_coordinate_operation() {
+        assert_eq!(
+            coordinate_operation_definition("EPSG:1172"),
+            "urn:ogc:def:coordinateOperation:EPSG::1172"
+        );
+        assert_eq!(
+            coordinate_operation_definition("1172"),
+            "urn:ogc:def:coordinateOperation:EPSG::1172"
+        );
+        assert_eq!(coordinate_operation_definition("+proj=noop"), "+proj=noop");
+
+        // NAD27 has several transformations to WGS 84, depending on the area: the one for
+        // Canada (EPSG:1172) isn't the default for a point in Kansas.
+        let nad27 = SpatialRef::from_epsg(4267).unwrap();
+        let wgs84 = SpatialRef::from_epsg(4326).unwrap();
+        let transform = |operation: Option<&str>| {
+            let mut options = CoordTransformOptions::new().unwrap();
+            if let Some(operation) = operation {
+                options.with_coordinate_operation(operation).unwrap();
+            }
+            let trafo = CoordTransform::new_with_options(&nad27, &wgs84, &options).unwrap();
+            let (mut lat, mut lon) = ([39.0], [-98.0]);
+            trafo.transform_coords(&mut lat, &mut lon, &mut []).unwrap();
+            (lat[0], lon[0])
+        };
+        let default = transform(None);
+        let canada = transform(Some("EPSG:1172"));
+        assert_ne!(default, canada);
+        assert_eq!(transform(Some("1172")), canada);
+    }
 }
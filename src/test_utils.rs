@@ -186,3 +186,59 @@ macro_rules! assert_near {
         );
     };
 }
+
+/// A chunk of a warp: `(x_off, y_off, x_size, y_size)` in destination pixels.
+pub(crate) type WarpChunk = (usize, usize, usize, usize);
+
+/// The chunks `dest` is warped in from `source` with `opts`, sorted, as seen by the identity
+/// transformer the warp is made with.
+///
+/// The warp kernel transforms the destination pixels one row at a time, while the source
+/// window of a region is computed by transforming points along its edges, for every region
+/// the output is recursively split into, the chunks being the smallest ones.
+pub(crate) fn warp_chunks(
+    source: &Dataset,
+    dest: &Dataset,
+    opts: &mut crate::raster::ReprojectIntoOptions,
+) -> crate::errors::Result<Vec<WarpChunk>> {
+    use std::sync::{Arc, Mutex};
+
+    let regions = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&regions);
+    opts.warp_options_mut()
+        .with_custom_transformer(move |x, y, _| {
+            let bounds = |values: &[f64]| {
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (min, max)
+            };
+            let (min_y, max_y) = bounds(y);
+            if min_y < max_y {
+                let (min_x, max_x) = bounds(x);
+                recorder.lock().unwrap().push((
+                    min_x as usize,
+                    min_y as usize,
+                    (max_x - min_x) as usize,
+                    (max_y - min_y) as usize,
+                ));
+            }
+            vec![true; x.len()]
+        });
+    crate::raster::reproject_into(source, dest, opts)?;
+
+    let mut regions: Vec<WarpChunk> = regions.lock().unwrap().clone();
+    regions.sort_unstable();
+    regions.dedup();
+    let contains = |outer: &WarpChunk, inner: &WarpChunk| {
+        outer != inner
+            && inner.0 >= outer.0
+            && inner.1 >= outer.1
+            && inner.0 + inner.2 <= outer.0 + outer.2
+            && inner.1 + inner.3 <= outer.1 + outer.3
+    };
+    Ok(regions
+        .iter()
+        .filter(|region| !regions.iter().any(|other| contains(region, other)))
+        .copied()
+        .collect())
+}
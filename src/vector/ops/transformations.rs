@@ -6,6 +6,7 @@ use crate::spatial_ref::CoordTransform;
 use crate::spatial_ref::SpatialRef;
 use crate::utils::_last_null_pointer_err;
 use crate::vector::Geometry;
+use crate::version::VersionInfo;
 
 /// # Geometry Transformations
 ///
@@ -80,6 +81,22 @@ impl Geometry {
         Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
     }
 
+    /// Compute a point guaranteed to lie on the surface of this geometry, e.g. to place a label
+    /// inside a concave polygon, which its centroid may fall outside of.
+    ///
+    /// # Notes
+    /// This function requires the GEOS library.
+    /// If OGR is built without the GEOS library, this function will always fail.
+    ///
+    /// See: [`OGR_G_PointOnSurface`](https://gdal.org/api/vector_c_api.html#_CPPv420OGR_G_PointOnSurface12OGRGeometryH)
+    pub fn point_on_surface(&self) -> Result<Geometry> {
+        let c_geom = unsafe { gdal_sys::OGR_G_PointOnSurface(self.c_geometry()) };
+        if c_geom.is_null() {
+            return Err(_last_null_pointer_err("OGR_G_PointOnSurface"));
+        };
+        Ok(unsafe { Geometry::with_c_geometry(c_geom, true) })
+    }
+
     #[cfg(any(all(major_is_2, minor_ge_1), major_ge_3))]
     /// Return a [Delaunay triangulation of][dt] the vertices of the geometry.
     ///
@@ -173,6 +190,10 @@ impl Geometry {
     ///
     /// When GEOS < 3.8, this method will return `Ok(self.clone())` if it is valid, or `Err` if not.
     ///
+    /// # Notes
+    /// This function requires the GEOS library, and returns `Err` if OGR is built without it.
+    /// Check with [`VersionInfo::has_geos`][has_geos].
+    ///
     /// See: [OGR_G_MakeValidEx](https://gdal.org/api/vector_c_api.html#_CPPv417OGR_G_MakeValidEx12OGRGeometryH12CSLConstList)
     ///
     /// # Example
@@ -186,7 +207,15 @@ impl Geometry {
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// [has_geos]: crate::version::VersionInfo::has_geos
     pub fn make_valid(&self, opts: &CslStringList) -> Result<Geometry> {
+        if !VersionInfo::has_geos() {
+            return Err(GdalError::BadArgument(
+                "make_valid requires GDAL to be built with GEOS support".into(),
+            ));
+        }
+
         #[cfg(all(major_ge_3, minor_ge_4))]
         let c_geom = unsafe { gdal_sys::OGR_G_MakeValidEx(self.c_geometry(), opts.as_ptr()) };
 
@@ -239,6 +268,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_point_on_surface() -> Result<()> {
+        // A "U", whose centroid is in the gap between its arms.
+        let polygon =
+            Geometry::from_wkt("POLYGON ((0 0, 3 0, 3 3, 2 3, 2 1, 1 1, 1 3, 0 3, 0 0))")?;
+        let centroid = Geometry::from_wkt("POINT (1.5 1.357142857142857)")?;
+        assert!(!polygon.contains(&centroid));
+
+        let point = polygon.point_on_surface()?;
+        assert_eq!(
+            point.geometry_type(),
+            ::gdal_sys::OGRwkbGeometryType::wkbPoint
+        );
+        assert!(polygon.contains(&point));
+
+        let envelope = polygon.envelope();
+        assert_eq!(
+            (envelope.MinX, envelope.MinY, envelope.MaxX, envelope.MaxY),
+            (0.0, 0.0, 3.0, 3.0)
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_simplify() -> Result<()> {
         let line = Geometry::from_wkt("LINESTRING(1.2 0.19,1.63 0.58,1.98 0.65,2.17 0.89)")?;
@@ -292,7 +344,15 @@ mod tests {
         assert!(!src.is_valid());
         let dst = src.make_valid(&CslStringList::new());
         assert!(dst.is_ok());
-        assert!(dst.unwrap().is_valid());
+        let dst = dst.unwrap();
+        assert!(dst.is_valid());
+        // The bowtie is split into its two triangles.
+        assert_eq!(
+            dst.geometry_type(),
+            ::gdal_sys::OGRwkbGeometryType::wkbMultiPolygon
+        );
+        assert_eq!(dst.geometry_count(), 2);
+        assert!((dst.area() - 50.0).abs() < 1e-9);
     }
 
     #[cfg(all(major_ge_3, minor_ge_4))]
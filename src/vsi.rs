@@ -190,6 +190,42 @@ where
     Ok(fun(slice))
 }
 
+/// Size and modification time of a file, as returned by [`stat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VsiStat {
+    /// Size of the file, in bytes.
+    pub size: u64,
+    /// Last modification time, in seconds since the Unix epoch.
+    pub modified: i64,
+}
+
+/// The `VSIStatBufL` type of the platform, which the GDAL bindings leave opaque.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+type StatBuf = libc::stat64;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+type StatBuf = libc::stat;
+
+/// Fetch the size and modification time of `path`, which may be on any virtual file system.
+///
+/// Returns `Ok(None)` if there is no such file.
+///
+/// See: [`VSIStatL`](https://gdal.org/api/cpl.html#_CPPv48VSIStatLPKcP11VSIStatBufL)
+pub fn stat<P: AsRef<Path>>(path: P) -> Result<Option<VsiStat>> {
+    let path = _path_to_c_string(path.as_ref())?;
+    let mut buf = std::mem::MaybeUninit::<StatBuf>::zeroed();
+    let rv = unsafe { gdal_sys::VSIStatL(path.as_ptr(), buf.as_mut_ptr().cast()) };
+    if rv != 0 {
+        return Ok(None);
+    }
+    let buf = unsafe { buf.assume_init() };
+    // `off_t` and `time_t` are narrower on some platforms.
+    #[allow(clippy::unnecessary_cast)]
+    Ok(Some(VsiStat {
+        size: buf.st_size as u64,
+        modified: buf.st_mtime as i64,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,4 +352,16 @@ mod tests {
         // Attempting to read without VSI prefix returns error.
         assert!(read_dir(zip_path, false).is_err());
     }
+
+    #[test]
+    fn test_stat() {
+        let file_name = "/vsimem/stat_test.bin";
+        assert_eq!(stat(file_name).unwrap(), None);
+
+        create_mem_file(file_name, vec![0_u8; 42]).unwrap();
+        let file_stat = stat(file_name).unwrap().unwrap();
+        assert_eq!(file_stat.size, 42);
+        assert!(file_stat.modified > 0);
+        unlink_mem_file(file_name).unwrap();
+    }
 }